@@ -1,6 +1,6 @@
 pub use self::events::{
     DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineHintsEvent,
-    NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+    NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent, VariableChangedEvent,
 };
 pub use self::{
     builder::DialogueRunnerBuilder,
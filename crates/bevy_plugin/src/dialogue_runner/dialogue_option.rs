@@ -30,6 +30,12 @@ pub struct DialogueOption {
     /// This is intended for situations where games wish to show options that the player _could_ have taken,
     /// if some other condition had been met (e.g. having enough "charisma" points).
     pub is_available: bool,
+
+    /// The source code of the line condition attached to this option, e.g. `$gold > 10` for `-> Buy <<if $gold > 10>>`,
+    /// or [`None`] if the option has no condition.
+    /// Useful for explaining to the player why an option is not [available](DialogueOption::is_available).
+    /// The option's hashtags are found in the [`LocalizedLine::metadata`] of [`DialogueOption::line`].
+    pub condition: Option<String>,
 }
 
 impl DialogueOption {
@@ -43,6 +49,7 @@ impl DialogueOption {
             id: yarn_dialogue_option.id,
             destination_node: yarn_dialogue_option.destination_node,
             is_available: yarn_dialogue_option.is_available,
+            condition: yarn_dialogue_option.condition,
         }
     }
 }
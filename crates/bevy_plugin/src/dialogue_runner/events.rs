@@ -9,6 +9,7 @@ pub(crate) fn dialogue_runner_events_plugin(app: &mut App) {
         .add_event::<NodeCompleteEvent>()
         .add_event::<NodeStartEvent>()
         .add_event::<LineHintsEvent>()
+        .add_event::<VariableChangedEvent>()
         .add_event::<DialogueCompleteEvent>()
         .add_event::<DialogueStartEvent>();
 }
@@ -81,6 +82,21 @@ pub struct LineHintsEvent {
     pub source: Entity,
 }
 
+/// An event that is fired when the dialogue assigned a value to a variable, e.g. through `<<set>>`.
+/// Useful for reacting to changes in the dialogue's state, such as updating a quest log, without polling the [`VariableStorage`].
+/// Handling this event is **optional** for dialogue views.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct VariableChangedEvent {
+    /// The name of the variable, including the leading `$`.
+    pub name: String,
+    /// The value the variable had before, or [`None`] if this is the first time it was assigned a value.
+    pub old: Option<YarnValue>,
+    /// The value the variable has now.
+    pub new: YarnValue,
+    /// The [`DialogueRunner`] that changed this variable.
+    pub source: Entity,
+}
+
 /// An event that is fired when a dialogue has been started via [`DialogueRunner::start_node`]/
 /// Handling this event is **optional** for dialogue views.
 #[derive(Debug, Clone, PartialEq, Event)]
@@ -177,10 +177,12 @@ impl LocalizedLine {
 
 impl From<LocalizedLine> for YarnLine {
     fn from(line: LocalizedLine) -> Self {
+        let is_last_line_before_options = line.is_last_line_before_options();
         Self {
             id: line.id,
             text: line.text,
             attributes: line.attributes,
+            is_last_line_before_options,
         }
     }
 }
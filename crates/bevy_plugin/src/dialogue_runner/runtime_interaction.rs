@@ -36,6 +36,7 @@ fn continue_runtime(
     mut node_complete_events: EventWriter<NodeCompleteEvent>,
     mut node_start_events: EventWriter<NodeStartEvent>,
     mut line_hints_events: EventWriter<LineHintsEvent>,
+    mut variable_changed_events: EventWriter<VariableChangedEvent>,
     mut dialogue_complete_events: EventWriter<DialogueCompleteEvent>,
     mut dialogue_start_events: EventWriter<DialogueStartEvent>,
     mut last_options: Local<HashMap<Entity, Vec<DialogueOption>>>,
@@ -132,6 +133,14 @@ fn continue_runtime(
                 DialogueEvent::LineHints(line_ids) => {
                     line_hints_events.send(LineHintsEvent { line_ids, source });
                 }
+                DialogueEvent::VariableChanged { name, old, new } => {
+                    variable_changed_events.send(VariableChangedEvent {
+                        name,
+                        old,
+                        new,
+                        source,
+                    });
+                }
                 DialogueEvent::DialogueComplete => {
                     if !is_sending_missed_events {
                         dialogue_runner.is_running = false;
@@ -123,6 +123,7 @@ pub mod events {
     pub use crate::dialogue_runner::{
         DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineHintsEvent,
         NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+        VariableChangedEvent,
     };
 }
 
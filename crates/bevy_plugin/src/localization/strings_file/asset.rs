@@ -6,10 +6,10 @@ use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, BoxedFuture, LoadContex
 use bevy::prelude::*;
 use bevy::reflect::TypePath;
 use bevy::utils::HashMap;
-use sha2::{Digest, Sha256};
 use std::fs;
 use std::fs::File;
 use std::path::Path;
+use yarnspinner::compiler::{line_metadata_comment, string_lock, LINE_METADATA_PREFIX};
 
 pub(crate) fn strings_file_asset_plugin(app: &mut App) {
     app.init_asset::<StringsFile>()
@@ -162,7 +162,7 @@ impl StringsFile {
                     node: string_info.node_name,
                     line_number: string_info.line_number,
                     lock,
-                    comment: read_comments(string_info.metadata),
+                    comment: line_metadata_comment(string_info.metadata),
                 },
             );
         }
@@ -242,7 +242,6 @@ fn extract_translator_comment(comment: &str) -> Option<&str> {
         .map(|s| s.trim_end_matches(LINE_METADATA_PREFIX_SEPARATOR))
 }
 
-const LINE_METADATA_PREFIX: &str = "Line metadata: ";
 const LINE_METADATA_PREFIX_SEPARATOR: &str = ", ";
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -262,6 +261,9 @@ pub(crate) struct StringsFileRecord {
 
     /// The 1-indexed line number in the file indicated by [`file`](StringsFileRecord::file) at
     /// which the original version of this line can be found.
+    ///
+    /// Also read from a `lineNumber` column, as written by the upstream tools and [`Compilation::string_table_to_csv`](yarnspinner::compiler::Compilation::string_table_to_csv).
+    #[serde(alias = "lineNumber")]
     pub(crate) line_number: usize,
     /// A string used as part of a mechanism for checking if translated
     /// versions of this string are out of date.
@@ -288,36 +290,8 @@ pub(crate) struct StringsFileRecord {
 pub(crate) struct Lock(String);
 
 impl Lock {
-    /// Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/462c735766a4c4881cd1ef1f15de28c83b2ba0a8/Editor/Importers/YarnImporter.cs#L149>
     pub(crate) fn compute_from(text: &str) -> Self {
-        const MAX_CHARS: usize = 8;
-        let hash = Sha256::digest(text);
-        let hex = format!("{hash:x}");
-        let lock = hex.chars().take(MAX_CHARS).collect();
-        Self(lock)
-    }
-}
-
-/// Generates a string with the line metadata. This string is intended
-/// to be used in the "comment" column of a strings table CSV. Because
-/// of this, it will ignore the line ID if it exists (which is also
-/// part of the line metadata).
-///
-/// ## Return value
-/// A string prefixed with "Line metadata: ", followed by each
-/// piece of metadata separated by whitespace. If no metadata exists or
-/// only the line ID is part of the metadata, returns an empty string
-/// instead.
-fn read_comments(metadata: impl IntoIterator<Item = String>) -> String {
-    // Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/462c735766a4c4881cd1ef1f15de28c83b2ba0a8/Editor/Importers/YarnProjectImporter.cs#L652>
-    let cleaned_metadata: Vec<_> = metadata
-        .into_iter()
-        .filter(|metadata| !metadata.starts_with("line:"))
-        .collect();
-    if cleaned_metadata.is_empty() {
-        String::new()
-    } else {
-        format!("{LINE_METADATA_PREFIX}{}", cleaned_metadata.join(" "))
+        Self(string_lock(text))
     }
 }
 
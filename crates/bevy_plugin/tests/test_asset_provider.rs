@@ -208,11 +208,12 @@ where
     T: AssetProvider + ?Sized,
 {
     fn get_assets_for_id(&self, id: &str) -> LineAssets {
-        let line_id = LineId(id.to_owned());
+        let line_id = LineId::from(id);
         let yarn_line = UnderlyingYarnLine {
             id: line_id,
             text: String::new(),
             attributes: vec![],
+            is_last_line_before_options: false,
         };
         T::get_assets(self, &yarn_line)
     }
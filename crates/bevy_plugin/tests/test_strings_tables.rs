@@ -133,7 +133,7 @@ fn generates_strings_file() -> anyhow::Result<()> {
 
     assert!(strings_file_line_ids
         .iter()
-        .all(|line_id| string_table.contains_key(&LineId(line_id.to_string()))));
+        .all(|line_id| string_table.contains_key(&LineId::from(line_id.to_string()))));
 
     Ok(())
 }
@@ -197,7 +197,7 @@ fn appends_to_pre_existing_strings_file() -> anyhow::Result<()> {
 
     assert!(string_table
         .keys()
-        .all(|line_id| strings_file_line_ids.contains(&line_id.0.as_str())));
+        .all(|line_id| strings_file_line_ids.contains(&line_id.as_str())));
 
     Ok(())
 }
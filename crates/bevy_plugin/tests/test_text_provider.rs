@@ -16,7 +16,7 @@ fn loads_line_without_localization() {
     let line = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:9".to_owned()))
+        .get_text(&LineId::from("line:9"))
         .unwrap();
     assert_eq!("Man: All right. I don't believe this; but there's no harm in wishing. I wish to know who I am.", line);
 }
@@ -33,7 +33,7 @@ fn fails_to_get_invalid_line() {
     let result = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:99".to_owned()));
+        .get_text(&LineId::from("line:99"));
     assert!(result.is_none());
 }
 
@@ -55,7 +55,7 @@ fn loads_line_from_base_language_without_explicit_language() {
     let line = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:9".to_owned()))
+        .get_text(&LineId::from("line:9"))
         .unwrap();
     assert_eq!("Man: All right. I don't believe this; but there's no harm in wishing. I wish to know who I am.", line);
 }
@@ -80,7 +80,7 @@ fn loads_line_from_base_language_with_explicit_language() {
     let line = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:9".to_owned()))
+        .get_text(&LineId::from("line:9"))
         .unwrap();
     assert_eq!("Man: All right. I don't believe this; but there's no harm in wishing. I wish to know who I am.", line);
 }
@@ -124,7 +124,7 @@ fn loads_line_from_fallback_on_missing_line() {
     let line = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:10".to_owned()))
+        .get_text(&LineId::from("line:10"))
         .unwrap();
     assert_eq!("Hag: Funny,", line);
 }
@@ -149,7 +149,7 @@ fn loads_line_from_translated_language() {
     let line = app
         .dialogue_runner()
         .text_provider()
-        .get_text(&LineId("line:9".to_owned()))
+        .get_text(&LineId::from("line:9"))
         .unwrap();
     assert_eq!("Mann: Also gut. Ich glaub das zwar nicht, aber es kann ja nicht schaden, wenn ich mir was wünsche. Ich möchte wissen, wer ich bin.", line);
 }
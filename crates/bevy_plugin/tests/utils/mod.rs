@@ -164,9 +164,10 @@ pub trait DialogueRunnerExt {
 impl DialogueRunnerExt for DialogueRunner {
     fn get_assets_for_id(&self, line_id: &str) -> LineAssets {
         let line_id = UnderlyingYarnLine {
-            id: LineId(line_id.to_string()),
+            id: LineId::from(line_id.to_string()),
             text: String::new(),
             attributes: vec![],
+            is_last_line_before_options: false,
         };
         self.asset_providers()
             .map(|p| p.get_assets(&line_id))
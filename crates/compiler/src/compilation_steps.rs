@@ -1,12 +1,20 @@
 mod add_initial_value_registrations;
 mod add_tracking_declarations;
+mod check_markup;
 mod check_types;
 mod clean_up_diagnostics;
+mod compile_smart_variables;
+mod create_declarations_for_once_blocks;
 mod create_declarations_for_tracking_nodes;
 mod early_breaks;
+mod find_commands;
+mod find_jump_cycles;
 mod find_tracking_nodes;
+mod find_unreachable_nodes;
+mod find_unused_variables;
 mod generate_code;
 mod get_declarations;
+mod get_node_infos;
 mod parse_files;
 mod register_initial_variables;
 mod register_strings;
@@ -14,9 +22,11 @@ mod resolve_deferred_type_diagnostic;
 mod validate_unique_node_names;
 
 pub(crate) use self::{
-    add_initial_value_registrations::*, add_tracking_declarations::*, check_types::*,
-    clean_up_diagnostics::*, create_declarations_for_tracking_nodes::*, early_breaks::*,
-    find_tracking_nodes::*, generate_code::*, get_declarations::*, parse_files::*,
-    register_initial_variables::*, register_strings::*, resolve_deferred_type_diagnostic::*,
-    validate_unique_node_names::*,
+    add_initial_value_registrations::*, add_tracking_declarations::*, check_markup::*,
+    check_types::*, clean_up_diagnostics::*, compile_smart_variables::*,
+    create_declarations_for_once_blocks::*, create_declarations_for_tracking_nodes::*,
+    early_breaks::*, find_commands::*, find_jump_cycles::*, find_tracking_nodes::*,
+    find_unreachable_nodes::*, find_unused_variables::*, generate_code::*, get_declarations::*,
+    get_node_infos::*, parse_files::*, register_initial_variables::*, register_strings::*,
+    resolve_deferred_type_diagnostic::*, validate_unique_node_names::*,
 };
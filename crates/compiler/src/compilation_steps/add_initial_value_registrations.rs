@@ -15,7 +15,11 @@ pub(crate) fn add_initial_value_registrations(
     let declarations = state
         .known_variable_declarations
         .iter()
-        .filter(|decl| !matches!(decl.r#type, Type::Function(_)));
+        .filter(|decl| !matches!(decl.r#type, Type::Function(_)))
+        // Constants are replaced by their value during code generation, so they don't need to be stored
+        .filter(|decl| !decl.is_constant)
+        // Smart variables are computed every time they are read, so they don't have a value to store
+        .filter(|decl| decl.smart_expression.is_none());
 
     for declaration in declarations {
         let Some(default_value) = declaration.default_value.clone() else {
@@ -39,6 +43,12 @@ pub(crate) fn add_initial_value_registrations(
         }
     }
 
+    if let Some(ref mut program) = compilation.program {
+        program
+            .smart_variables
+            .extend(state.smart_variables.clone());
+    }
+
     compilation.declarations = state.derived_variable_declarations.clone();
     state
 }
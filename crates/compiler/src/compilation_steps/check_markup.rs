@@ -0,0 +1,111 @@
+use crate::prelude::*;
+use crate::visitors::MarkupVisitor;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+
+pub(crate) fn check_markup(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    // The runtime only finds out about broken markup when the line is shown, either failing to parse it
+    // or silently dropping tags that are never closed, so catch it here where we can still point at the offending tag
+    for (parsed_file, file) in state.parsed_files.iter().zip(&state.job.files) {
+        let mut visitor = MarkupVisitor::new(
+            parsed_file.name.clone(),
+            &file.source,
+            state.job.markup_attributes.as_deref(),
+        );
+        visitor.visit(parsed_file.tree.as_ref());
+        state.diagnostics.extend(visitor.diagnostics);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markup_diagnostics(line: &str) -> Vec<Diagnostic> {
+        diagnostics_with_code(&mut Compiler::new(), line, DiagnosticCode::UnbalancedMarkup)
+    }
+
+    fn diagnostics_with_code(
+        compiler: &mut Compiler,
+        line: &str,
+        code: DiagnosticCode,
+    ) -> Vec<Diagnostic> {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: format!("title: Start\n---\n{line}\n===\n"),
+        };
+        let diagnostics = match compiler.add_file(file).compile() {
+            Ok(compilation) => compilation.warnings,
+            Err(CompilerError(diagnostics)) => diagnostics,
+        };
+        diagnostics
+            .into_iter()
+            .filter(|warning| warning.code == Some(code))
+            .collect()
+    }
+
+    #[test]
+    fn warns_about_unclosed_tag() {
+        let warnings = markup_diagnostics("Alice: This is [b]very important {$gold} [i]indeed[/i]");
+
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].message.contains("[b]"));
+        assert_eq!(warnings[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(
+            warnings[0].range,
+            Some(
+                Position {
+                    line: 2,
+                    character: 15
+                }..Position {
+                    line: 2,
+                    character: 18
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_closing_tag_that_is_not_open() {
+        let errors = markup_diagnostics("Hello[/wave] there");
+
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].message.contains("[/wave]"));
+        assert_eq!(errors[0].severity, DiagnosticSeverity::Error);
+    }
+
+    #[test]
+    fn accepts_balanced_markup() {
+        let line = "[wave speed={$speed}]Hi[/wave], [b][i]two[/], a pause[pause/] \
+                    [nomarkup][b] is literal[/nomarkup] and \\[escaped\\]";
+        assert_eq!(markup_diagnostics(line), []);
+    }
+
+    #[test]
+    fn warns_about_unknown_attributes() {
+        let line = "[shake]Hi[/shake] [b]there[/b][pause/], [nomarkup][c][/nomarkup] \
+                    {$count} [plural value={$count} one=coin other=coins/]";
+        let warnings = diagnostics_with_code(
+            Compiler::new().with_markup_attributes(["shake"]),
+            line,
+            DiagnosticCode::UnknownMarkupAttribute,
+        );
+
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[0].message.contains("[b]"));
+        assert!(warnings[1].message.contains("[pause]"));
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.severity == DiagnosticSeverity::Warning));
+    }
+
+    #[test]
+    fn does_not_check_attribute_names_by_default() {
+        let warnings = diagnostics_with_code(
+            &mut Compiler::new(),
+            "[shake]Hi[/shake]",
+            DiagnosticCode::UnknownMarkupAttribute,
+        );
+        assert_eq!(warnings, []);
+    }
+}
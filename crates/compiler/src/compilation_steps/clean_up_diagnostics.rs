@@ -23,6 +23,21 @@ pub(crate) fn clean_up_diagnostics(mut state: CompilationIntermediate) -> Compil
             ordered_unique_diagnostics.push(diagnostic);
         }
     }
+    // Not in the original implementation: sort so that the output is stable across runs,
+    // e.g. for snapshot tests. The sort is stable, so diagnostics at the same position keep their order.
+    ordered_unique_diagnostics.sort_by(|a, b| {
+        let position = |d: &Diagnostic| d.range.as_ref().map(|r| (r.start.line, r.start.character));
+        a.file_name
+            .cmp(&b.file_name)
+            .then_with(|| position(a).cmp(&position(b)))
+    });
+    if state.job.treat_warnings_as_errors {
+        for diagnostic in &mut ordered_unique_diagnostics {
+            if diagnostic.severity == DiagnosticSeverity::Warning {
+                diagnostic.severity = DiagnosticSeverity::Error;
+            }
+        }
+    }
     state.diagnostics = ordered_unique_diagnostics;
     if state.diagnostics.has_errors() {
         state.result = Some(Err(CompilerError(state.diagnostics.clone())));
@@ -31,3 +46,45 @@ pub(crate) fn clean_up_diagnostics(mut state: CompilationIntermediate) -> Compil
     }
     state
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(file_name: &str, line: usize, character: usize) -> Diagnostic {
+        Diagnostic::from_message("Something is off")
+            .with_file_name(file_name)
+            .with_range(Position { line, character }..Position { line, character })
+            .with_severity(DiagnosticSeverity::Warning)
+    }
+
+    #[test]
+    fn removes_duplicates_and_sorts_by_file_and_position() {
+        let compiler = Compiler::new();
+        let mut state = CompilationIntermediate::from_job(&compiler, vec![]);
+        state.result = Some(Ok(Compilation::default()));
+        state.diagnostics = vec![
+            diagnostic("b.yarn", 0, 0),
+            diagnostic("a.yarn", 3, 2),
+            diagnostic("a.yarn", 1, 5),
+            diagnostic("a.yarn", 3, 2),
+            diagnostic("a.yarn", 3, 0),
+        ];
+
+        let warnings = clean_up_diagnostics(state)
+            .result
+            .unwrap()
+            .unwrap()
+            .warnings;
+
+        assert_eq!(
+            warnings,
+            vec![
+                diagnostic("a.yarn", 1, 5),
+                diagnostic("a.yarn", 3, 0),
+                diagnostic("a.yarn", 3, 2),
+                diagnostic("b.yarn", 0, 0),
+            ]
+        );
+    }
+}
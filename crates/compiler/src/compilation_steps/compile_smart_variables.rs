@@ -0,0 +1,142 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+pub(crate) fn compile_smart_variables(
+    mut state: CompilationIntermediate,
+) -> CompilationIntermediate {
+    // Smart variables may refer to the ones declared before them, so each expression
+    // is compiled knowing the types of the smart variables resolved so far.
+    // Referring to itself or to a later smart variable fails because its type is still unknown.
+    // Smart variables are declared either in the files via `<<declare $name = expression>>`
+    // or through the API via `Declaration::with_smart_expression`, so they are found among all known declarations.
+    let smart_declarations: Vec<_> = state
+        .known_variable_declarations
+        .iter()
+        .filter(|declaration| declaration.smart_expression.is_some())
+        .cloned()
+        .collect();
+    let mut unresolved_names: HashSet<_> = smart_declarations
+        .iter()
+        .map(|declaration| declaration.name.clone())
+        .collect();
+
+    for declaration in smart_declarations {
+        let expression = declaration.smart_expression.as_ref().unwrap();
+        let compiler = Compiler {
+            files: Vec::new(),
+            variable_declarations: state
+                .known_variable_declarations
+                .iter()
+                .filter(|known| !unresolved_names.contains(&known.name))
+                .cloned()
+                .collect(),
+            ..state.job.clone()
+        };
+        match compiler.compile_typed_expression(expression) {
+            Ok((_, r#type))
+                if declaration.r#type != Type::Any
+                    && !r#type.is_sub_type_of(&declaration.r#type) =>
+            {
+                let mut diagnostic = Diagnostic::from_message(format!(
+                    "Type {} does not match expression {expression} ({})",
+                    declaration.r#type, r#type
+                ));
+                if let DeclarationSource::File(file_name) = &declaration.source_file_name {
+                    diagnostic = diagnostic.with_file_name(file_name.clone());
+                }
+                if let Some(range) = declaration.range.clone() {
+                    diagnostic = diagnostic.with_range(range);
+                }
+                state.diagnostics.push(diagnostic);
+            }
+            Ok((node, r#type)) => {
+                unresolved_names.remove(&declaration.name);
+                state
+                    .known_variable_declarations
+                    .iter_mut()
+                    .chain(state.derived_variable_declarations.iter_mut())
+                    .filter(|known| known.name == declaration.name)
+                    .for_each(|known| known.r#type = r#type.clone());
+                let node = Node {
+                    name: declaration.name.clone(),
+                    ..node
+                };
+                state.smart_variables.insert(declaration.name.clone(), node);
+            }
+            Err(error) => {
+                // Point the diagnostics at the declaration instead of the synthetic file the expression was compiled in
+                let file_name = match &declaration.source_file_name {
+                    DeclarationSource::File(file_name) => Some(file_name.clone()),
+                    DeclarationSource::External => None,
+                };
+                let diagnostics = error.0.into_iter().map(|diagnostic| Diagnostic {
+                    message: format!(
+                        "In the expression of smart variable {}: {}",
+                        declaration.name, diagnostic.message
+                    ),
+                    file_name: file_name.clone(),
+                    range: declaration.range.clone(),
+                    start_line: declaration.source_file_line().unwrap_or_default(),
+                    context: None,
+                    ..diagnostic
+                });
+                state.diagnostics.extend(diagnostics);
+            }
+        }
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarnspinner_core::types::Type;
+
+    fn compile_with_is_rich(source: &str) -> crate::Result<Compilation> {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: source.to_string(),
+        };
+        Compiler::new()
+            .add_file(file)
+            .declare_variable(
+                Declaration::new("$is_rich", Type::Any).with_smart_expression("$gold > 100"),
+            )
+            .compile()
+    }
+
+    #[test]
+    fn compiles_smart_variables() {
+        let result = compile_with_is_rich(
+            "title: test
+---
+<<declare $gold = 0>>
+<<if $is_rich>>
+    You are rich.
+<<endif>>
+===",
+        )
+        .unwrap();
+
+        assert!(result.warnings.is_empty());
+        let program = result.program.unwrap();
+        assert!(program.smart_variables.contains_key("$is_rich"));
+        assert!(!program.initial_values.contains_key("$is_rich"));
+    }
+
+    #[test]
+    fn rejects_assignments_to_smart_variables() {
+        let result = compile_with_is_rich(
+            "title: test
+---
+<<declare $gold = 0>>
+<<set $is_rich to true>>
+===",
+        );
+
+        let diagnostics = result.unwrap_err().0;
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("is a smart variable")));
+    }
+}
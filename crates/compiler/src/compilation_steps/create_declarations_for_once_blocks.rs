@@ -0,0 +1,30 @@
+use crate::prelude::*;
+use yarnspinner_core::prelude::Library;
+use yarnspinner_core::types::Type;
+
+pub(crate) fn create_declarations_for_once_blocks(
+    mut state: CompilationIntermediate,
+) -> CompilationIntermediate {
+    // Like the tracking variables, these need to be declared so that the
+    // initial values of the program make every `<<once>>` block start out as not yet run
+    let once_declarations: Vec<_> = state
+        .once_blocks
+        .iter()
+        .map(|(node, index)| {
+            let name = Library::generate_unique_once_variable(node, *index);
+            Declaration::new(name, Type::Boolean)
+                .with_default_value(false)
+                .with_description(format!(
+                    "The generated variable for tracking whether once block {index} of node {node} has run"
+                ))
+        })
+        .collect();
+
+    state
+        .known_variable_declarations
+        .extend(once_declarations.clone());
+    state
+        .derived_variable_declarations
+        .extend(once_declarations);
+    state
+}
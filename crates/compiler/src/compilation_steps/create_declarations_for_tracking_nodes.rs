@@ -27,3 +27,57 @@ pub(crate) fn create_declarations_for_tracking_nodes(
 
     state
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracking_declarations_are_ordered_deterministically() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Zebra
+tracking: always
+---
+Z
+===
+title: Apple
+tracking: always
+---
+A
+===
+title: Mango
+tracking: always
+---
+M
+===
+title: Kiwi
+tracking: always
+---
+K
+===
+"
+            .to_string(),
+        };
+        let declaration_names = || {
+            Compiler::new()
+                .add_file(file.clone())
+                .compile()
+                .unwrap()
+                .declarations
+                .into_iter()
+                .map(|declaration| declaration.name)
+                .collect::<Vec<_>>()
+        };
+
+        let first = declaration_names();
+        let second = declaration_names();
+        assert_eq!(first, second);
+
+        let expected: Vec<_> = ["Apple", "Kiwi", "Mango", "Zebra"]
+            .into_iter()
+            .map(Library::generate_unique_visited_variable_for_node)
+            .collect();
+        assert!(first.starts_with(&expected), "{first:?}");
+    }
+}
@@ -3,11 +3,13 @@ use crate::prelude::*;
 pub(crate) fn break_on_job_with_only_strings(
     mut state: CompilationIntermediate,
 ) -> CompilationIntermediate {
-    if state.job.compilation_type == CompilationType::StringsOnly {
+    if state.compilation_type == CompilationType::StringsOnly {
         state.result = Some(Ok(Compilation {
             string_table: state.string_table.clone().into(),
             contains_implicit_string_tags: state.string_table.contains_implicit_string_tags(),
             warnings: state.diagnostics.clone(),
+            nodes: state.node_infos.clone(),
+            commands: state.commands.clone(),
             ..Default::default()
         }));
         state.early_break = true;
@@ -18,11 +20,16 @@ pub(crate) fn break_on_job_with_only_strings(
 pub(crate) fn break_on_job_with_only_declarations(
     mut state: CompilationIntermediate,
 ) -> CompilationIntermediate {
-    if state.job.compilation_type == CompilationType::DeclarationsOnly {
+    if matches!(
+        state.compilation_type,
+        CompilationType::DeclarationsOnly | CompilationType::CheckOnly
+    ) {
         state.result = Some(Ok(Compilation {
             declarations: state.derived_variable_declarations.clone(),
             warnings: state.diagnostics.clone(),
             file_tags: state.file_tags.clone(),
+            nodes: state.node_infos.clone(),
+            commands: state.commands.clone(),
             ..Default::default()
         }));
         state.early_break = true;
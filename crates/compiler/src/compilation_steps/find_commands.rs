@@ -0,0 +1,45 @@
+use crate::prelude::*;
+use crate::visitors::CommandVisitor;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+
+pub(crate) fn find_commands(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    for file in &state.parsed_files {
+        let mut visitor = CommandVisitor::new(file.name.clone());
+        visitor.visit(file.tree.as_ref());
+        state.commands.extend(visitor.commands);
+        state.once_blocks.extend(visitor.once_blocks);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn finds_custom_commands() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+<<declare $gold = 10>>
+<<fade_out 1.5>>
+Hello there
+<<give_item \"rusty sword\" {$gold + 1}>>
+<<fade_out 2>>
+<<stop>>
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        let names: BTreeSet<_> = result.command_names().collect();
+        assert_eq!(names, BTreeSet::from(["fade_out", "give_item"]));
+        assert_eq!(result.commands.len(), 3);
+        let give_item = &result.commands[1];
+        assert_eq!(give_item.argument_count, 2);
+        assert_eq!(give_item.node_name, "Start");
+        assert_eq!(give_item.line_number, 6);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use crate::visitors::build_jump_graph;
+use std::collections::{HashMap, HashSet};
+
+pub(crate) fn find_jump_cycles(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    if !state.job.detect_jump_cycles {
+        return state;
+    }
+    let diagnostics = get_jump_cycle_diagnostics(&state.parsed_files);
+    state.diagnostics.extend(diagnostics);
+    state
+}
+
+/// Reports every cycle of nodes that immediately jump to each other without running any lines, options or commands,
+/// as entering such a cycle would hang the dialogue.
+fn get_jump_cycle_diagnostics(parsed_files: &[FileParseResult]) -> Vec<Diagnostic> {
+    let nodes = build_jump_graph(parsed_files);
+    let nodes_by_name: HashMap<_, _> = nodes
+        .iter()
+        .map(|entry| (entry.0.name.as_str(), entry))
+        .collect();
+
+    // Every node has at most one immediate destination,
+    // so following them from any node leads either to a dead end or into exactly one cycle.
+    let mut visited = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for (node, _) in &nodes {
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = Some(node.name.as_str());
+        while let Some(name) = current {
+            if let Some(cycle_start) = path.iter().position(|visited_name| *visited_name == name) {
+                let cycle = &path[cycle_start..];
+                let (first_node, file) = nodes_by_name[cycle[0]];
+                let cycle_description = cycle
+                    .iter()
+                    .chain(std::iter::once(&cycle[0]))
+                    .map(|name| format!("\"{name}\""))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                diagnostics.push(
+                    Diagnostic::from_message(format!(
+                        "The nodes {cycle_description} jump to each other without running any lines, options or commands, so the dialogue will never finish once it reaches them"
+                    ))
+                    .with_file_name(file.name.clone())
+                    .with_parser_context(first_node.title_header.as_ref(), file.tokens())
+                    .with_severity(DiagnosticSeverity::Warning)
+                    .with_code(DiagnosticCode::JumpCycle),
+                );
+                break;
+            }
+            if !visited.insert(name) {
+                break;
+            }
+            path.push(name);
+            current = nodes_by_name
+                .get(name)
+                .and_then(|(node, _)| node.immediate_destination.as_deref());
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_about_cycles_without_content() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Ping
+---
+<<jump Pong>>
+===
+title: Pong
+---
+<<set $bounces to 1>>
+<<jump Ping>>
+===
+title: Question
+---
+Are we there yet?
+<<jump Answer>>
+===
+title: Answer
+---
+No.
+<<jump Question>>
+==="
+            .to_string(),
+        };
+        let mut compiler = Compiler::new();
+        compiler.add_file(file);
+        let without_detection = compiler.compile().unwrap();
+        let with_detection = compiler.with_jump_cycle_detection(true).compile().unwrap();
+
+        let is_cycle_warning = |warning: &&Diagnostic| warning.message.contains("never finish");
+        assert_eq!(
+            without_detection
+                .warnings
+                .iter()
+                .filter(is_cycle_warning)
+                .count(),
+            0
+        );
+        let cycle_warnings: Vec<_> = with_detection
+            .warnings
+            .iter()
+            .filter(is_cycle_warning)
+            .collect();
+        assert_eq!(cycle_warnings.len(), 1);
+        assert_eq!(cycle_warnings[0].severity, DiagnosticSeverity::Warning);
+        assert!(cycle_warnings[0]
+            .message
+            .contains("\"Ping\" -> \"Pong\" -> \"Ping\""));
+    }
+}
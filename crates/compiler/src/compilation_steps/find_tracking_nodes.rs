@@ -9,12 +9,141 @@ pub(crate) fn find_tracking_nodes(mut state: CompilationIntermediate) -> Compila
     // so that any tracking variables are included in the compiled declarations
     let mut tracking_nodes = HashSet::new();
     let mut ignore_nodes = HashSet::new();
+    let mut calls_visit_functions = false;
     for file in &state.parsed_files {
         let mut visitor = NodeTrackingVisitor::new();
         visitor.visit(file.tree.as_ref());
         tracking_nodes.extend(visitor.tracking_nodes);
         ignore_nodes.extend(visitor.ignoring_nodes);
+        calls_visit_functions |= visitor.calls_visit_functions;
+    }
+    // The expressions of smart variables are only compiled to instructions, not parsed as part of the files
+    for instructions in state
+        .smart_variables
+        .values()
+        .map(|node| &node.instructions)
+    {
+        for (index, instruction) in instructions.iter().enumerate() {
+            let is_visit_function = instruction.opcode == OpCode::CallFunc as i32
+                && ["visited", "visited_count"]
+                    .contains(&instruction.read_operand::<String>(0).as_str());
+            if !is_visit_function {
+                continue;
+            }
+            calls_visit_functions = true;
+            // A static node name is pushed right before the parameter count
+            let node_name = index
+                .checked_sub(2)
+                .map(|push_index| &instructions[push_index])
+                .filter(|push| push.opcode == OpCode::PushString as i32)
+                .map(|push| push.read_operand::<String>(0));
+            tracking_nodes.extend(node_name);
+        }
+    }
+    if state.job.skip_unused_visit_tracking && !calls_visit_functions {
+        state.tracking_nodes.clear();
+        return state;
     }
     state.tracking_nodes = tracking_nodes.difference(&ignore_nodes).cloned().collect();
     state
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarnspinner_core::prelude::Library;
+    use yarnspinner_core::types::Type;
+
+    fn tracking_declarations(source: &str, skip_unused_visit_tracking: bool) -> Vec<String> {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: source.to_string(),
+        };
+        Compiler::new()
+            .add_file(file)
+            .with_unused_visit_tracking_skipped(skip_unused_visit_tracking)
+            .compile()
+            .unwrap()
+            .declarations
+            .into_iter()
+            .map(|declaration| declaration.name)
+            .filter(|name| name.starts_with("$Yarn.Internal.Visiting."))
+            .collect()
+    }
+
+    #[test]
+    fn skips_visit_tracking_if_unused() {
+        let source = "title: Start
+tracking: always
+---
+<<jump End>>
+===
+title: End
+---
+Bye
+===
+";
+        assert!(!tracking_declarations(source, false).is_empty());
+        assert!(tracking_declarations(source, true).is_empty());
+    }
+
+    #[test]
+    fn keeps_visit_tracking_if_used() {
+        let source = "title: Start
+---
+<<if visited(\"End\")>>
+    Welcome back
+<<endif>>
+<<jump End>>
+===
+title: End
+---
+Bye
+===
+";
+        let declarations = tracking_declarations(source, true);
+        let expected = Library::generate_unique_visited_variable_for_node("End");
+        assert!(!declarations.is_empty());
+        assert!(declarations.iter().all(|name| *name == expected));
+    }
+
+    #[test]
+    fn tracks_nodes_visited_in_smart_variables() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+<<if $knows_the_way>>
+    Off you go.
+<<endif>>
+<<jump Directions>>
+===
+title: Directions
+---
+Turn left.
+===
+"
+            .to_string(),
+        };
+        let compilation = Compiler::new()
+            .add_file(file)
+            .declare_variable(
+                Declaration::new("$knows_the_way", Type::Any)
+                    .with_smart_expression("visited(\"Directions\")"),
+            )
+            .with_unused_visit_tracking_skipped(true)
+            .compile()
+            .unwrap();
+
+        let expected = Library::generate_unique_visited_variable_for_node("Directions");
+        assert!(compilation
+            .declarations
+            .iter()
+            .any(|declaration| declaration.name == expected));
+        assert!(compilation
+            .program
+            .unwrap()
+            .initial_values
+            .contains_key(&expected));
+    }
+}
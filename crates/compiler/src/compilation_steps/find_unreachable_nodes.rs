@@ -0,0 +1,120 @@
+use crate::prelude::*;
+use crate::visitors::{build_jump_graph, JumpGraphNode};
+use std::collections::{HashMap, HashSet};
+
+pub(crate) fn find_unreachable_nodes(
+    mut state: CompilationIntermediate,
+) -> CompilationIntermediate {
+    let diagnostics = get_unreachable_node_diagnostics(&state.parsed_files);
+    state.diagnostics.extend(diagnostics);
+    state
+}
+
+/// Walks the jump graph starting at all entry points and reports every node that is never reached.
+///
+/// Entry points are the node named "Start" and any node with an `entry: true` header.
+/// If there are no entry points, or a reachable node jumps to an expression, we cannot
+/// know which nodes are reachable, so nothing is reported.
+fn get_unreachable_node_diagnostics(parsed_files: &[FileParseResult]) -> Vec<Diagnostic> {
+    let nodes = build_jump_graph(parsed_files);
+    let nodes_by_name: HashMap<&str, &JumpGraphNode> = nodes
+        .iter()
+        .map(|(node, _)| (node.name.as_str(), node))
+        .collect();
+
+    let mut unvisited: Vec<&str> = nodes
+        .iter()
+        .filter(|(node, _)| node.is_entry_point)
+        .map(|(node, _)| node.name.as_str())
+        .collect();
+    if unvisited.is_empty() {
+        return vec![];
+    }
+    let mut reachable = HashSet::new();
+    while let Some(name) = unvisited.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        // Jumps to nodes that don't exist are reported at runtime
+        let Some(node) = nodes_by_name.get(name) else {
+            continue;
+        };
+        if node.has_dynamic_jump {
+            return vec![];
+        }
+        unvisited.extend(node.destinations.iter().map(String::as_str));
+    }
+
+    nodes
+        .iter()
+        .filter(|(node, _)| !reachable.contains(node.name.as_str()))
+        .map(|(node, file)| {
+            Diagnostic::from_message(format!(
+                "Node \"{}\" is not reachable from any entry point. If this is intended, add the header \"entry: true\" to it",
+                node.name
+            ))
+            .with_file_name(file.name.clone())
+            .with_parser_context(node.title_header.as_ref(), file.tokens())
+            .with_severity(DiagnosticSeverity::Warning)
+            .with_code(DiagnosticCode::UnreachableNode)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_about_orphaned_nodes() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+Hello there
+<<jump Reachable>>
+===
+title: Reachable
+---
+You found me
+===
+title: Orphaned
+---
+Nobody will ever see this
+===
+title: Intentional
+entry: true
+---
+This node is started from code
+===
+"
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert_eq!(result.warnings.len(), 1);
+        let warning = &result.warnings[0];
+        assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+        assert!(warning.message.contains("\"Orphaned\""));
+    }
+
+    #[test]
+    fn does_not_warn_without_entry_points() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: First
+---
+Hello
+===
+title: Second
+---
+There
+===
+"
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert!(result.warnings.is_empty());
+    }
+}
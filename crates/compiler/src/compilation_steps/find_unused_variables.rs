@@ -0,0 +1,105 @@
+use crate::listeners::DiagnosticVec;
+use crate::prelude::*;
+use crate::visitors::VariableUsageVisitor;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+use std::collections::HashSet;
+
+pub(crate) fn find_unused_variables(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    // Unused variables are not worth mentioning when the user has errors to fix first.
+    if state.diagnostics.has_errors() {
+        return state;
+    }
+    let mut used_variables = HashSet::new();
+    for file in &state.parsed_files {
+        let mut visitor = VariableUsageVisitor::default();
+        visitor.visit(file.tree.as_ref());
+        used_variables.extend(visitor.used_variables);
+    }
+    // The expressions of smart variables are only compiled to instructions, not parsed as part of the files
+    let variables_read_by_smart_variables = state
+        .smart_variables
+        .values()
+        .flat_map(|node| &node.instructions)
+        .filter(|instruction| instruction.opcode == OpCode::PushVariable as i32)
+        .map(|instruction| instruction.read_operand::<String>(0));
+    used_variables.extend(variables_read_by_smart_variables);
+
+    // The variables used for visit tracking are managed by the compiler, not the user
+    let tracking_variable_prefix = Library::generate_unique_visited_variable_for_node("");
+    let diagnostics: Vec<_> = state
+        .derived_variable_declarations
+        .iter()
+        .filter(|declaration| {
+            !declaration.is_implicit
+                && !declaration.name.starts_with(&tracking_variable_prefix)
+                && !used_variables.contains(&declaration.name)
+        })
+        .map(|declaration| {
+            let mut diagnostic = Diagnostic::from_message(format!(
+                "Variable {} is declared but never used",
+                declaration.name
+            ))
+            .with_severity(DiagnosticSeverity::Warning)
+            .with_code(DiagnosticCode::UnusedVariable);
+            if let DeclarationSource::File(file_name) = &declaration.source_file_name {
+                diagnostic = diagnostic.with_file_name(file_name.clone());
+            }
+            if let Some(range) = declaration.range.clone() {
+                diagnostic = diagnostic.with_range(range);
+            }
+            diagnostic
+        })
+        .collect();
+    state.diagnostics.extend(diagnostics);
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_about_declared_but_unused_variables() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $playerName = \"Alex\">>
+<<declare $gold = 0>>
+<<set $gold to $gold + 10>>
+You have {$gold} gold.
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        let unused_warnings: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|warning| warning.message.contains("never used"))
+            .collect();
+        assert_eq!(unused_warnings.len(), 1);
+        assert_eq!(
+            unused_warnings[0].message,
+            "Variable $playerName is declared but never used"
+        );
+        assert_eq!(unused_warnings[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(unused_warnings[0].file_name.as_deref(), Some("test.yarn"));
+    }
+
+    #[test]
+    fn does_not_warn_about_tracking_variables() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+tracking: always
+---
+Hello there
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert!(result.warnings.is_empty());
+    }
+}
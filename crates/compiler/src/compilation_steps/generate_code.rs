@@ -3,7 +3,7 @@ use crate::prelude::generated::yarnspinnerparser::YarnSpinnerParserTreeWalker;
 use crate::prelude::*;
 use crate::visitors::KnownTypes;
 use crate::Result;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 
 pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIntermediate {
     let has_errors = state.diagnostics.has_errors();
@@ -12,6 +12,15 @@ pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIn
         vec![]
     } else {
         // No errors! Go ahead and generate the code for all parsed files.
+        let constants: HashMap<_, _> = state
+            .known_variable_declarations
+            .iter()
+            .filter(|declaration| declaration.is_constant)
+            .filter_map(|declaration| {
+                let value = declaration.default_value.clone()?;
+                Some((declaration.name.clone(), value))
+            })
+            .collect();
         let template = Compilation {
             string_table: state.string_table.0.clone(),
             contains_implicit_string_tags: state.string_table.contains_implicit_string_tags(),
@@ -25,6 +34,7 @@ pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIn
                 generate_code_for_file(
                     &mut state.tracking_nodes,
                     state.known_types.clone(),
+                    constants.clone(),
                     template.clone(),
                     file,
                 )
@@ -43,10 +53,11 @@ pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIn
         Err(CompilerError(total_diagnostics))
     } else {
         let compilations = results.into_iter().map(|r| r.unwrap());
-        Ok(Compilation::combine(
-            compilations,
-            state.string_table.clone(),
-        ))
+        Ok(Compilation {
+            nodes: state.node_infos.clone(),
+            commands: state.commands.clone(),
+            ..Compilation::combine(compilations, state.string_table.clone())
+        })
     };
 
     state.result = Some(result);
@@ -54,14 +65,16 @@ pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIn
 }
 
 fn generate_code_for_file<'a, 'b: 'a, 'input: 'a + 'b>(
-    tracking_nodes: &mut HashSet<String>,
+    tracking_nodes: &mut BTreeSet<String>,
     known_types: KnownTypes,
+    constants: HashMap<String, YarnValue>,
     result_template: Compilation,
     file: &'a FileParseResult<'input>,
 ) -> Result<Compilation> {
     let compiler_listener = Box::new(CompilerListener::new(
-        tracking_nodes.clone(),
+        tracking_nodes.iter().cloned().collect(),
         known_types,
+        constants,
         file.clone(),
     ));
     let compiler_tracking_nodes = compiler_listener.tracking_nodes.clone();
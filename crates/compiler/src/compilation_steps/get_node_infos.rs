@@ -0,0 +1,47 @@
+use crate::prelude::*;
+use crate::visitors::NodeInfoVisitor;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+
+pub(crate) fn get_node_infos(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    for file in &state.parsed_files {
+        let mut visitor = NodeInfoVisitor::new(file.name.clone());
+        visitor.visit(file.tree.as_ref());
+        state.node_infos.extend(visitor.node_infos);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_custom_headers() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+group: Intro
+position: 10,-20
+---
+Hello there
+===
+title: Other
+---
+General Kenobi
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert_eq!(result.nodes.len(), 2);
+        let start = &result.nodes[0];
+        assert_eq!(start.title, "Start");
+        assert_eq!(start.file_name, "test.yarn");
+        assert_eq!(start.line_number, 1);
+        assert_eq!(start.headers.len(), 3);
+        assert_eq!(start.header("group"), Some("Intro"));
+        assert_eq!(start.header("position"), Some("10,-20"));
+        assert_eq!(result.nodes[1].title, "Other");
+        assert_eq!(result.nodes[1].header("group"), None);
+    }
+}
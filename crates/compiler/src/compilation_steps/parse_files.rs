@@ -1,9 +1,60 @@
 use crate::prelude::*;
 
 pub(crate) fn parse_files(mut state: CompilationIntermediate) -> CompilationIntermediate {
-    for (file, chars) in state.job.files.iter().zip(state.file_chars.iter()) {
-        let parse_result = parse_syntax_tree(file, chars, &mut state.diagnostics);
+    // Files parsed ahead of time through `Compiler::parse_files` are passed in already
+    if !state.parsed_files.is_empty() {
+        return state;
+    }
+    // Parsing is by far the most expensive part of a compilation, and every file is parsed independently,
+    // so we hand the files to a thread pool. The results are collected in file order, so
+    // everything that runs afterwards (e.g. the string table registration) sees the same order
+    // as a sequential parse would.
+    for ParsedFile(parse_result, diagnostics) in parse_all(&state.job.files, &state.file_chars) {
+        state.diagnostics.extend(diagnostics);
         state.parsed_files.push(parse_result);
     }
     state
 }
+
+/// The result of parsing a single file on a worker thread, together with the diagnostics produced while parsing it.
+struct ParsedFile<'input>(FileParseResult<'input>, Vec<Diagnostic>);
+
+// SAFETY: The parse tree and parser are built on `Rc`, which is why they are not `Send` on their own.
+// However, `parse_syntax_tree` drops all of its own clones of those `Rc`s before returning,
+// so every `Rc` reachable from a `ParsedFile` is owned by that `ParsedFile` alone and moves together with it.
+// The only state that the generated lexer and parser share between instances are their ATN and DFA caches,
+// which are already thread-safe.
+unsafe impl Send for ParsedFile<'_> {}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_all<'input>(
+    files: &'input [File],
+    file_chars: &[&'input [u32]],
+) -> Vec<ParsedFile<'input>> {
+    use rayon::prelude::*;
+
+    files
+        .par_iter()
+        .zip(file_chars.par_iter())
+        .map(|(file, chars)| parse_file(file, chars))
+        .collect()
+}
+
+/// There are no threads to hand the files to on the web, so we parse them one after the other.
+#[cfg(target_arch = "wasm32")]
+fn parse_all<'input>(
+    files: &'input [File],
+    file_chars: &[&'input [u32]],
+) -> Vec<ParsedFile<'input>> {
+    files
+        .iter()
+        .zip(file_chars.iter())
+        .map(|(file, chars)| parse_file(file, chars))
+        .collect()
+}
+
+fn parse_file<'input>(file: &'input File, chars: &'input [u32]) -> ParsedFile<'input> {
+    let mut diagnostics = Vec::new();
+    let parse_result = parse_syntax_tree(file, chars, &mut diagnostics);
+    ParsedFile(parse_result, diagnostics)
+}
@@ -3,8 +3,13 @@ use crate::visitors::{LastLineBeforeOptionsVisitor, StringTableGeneratorVisitor}
 use antlr_rust::tree::ParseTreeVisitorCompat;
 
 pub(crate) fn register_strings(mut state: CompilationIntermediate) -> CompilationIntermediate {
-    // First pass: parse all files, generate their syntax trees,
-    // and figure out what variables they've declared
+    if state.compilation_type == CompilationType::CheckOnly {
+        return state;
+    }
+    // The files were parsed in parallel, but their strings are merged one file after the other, in file order.
+    // The generated implicit line IDs depend on the size of the string table at the time of insertion,
+    // and duplicate line IDs are detected against the strings of all previous files,
+    // so this is what keeps the string table identical between compilations.
     for file in &state.parsed_files {
         // ok now we will add in our lastline tags
         // we do this BEFORE we build our strings table otherwise the tags will get missed
@@ -1,5 +1,6 @@
 use crate::prelude::generated::yarnspinnerparser::{DialogueContextAttrs, NodeContextAttrs};
 use crate::prelude::*;
+use antlr_rust::parser_rule_context::ParserRuleContext;
 use antlr_rust::token::Token;
 use std::collections::HashMap;
 
@@ -48,14 +49,78 @@ pub(crate) fn validate_unique_node_names(
         .into_iter()
         .filter(|(_, nodes)| nodes.len() > 1)
     {
-        // More than one node has this name! Report an error on both.
-        for (header_context, file) in nodes {
+        // More than one node has this name! Report an error on both,
+        // each naming where the others are so they can be found without searching
+        let locations: Vec<_> = nodes
+            .iter()
+            .map(|(header_context, file)| {
+                format!(
+                    "{}:{}",
+                    file.name,
+                    header_context.start().get_line_as_usize()
+                )
+            })
+            .collect();
+        for (index, (header_context, file)) in nodes.iter().enumerate() {
+            let other_locations: Vec<_> = locations
+                .iter()
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != index)
+                .map(|(_, location)| location.as_str())
+                .collect();
             state.diagnostics.push(
-                Diagnostic::from_message(format!("More than one node is named {name}",))
-                    .with_file_name(file.name.clone())
-                    .with_parser_context(header_context.as_ref(), file.tokens()),
+                Diagnostic::from_message(format!(
+                    "More than one node is named {name}, it is also defined at {}",
+                    other_locations.join(", ")
+                ))
+                .with_file_name(file.name.clone())
+                .with_parser_context(header_context.as_ref(), file.tokens())
+                .with_code(DiagnosticCode::DuplicateNodeName),
             );
         }
     }
     state
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_duplicate_node_names_with_both_locations() {
+        let mut compiler = Compiler::new();
+        compiler
+            .add_file(File {
+                file_name: "first.yarn".to_string(),
+                source: "title: Start\n---\nHello\n===\n".to_string(),
+            })
+            .add_file(File {
+                file_name: "second.yarn".to_string(),
+                source: "title: Other\n---\nHi\n===\ntitle: Start\n---\nHello again\n===\n"
+                    .to_string(),
+            });
+
+        let error = compiler.compile().unwrap_err();
+        let duplicates: Vec<_> = error
+            .0
+            .errors()
+            .filter(|diagnostic| diagnostic.code == Some(DiagnosticCode::DuplicateNodeName))
+            .collect();
+
+        assert_eq!(duplicates.len(), 2);
+        let first = duplicates
+            .iter()
+            .find(|diagnostic| diagnostic.file_name.as_deref() == Some("first.yarn"))
+            .unwrap();
+        assert!(first.message.contains("second.yarn:5"), "{}", first.message);
+        let second = duplicates
+            .iter()
+            .find(|diagnostic| diagnostic.file_name.as_deref() == Some("second.yarn"))
+            .unwrap();
+        assert!(
+            second.message.contains("first.yarn:1"),
+            "{}",
+            second.message
+        );
+    }
+}
@@ -2,14 +2,20 @@
 //! and <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/CompilationJob.cs>
 
 use crate::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use yarnspinner_core::prelude::*;
 
 mod add_tags_to_lines;
 pub(crate) mod antlr_rust_ext;
+mod parse;
+mod parsed_files;
+mod recompile_file;
 pub(crate) mod run_compilation;
 pub(crate) mod utils;
 
+pub use self::add_tags_to_lines::tag_lines;
+pub use self::parsed_files::{ParsedFiles, SourceCodePoints};
+
 #[allow(missing_docs)]
 pub type Result<T> = std::result::Result<T, CompilerError>;
 
@@ -43,6 +49,26 @@ pub struct Compiler {
 
     /// The declarations for variables.
     pub variable_declarations: Vec<Declaration>,
+
+    /// Whether to warn about nodes that jump to each other in a cycle without running any lines, options or commands.
+    /// Such a cycle would never finish once entered. By default, this is `false`.
+    pub detect_jump_cycles: bool,
+
+    /// Whether every warning should be reported as an error instead, which makes the compilation fail, e.g. in CI.
+    /// By default, this is `false`.
+    pub treat_warnings_as_errors: bool,
+
+    /// Whether to skip generating the variables that track node visits if no script calls `visited` or `visited_count`.
+    /// Nodes with the `tracking: always` header are then not tracked either, which keeps their variables out of
+    /// [`Compilation::declarations`]. By default, this is `false`.
+    pub skip_unused_visit_tracking: bool,
+
+    /// The names of the markup attributes that the game handles, e.g. through
+    /// [`Dialogue::add_markup_attribute_handler`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/struct.Dialogue.html#method.add_markup_attribute_handler)
+    /// or by styling the text of [`Line::attributes`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/struct.Line.html#structfield.attributes).
+    /// If set, the compiler warns about attributes in lines that are neither in this list nor built into the runtime, such as `select` or `nomarkup`.
+    /// By default, this is `None`, which does not check the names of attributes at all.
+    pub markup_attributes: Option<Vec<String>>,
 }
 
 impl Compiler {
@@ -51,6 +77,26 @@ impl Compiler {
         Self::default()
     }
 
+    /// Creates a new [`Compiler`] with the default settings and all `.yarn` files found in the given directory and its subdirectories added.
+    /// Files ending in `.upgraded.yarn` are skipped. The files are added in alphabetical order of their paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the offending path if a directory cannot be listed or a file cannot be read.
+    pub fn from_directory(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut file_paths = Vec::new();
+        collect_yarn_files(path.as_ref(), &mut file_paths)?;
+        file_paths.sort();
+
+        let mut compiler = Self::new();
+        for file_path in file_paths {
+            compiler
+                .try_read_file(&file_path)
+                .map_err(|error| with_path(error, &file_path))?;
+        }
+        Ok(compiler)
+    }
+
     /// Adds a file to the compilation.
     pub fn add_file(&mut self, file: File) -> &mut Self {
         self.files.push(file);
@@ -91,6 +137,36 @@ impl Compiler {
         self
     }
 
+    /// Sets whether the compiler should warn about cycles of nodes that only jump to each other. See [`Compiler::detect_jump_cycles`].
+    pub fn with_jump_cycle_detection(&mut self, detect_jump_cycles: bool) -> &mut Self {
+        self.detect_jump_cycles = detect_jump_cycles;
+        self
+    }
+
+    /// Sets whether the compiler should escalate warnings to errors. See [`Compiler::treat_warnings_as_errors`].
+    pub fn with_warnings_as_errors(&mut self, treat_warnings_as_errors: bool) -> &mut Self {
+        self.treat_warnings_as_errors = treat_warnings_as_errors;
+        self
+    }
+
+    /// Sets whether the compiler should skip visit tracking when no script uses it. See [`Compiler::skip_unused_visit_tracking`].
+    pub fn with_unused_visit_tracking_skipped(
+        &mut self,
+        skip_unused_visit_tracking: bool,
+    ) -> &mut Self {
+        self.skip_unused_visit_tracking = skip_unused_visit_tracking;
+        self
+    }
+
+    /// Sets the names of the markup attributes the game handles, which makes the compiler warn about all others. See [`Compiler::markup_attributes`].
+    pub fn with_markup_attributes(
+        &mut self,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> &mut Self {
+        self.markup_attributes = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
     /// Adds a variable declaration to the compilation.
     pub fn declare_variable(&mut self, declaration: Declaration) -> &mut Self {
         self.variable_declarations.push(declaration);
@@ -101,6 +177,146 @@ impl Compiler {
     pub fn compile(&self) -> Result<Compilation> {
         run_compilation::compile(self)
     }
+
+    /// Compiles a single expression such as `$gold + 5 > 10` against the library and variable declarations of this compiler.
+    /// The files previously added are ignored.
+    ///
+    /// The instructions of the returned [`Node`] leave the value of the expression on the stack when run,
+    /// which is what `Dialogue::evaluate` in the runtime expects.
+    ///
+    /// ## Implementation notes
+    ///
+    /// Not part of the original implementation. The expression is compiled as the value assigned to a variable
+    /// in a synthetic node, of which only the instructions leading up to storing the variable are kept.
+    pub fn compile_expression(&self, expression: &str) -> Result<Node> {
+        self.compile_typed_expression(expression)
+            .map(|(node, _type)| node)
+    }
+
+    /// Like [`Compiler::compile_expression`], but additionally returns the type of the expression,
+    /// which the type checker inferred for the synthetic variable it was assigned to.
+    pub(crate) fn compile_typed_expression(&self, expression: &str) -> Result<(Node, Type)> {
+        const NODE_NAME: &str = "YarnSpinnerExpression";
+        const RESULT_VARIABLE_NAME: &str = "$yarn_spinner_expression_result";
+        let source_with = |expression: &str| {
+            format!(
+                "title: {NODE_NAME}\n---\n<<set {RESULT_VARIABLE_NAME} to ({expression})>>\n===\n"
+            )
+        };
+        check_expression_is_self_contained(expression, source_with)?;
+        let file = File {
+            file_name: "<expression>".to_owned(),
+            source: source_with(expression),
+        };
+        let compiler = Self {
+            files: vec![file],
+            compilation_type: CompilationType::FullCompilation,
+            // Only errors matter here, since the warnings of the synthetic node are discarded
+            treat_warnings_as_errors: false,
+            ..self.clone()
+        };
+        let compilation = compiler.compile()?;
+        let r#type = compilation
+            .declarations
+            .iter()
+            .find(|declaration| declaration.name == RESULT_VARIABLE_NAME)
+            .map(|declaration| declaration.r#type.clone())
+            .expect("The checked expression is always assigned to the result variable");
+        let mut node = compilation
+            .program
+            .and_then(|mut program| program.nodes.remove(NODE_NAME))
+            .expect("The checked expression is always compiled into the synthetic node");
+        let store_index = node
+            .instructions
+            .iter()
+            .position(|instruction| instruction.opcode == OpCode::StoreVariable as i32)
+            .expect("The checked expression is always stored in the result variable");
+        node.instructions.truncate(store_index);
+        node.labels.clear();
+        Ok((node, r#type))
+    }
+}
+
+/// Returns an error unless `expression` consists only of expression tokens with balanced parentheses.
+/// Otherwise, it could end the statement it is compiled in and smuggle in statements of its own, e.g. `1)>> <<set $gold to (2`.
+///
+/// `source_with` embeds an expression into the source it is compiled in. The tokens around the expression
+/// must be the same as around a plain number.
+fn check_expression_is_self_contained(
+    expression: &str,
+    source_with: impl Fn(&str) -> String,
+) -> Result<()> {
+    let token_types = |expression: &str| -> Vec<String> {
+        tokenize(&source_with(expression))
+            .into_iter()
+            .map(|token| token.token_type)
+            .collect()
+    };
+    let placeholder_tokens = token_types("0");
+    let placeholder_index = placeholder_tokens
+        .iter()
+        .position(|token_type| token_type == "NUMBER")
+        .unwrap();
+    let prefix = &placeholder_tokens[..placeholder_index];
+    let suffix = &placeholder_tokens[placeholder_index + 1..];
+
+    let tokens = token_types(expression);
+    let is_self_contained = !expression.contains(['\n', '\r'])
+        && tokens.len() > prefix.len() + suffix.len()
+        && tokens.starts_with(prefix)
+        && tokens.ends_with(suffix)
+        && is_balanced_expression(&tokens[prefix.len()..tokens.len() - suffix.len()]);
+    if is_self_contained {
+        return Ok(());
+    }
+    Err(CompilerError(vec![Diagnostic::from_message(format!(
+        "\"{expression}\" is not a single expression"
+    ))
+    .with_file_name("<expression>")]))
+}
+
+fn is_balanced_expression(token_types: &[String]) -> bool {
+    let mut depth = 0_usize;
+    for token_type in token_types {
+        match token_type.as_str() {
+            "LPAREN" => depth += 1,
+            "RPAREN" => match depth.checked_sub(1) {
+                Some(new_depth) => depth = new_depth,
+                None => return false,
+            },
+            "STRING" | "FUNC_ID" | "VAR_ID" | "NUMBER" | "COMMA" | "DOT" | "EXPR_WS" => {}
+            token_type
+                if token_type.starts_with("OPERATOR_") || token_type.starts_with("KEYWORD_") => {}
+            _ => return false,
+        }
+    }
+    depth == 0
+}
+
+fn collect_yarn_files(directory: &Path, file_paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let entries = std::fs::read_dir(directory).map_err(|error| with_path(error, directory))?;
+    for entry in entries {
+        let path = entry.map_err(|error| with_path(error, directory))?.path();
+        if path.is_dir() {
+            collect_yarn_files(&path, file_paths)?;
+            continue;
+        }
+        let is_yarn_file = path
+            .extension()
+            .is_some_and(|extension| extension == "yarn");
+        // ".upgraded.yarn" files are written by the upgrader next to the originals
+        let is_upgraded = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(".upgraded.yarn"));
+        if is_yarn_file && !is_upgraded {
+            file_paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn with_path(error: std::io::Error, path: &Path) -> std::io::Error {
+    std::io::Error::new(error.kind(), format!("{}: {error}", path.display()))
 }
 
 /// Represents the contents of a file to compile.
@@ -145,6 +361,14 @@ pub enum CompilationType {
 
     /// The compiler will generate a string table only.
     StringsOnly,
+
+    /// The compiler will check the script and report its diagnostics without building a string table
+    /// or generating code, e.g. for quick checks in an editor.
+    /// Like [`CompilationType::DeclarationsOnly`], the declarations, file tags, nodes and commands are still returned,
+    /// but [`Compilation::string_table`] stays empty.
+    ///
+    /// Since the string table is skipped, duplicate line IDs are not reported in this mode.
+    CheckOnly,
 }
 
 #[cfg(test)]
@@ -170,4 +394,217 @@ a {1 + 3} cool expression
         };
         Compiler::new().add_file(file).compile().unwrap();
     }
+
+    #[test]
+    fn reads_yarn_files_from_directory() {
+        let directory =
+            std::env::temp_dir().join(format!("yarnspinner_from_directory_{}", std::process::id()));
+        let nested = directory.join("chapter_two");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(directory.join("one.yarn"), "title: One\n---\nHi\n===\n").unwrap();
+        std::fs::write(nested.join("two.yarn"), "title: Two\n---\nHo\n===\n").unwrap();
+        std::fs::write(
+            directory.join("one.upgraded.yarn"),
+            "title: One\n---\n===\n",
+        )
+        .unwrap();
+        std::fs::write(directory.join("notes.txt"), "not yarn").unwrap();
+
+        let compiler = Compiler::from_directory(&directory);
+        std::fs::remove_dir_all(&directory).unwrap();
+        let compiler = compiler.unwrap();
+
+        let file_names: Vec<_> = compiler
+            .files
+            .iter()
+            .map(|file| Path::new(&file.file_name).file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(file_names, ["two.yarn", "one.yarn"]);
+        let program = compiler.compile().unwrap().program.unwrap();
+        assert_eq!(program.nodes.len(), 2);
+    }
+
+    #[test]
+    fn reports_missing_directory() {
+        let directory = Path::new("this/directory/does/not/exist");
+        let error = Compiler::from_directory(directory).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(error.to_string().contains(&directory.display().to_string()));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn parallel_parsing_produces_same_output_as_sequential_parsing() {
+        let files = (0..32).map(|i| File {
+            file_name: format!("test_{i}.yarn"),
+            source: format!(
+                "title: Node{i}
+---
+First line of file {i}
+Second line of file {i} #line:explicit_{i}
+-> Option of file {i}
+==="
+            ),
+        });
+        let mut compiler = Compiler::new();
+        compiler.add_files(files);
+        let compile_with_threads = |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(|| compiler.compile().unwrap())
+        };
+
+        let sequential = compile_with_threads(1);
+        // Every file registers two implicit strings and one explicit string,
+        // so the implicit IDs only line up if the files were merged in order
+        for i in 0..32 {
+            let implicit_id = LineId::from(format!("line:test_{i}.yarn-Node{i}-{}", i * 3));
+            assert_eq!(
+                sequential.string_table[&implicit_id].text,
+                format!("First line of file {i}")
+            );
+        }
+        for _ in 0..4 {
+            let parallel = compile_with_threads(8);
+            assert_eq!(sequential.string_table, parallel.string_table);
+            assert_eq!(sequential.program, parallel.program);
+            assert_eq!(sequential.warnings, parallel.warnings);
+        }
+    }
+
+    #[test]
+    fn returns_error_for_invalid_syntax() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<if $foo
+===
+"
+            .to_string(),
+        };
+        let error = Compiler::new().add_file(file).compile().unwrap_err();
+
+        assert!(error.0.has_errors());
+        assert!(error
+            .0
+            .errors()
+            .any(|diagnostic| diagnostic.code == Some(DiagnosticCode::SyntaxError)));
+        let error: &dyn std::error::Error = &error;
+        assert!(!error.to_string().is_empty());
+    }
+
+    #[test]
+    fn filters_diagnostics_by_severity() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $unused = 0>>
+Hello there
+===
+"
+            .to_string(),
+        };
+        let compilation = Compiler::new().add_file(file).compile().unwrap();
+
+        assert!(!compilation.warnings.has_errors());
+        assert_eq!(compilation.warnings.errors().count(), 0);
+        let warnings: Vec<_> = compilation.warnings.warnings().collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Variable $unused is declared but never used"
+        );
+    }
+
+    #[test]
+    fn check_only_skips_string_table_but_reports_diagnostics() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $unused = 0>>
+Hello there
+-> An option
+===
+"
+            .to_string(),
+        };
+        let compilation = Compiler::new()
+            .add_file(file)
+            .with_compilation_type(CompilationType::CheckOnly)
+            .compile()
+            .unwrap();
+
+        assert!(compilation.string_table.is_empty());
+        assert!(compilation.program.is_none());
+        let warnings: Vec<_> = compilation.warnings.warnings().collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Variable $unused is declared but never used"
+        );
+        assert!(compilation
+            .declarations
+            .iter()
+            .any(|declaration| declaration.name == "$unused"));
+    }
+
+    #[test]
+    fn check_only_reports_errors() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $number = 5>>
+<<set $number = \"5\">>
+===
+"
+            .to_string(),
+        };
+        let error = Compiler::new()
+            .add_file(file)
+            .with_compilation_type(CompilationType::CheckOnly)
+            .compile()
+            .unwrap_err();
+
+        assert!(
+            error
+                .0
+                .errors()
+                .any(|diagnostic| diagnostic.message
+                    == "$number (Number) cannot be assigned a String")
+        );
+    }
+
+    #[test]
+    fn escalates_warnings_to_errors_if_requested() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $unused = 0>>
+Hello there
+===
+"
+            .to_string(),
+        };
+        let error = Compiler::new()
+            .add_file(file)
+            .with_warnings_as_errors(true)
+            .compile()
+            .unwrap_err();
+
+        let errors: Vec<_> = error.0.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "Variable $unused is declared but never used"
+        );
+        assert_eq!(error.0.warnings().count(), 0);
+    }
 }
@@ -68,6 +68,23 @@ impl Compiler {
     }
 }
 
+/// Adds a `#line:` tag to every line, option and shortcut option in `source` that does not have one yet,
+/// and returns the rewritten source. Existing tags are kept as they are, and the new ones never collide with them.
+///
+/// The IDs are derived from the text of the lines, so tagging the same source twice gives the same result.
+/// Source that cannot be parsed is returned unchanged. Use [`Compiler::add_tags_to_lines`] to get the diagnostics
+/// or to avoid collisions with the tags of other files.
+///
+/// ## Implementation note
+///
+/// Not part of the original. Corresponds to the `ysc tag` command of the upstream console tool.
+pub fn tag_lines(source: &str) -> String {
+    match Compiler::add_tags_to_lines(source, Vec::new()) {
+        Ok(Some(tagged_source)) => tagged_source,
+        Ok(None) | Err(_) => source.to_owned(),
+    }
+}
+
 /// Parses a string of Yarn source code, and produces a [`FileParseResult`]
 /// and (if there were any problems) a collection of [`Diagnostic`]s.
 fn parse_source<'a, 'b: 'a>(
@@ -80,3 +97,102 @@ fn parse_source<'a, 'b: 'a>(
 
     (result, diagnostics)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn generates_deterministic_line_tags() {
+        let source = "title: Start
+---
+Hello there
+Hello there
+-> Bye
+===
+title: Other
+---
+Hello there
+===
+";
+        let output = Compiler::add_tags_to_lines(source, Vec::new())
+            .unwrap()
+            .unwrap();
+
+        let tag_regex = Regex::new(r"#(line:\w+)").unwrap();
+        let tags: Vec<_> = tag_regex
+            .captures_iter(&output)
+            .map(|captures| captures[1].to_owned())
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                "line:06425b6",
+                "line:0642328",
+                "line:00e2b2e",
+                "line:027cfb7"
+            ]
+        );
+        let second_output = Compiler::add_tags_to_lines(source, Vec::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(output, second_output);
+    }
+
+    #[test]
+    fn skips_existing_line_tags() {
+        let source = "title: Start
+---
+Hello there
+===
+";
+        let output = Compiler::add_tags_to_lines(source, vec!["line:06425b6".into()])
+            .unwrap()
+            .unwrap();
+
+        assert!(output.contains("#line:06425b7"));
+    }
+
+    #[test]
+    fn tag_lines_keeps_existing_tags() {
+        let source = "title: Start
+---
+Hello there #line:06425b6
+Hello there
+Alice: Nice to see you. #line:greeting
+-> Bye
+    Goodbye #hidden
+===
+";
+        let output = tag_lines(source);
+
+        let tag_regex = Regex::new(r"#(line:\w+)").unwrap();
+        let tags: Vec<_> = tag_regex
+            .captures_iter(&output)
+            .map(|captures| captures[1].to_owned())
+            .collect();
+        assert_eq!(tags.len(), 5);
+        assert_eq!(tags[0], "line:06425b6");
+        // The same text would get the same ID as the existing tag without the check for collisions
+        assert_eq!(tags[1], "line:06425b7");
+        assert_eq!(tags[2], "line:greeting");
+        let unique_tags: std::collections::HashSet<_> = tags.iter().collect();
+        assert_eq!(unique_tags.len(), tags.len());
+        assert!(output.contains("Goodbye #hidden #line:"));
+
+        let untagged_lines: String = tag_regex.replace_all(&output, "").into();
+        let original_lines: String = tag_regex.replace_all(source, "").into();
+        assert_eq!(
+            untagged_lines
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>(),
+            original_lines
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(tag_lines(&output), output);
+    }
+}
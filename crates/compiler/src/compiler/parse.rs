@@ -0,0 +1,102 @@
+use crate::listeners::DiagnosticVec;
+use crate::prelude::generated::yarnspinnerparser::{self, YarnSpinnerParserContext};
+use crate::prelude::*;
+use antlr_rust::parser_rule_context::ParserRuleContext;
+use antlr_rust::rule_context::RuleContext;
+use antlr_rust::tree::{ParseTree, Tree};
+
+impl Compiler {
+    /// Parses the Yarn files previously added without compiling them, and returns their [`SyntaxTree`]s
+    /// in the same order as [`Compiler::files`].
+    ///
+    /// This is useful for tools that only care about the structure of the source code, such as formatters.
+    /// None of the later compilation steps are run, so e.g. type errors are not reported.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if any of the files contains a syntax error.
+    pub fn parse(&self) -> Result<Vec<SyntaxTree>> {
+        let mut diagnostics = Vec::new();
+        let trees = self
+            .files
+            .iter()
+            .map(|file| {
+                let chars: Vec<_> = file.source.chars().map(|c| c as u32).collect();
+                let parse_result = parse_syntax_tree(file, &chars, &mut diagnostics);
+                SyntaxTree {
+                    file_name: file.file_name.clone(),
+                    root: to_syntax_node(parse_result.tree.as_ref()),
+                }
+            })
+            .collect();
+        if diagnostics.has_errors() {
+            Err(CompilerError(diagnostics))
+        } else {
+            Ok(trees)
+        }
+    }
+}
+
+fn to_syntax_node<'input>(ctx: &(impl YarnSpinnerParserContext<'input> + ?Sized)) -> SyntaxNode {
+    // Tokens have no rule of their own, so their index does not point into the list of rule names
+    let rule = yarnspinnerparser::ruleNames
+        .get(ctx.get_rule_index())
+        .map(|rule| rule.to_string());
+    SyntaxNode {
+        rule,
+        text: ctx.get_text(),
+        line_number: ctx.start().get_line_as_usize(),
+        children: ctx
+            .get_children()
+            .map(|child| to_syntax_node(child.as_ref()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_syntax_tree_without_compiling() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+Hello there
+<<set $undeclared to 1 + \"not a number\">>
+===
+title: Other
+---
+General Kenobi
+==="
+            .to_string(),
+        };
+        let trees = Compiler::new().add_file(file).parse().unwrap();
+
+        assert_eq!(trees.len(), 1);
+        let tree = &trees[0];
+        assert_eq!(tree.file_name, "test.yarn");
+        assert_eq!(tree.root.rule.as_deref(), Some("dialogue"));
+        assert_eq!(tree.nodes().count(), 2);
+        let other = tree.nodes().nth(1).unwrap();
+        assert_eq!(other.line_number, 6);
+        assert_eq!(other.children_of_rule("header").count(), 1);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+<<if $foo
+===
+"
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).parse();
+
+        assert!(result.unwrap_err().0.has_errors());
+    }
+}
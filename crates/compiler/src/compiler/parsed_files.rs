@@ -0,0 +1,167 @@
+use crate::prelude::*;
+use std::fmt::{Debug, Formatter};
+
+/// The source code of a [`Compiler`]'s files, decoded into the Unicode code points that the parser reads.
+/// Created by [`Compiler::source_code_points`] and passed to [`Compiler::parse_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceCodePoints(Vec<Vec<u32>>);
+
+/// The parse trees of a [`Compiler`]'s files, which can be checked and compiled without parsing the files again.
+/// This is useful for editors that check a file on every change and only compile it when it is about to run.
+///
+/// Created by [`Compiler::parse_files`].
+///
+/// ## Lifetimes
+///
+/// The parse trees point into the [`SourceCodePoints`] instead of copying them,
+/// so both the [`Compiler`] and the [`SourceCodePoints`] must outlive the [`ParsedFiles`].
+/// The trees are reference counted without synchronization, so [`ParsedFiles`] cannot be sent to other threads either.
+///
+/// [`ParsedFiles::check`] leaves the trees as they are and can be called any number of times.
+/// [`ParsedFiles::compile`] adds the implicit line IDs to the trees while building the string table,
+/// which is why it consumes the [`ParsedFiles`]. To compile again afterwards, parse the files again.
+pub struct ParsedFiles<'a> {
+    compiler: &'a Compiler,
+    file_chars: Vec<&'a [u32]>,
+    files: Vec<FileParseResult<'a>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Compiler {
+    /// Decodes the source code of the files previously added, which is the first step of [`Compiler::parse_files`].
+    pub fn source_code_points(&self) -> SourceCodePoints {
+        SourceCodePoints(
+            self.files
+                .iter()
+                .map(|file| file.source.chars().map(|c| c as u32).collect())
+                .collect(),
+        )
+    }
+
+    /// Parses the files previously added so that they can be checked and compiled through the returned [`ParsedFiles`]
+    /// without being parsed again. Syntax errors are reported by [`ParsedFiles::check`] and [`ParsedFiles::compile`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `source_code_points` were not created by [`Compiler::source_code_points`] for the same files.
+    pub fn parse_files<'a>(&'a self, source_code_points: &'a SourceCodePoints) -> ParsedFiles<'a> {
+        assert_eq!(
+            self.files.len(),
+            source_code_points.0.len(),
+            "The source code points do not belong to the files of this compiler"
+        );
+        let file_chars: Vec<_> = source_code_points.0.iter().map(Vec::as_slice).collect();
+        let mut diagnostics = Vec::new();
+        let files = self
+            .files
+            .iter()
+            .zip(file_chars.iter().copied())
+            .map(|(file, chars)| parse_syntax_tree(file, chars, &mut diagnostics))
+            .collect();
+        ParsedFiles {
+            compiler: self,
+            file_chars,
+            files,
+            diagnostics,
+        }
+    }
+}
+
+impl<'a> ParsedFiles<'a> {
+    /// Checks the parsed files like a compilation with [`CompilationType::CheckOnly`],
+    /// regardless of the [`Compiler::compilation_type`] of the compiler that parsed them.
+    pub fn check(&self) -> Result<Compilation> {
+        let mut state = self.to_compilation_intermediate();
+        state.compilation_type = CompilationType::CheckOnly;
+        run_compilation_steps(state)
+    }
+
+    /// Compiles the parsed files like [`Compiler::compile`] would, but without parsing them again.
+    pub fn compile(self) -> Result<Compilation> {
+        run_compilation_steps(self.to_compilation_intermediate())
+    }
+
+    fn to_compilation_intermediate(&self) -> CompilationIntermediate<'a> {
+        let mut state = CompilationIntermediate::from_job(self.compiler, self.file_chars.clone());
+        state.parsed_files = self.files.clone();
+        state.diagnostics = self.diagnostics.clone();
+        state
+    }
+}
+
+impl Debug for ParsedFiles<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParsedFiles")
+            .field(
+                "files",
+                &self.files.iter().map(|file| &file.name).collect::<Vec<_>>(),
+            )
+            .field("diagnostics", &self.diagnostics)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiler() -> Compiler {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+<<declare $gold = 5>>
+You have {$gold} gold.
+-> Buy a sword
+    <<set $gold to $gold - 3>>
+    Enjoy your sword
+-> Leave
+===
+"
+            .to_string(),
+        };
+        let mut compiler = Compiler::new();
+        compiler.add_file(file);
+        compiler
+    }
+
+    #[test]
+    fn compiles_from_parsed_files_like_a_fresh_compilation() {
+        let compiler = compiler();
+        let source_code_points = compiler.source_code_points();
+        let parsed_files = compiler.parse_files(&source_code_points);
+
+        let check = parsed_files.check().unwrap();
+        assert!(check.string_table.is_empty());
+        assert!(check.program.is_none());
+        let cached = parsed_files.compile().unwrap();
+
+        let fresh = compiler.compile().unwrap();
+        assert_eq!(cached.program, fresh.program);
+        assert_eq!(cached.string_table, fresh.string_table);
+        assert_eq!(cached.declarations, fresh.declarations);
+        assert_eq!(cached.warnings, fresh.warnings);
+    }
+
+    #[test]
+    fn reports_syntax_errors_of_parsed_files() {
+        let mut compiler = Compiler::new();
+        compiler.add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+<<if $foo
+===
+"
+            .to_string(),
+        });
+        let source_code_points = compiler.source_code_points();
+        let parsed_files = compiler.parse_files(&source_code_points);
+
+        let error = parsed_files.check().unwrap_err();
+        assert!(error
+            .0
+            .errors()
+            .any(|diagnostic| diagnostic.code == Some(DiagnosticCode::SyntaxError)));
+    }
+}
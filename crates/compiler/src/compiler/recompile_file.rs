@@ -0,0 +1,330 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+use yarnspinner_core::prelude::*;
+
+impl Compiler {
+    /// Recompiles a single file that changed since `previous` was produced by this [`Compiler`],
+    /// and merges the result with the parts of `previous` that came from the other files.
+    ///
+    /// This is meant for live-editing workflows, where recompiling every file after each edit would be wasteful.
+    /// If the recompilation succeeds, the changed file replaces the file with the same [`File::file_name`] in [`Compiler::files`],
+    /// or is added to them if there is none. Otherwise, [`Compiler::files`] is left untouched.
+    /// Only that file is parsed again; the declarations of the other files are taken from `previous`
+    /// so that the changed file can still use the variables declared elsewhere.
+    ///
+    /// Lines of the other files keep their line IDs. Implicit line IDs of the changed file may differ from the ones
+    /// a full compilation would generate, so add `#line:` tags to lines whose IDs need to stay stable.
+    /// Analyses that need to see the whole project at once, such as finding unreachable nodes, only consider the changed file.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the changed file does not compile, if it contains a node whose title
+    /// is already used by a node in another file, or if it contains a line whose `#line:` ID is already used by a line in another file.
+    pub fn recompile_file(&mut self, previous: &Compilation, file: File) -> Result<Compilation> {
+        let file_name = file.file_name.clone();
+        let source = DeclarationSource::File(file_name.clone());
+        let previous_node_titles: HashSet<_> = previous
+            .nodes
+            .iter()
+            .filter(|node| node.file_name == file_name)
+            .map(|node| node.title.clone())
+            .collect();
+        let previous_tracking_variables: HashSet<_> = previous_node_titles
+            .iter()
+            .map(|title| Library::generate_unique_visited_variable_for_node(title))
+            .collect();
+
+        let other_declarations: Vec<_> = previous
+            .declarations
+            .iter()
+            .filter(|decl| decl.source_file_name != source)
+            .filter(|decl| !previous_tracking_variables.contains(&decl.name))
+            .cloned()
+            .collect();
+        let file_compiler = Compiler {
+            files: vec![file.clone()],
+            library: self.library.clone(),
+            compilation_type: self.compilation_type.clone(),
+            variable_declarations: other_declarations
+                .iter()
+                .filter(|decl| matches!(decl.source_file_name, DeclarationSource::File(_)))
+                .cloned()
+                .collect(),
+            detect_jump_cycles: self.detect_jump_cycles,
+            treat_warnings_as_errors: self.treat_warnings_as_errors,
+            skip_unused_visit_tracking: self.skip_unused_visit_tracking,
+            markup_attributes: self.markup_attributes.clone(),
+        };
+        let changed = file_compiler.compile()?;
+
+        let other_node_titles: HashSet<_> = previous
+            .nodes
+            .iter()
+            .filter(|node| node.file_name != file_name)
+            .map(|node| node.title.as_str())
+            .collect();
+        let node_collisions = changed
+            .nodes
+            .iter()
+            .filter(|node| other_node_titles.contains(node.title.as_str()))
+            .map(|node| {
+                Diagnostic::from_message(format!("More than one node is named {}", node.title))
+                    .with_file_name(&file_name)
+                    .with_start_line(node.line_number.saturating_sub(1))
+                    .with_code(DiagnosticCode::DuplicateNodeName)
+            });
+        // Implicit line IDs contain the file name, so only explicit `#line:` tags can collide
+        let line_id_collisions = changed
+            .string_table
+            .iter()
+            .filter(|(line_id, _)| {
+                previous
+                    .string_table
+                    .get(line_id)
+                    .is_some_and(|string_info| string_info.file_name != file_name)
+            })
+            .map(|(line_id, string_info)| {
+                Diagnostic::from_message(format!("Duplicate line ID {line_id}"))
+                    .with_file_name(&file_name)
+                    .with_start_line(string_info.line_number.saturating_sub(1))
+            });
+        let collisions: Vec<_> = node_collisions.chain(line_id_collisions).collect();
+        if !collisions.is_empty() {
+            return Err(CompilerError(collisions));
+        }
+        match self.files.iter_mut().find(|f| f.file_name == file_name) {
+            Some(existing_file) => *existing_file = file,
+            None => self.files.push(file),
+        }
+
+        let program = match (previous.program.clone(), changed.program) {
+            (Some(mut program), Some(changed_program)) => {
+                program
+                    .nodes
+                    .retain(|name, _| !previous_node_titles.contains(name));
+                program
+                    .initial_values
+                    .retain(|name, _| other_declarations.iter().any(|decl| &decl.name == name));
+                program.nodes.extend(changed_program.nodes);
+                program
+                    .initial_values
+                    .extend(changed_program.initial_values);
+                Some(program)
+            }
+            (_, changed_program) => changed_program,
+        };
+
+        let mut string_table = previous.string_table.clone();
+        string_table.retain(|_, string_info| string_info.file_name != file_name);
+        string_table.extend(changed.string_table);
+        let contains_implicit_string_tags = string_table
+            .values()
+            .any(|string_info| string_info.is_implicit_tag);
+
+        let changed_declaration_names: HashSet<_> = changed
+            .declarations
+            .iter()
+            .map(|decl| decl.name.clone())
+            .collect();
+        let declarations = other_declarations
+            .into_iter()
+            .filter(|decl| !changed_declaration_names.contains(&decl.name))
+            .chain(changed.declarations)
+            .collect();
+
+        let mut file_tags = previous.file_tags.clone();
+        file_tags.remove(&file_name);
+        file_tags.extend(changed.file_tags);
+
+        let warnings = previous
+            .warnings
+            .iter()
+            .filter(|warning| warning.file_name.as_ref() != Some(&file_name))
+            .cloned()
+            .chain(changed.warnings)
+            .collect();
+
+        let mut debug_info = previous.debug_info.clone();
+        debug_info.retain(|name, _| !previous_node_titles.contains(name));
+        debug_info.extend(changed.debug_info);
+
+        // Keep the nodes in the order of the files they appear in
+        let nodes = self
+            .files
+            .iter()
+            .flat_map(|f| {
+                if f.file_name == file_name {
+                    changed.nodes.clone()
+                } else {
+                    previous
+                        .nodes
+                        .iter()
+                        .filter(|node| node.file_name == f.file_name)
+                        .cloned()
+                        .collect()
+                }
+            })
+            .collect();
+
+        let commands = self
+            .files
+            .iter()
+            .flat_map(|f| {
+                if f.file_name == file_name {
+                    changed.commands.clone()
+                } else {
+                    previous
+                        .commands
+                        .iter()
+                        .filter(|command| command.file_name == f.file_name)
+                        .cloned()
+                        .collect()
+                }
+            })
+            .collect();
+
+        Ok(Compilation {
+            program,
+            string_table,
+            declarations,
+            contains_implicit_string_tags,
+            file_tags,
+            warnings,
+            debug_info,
+            nodes,
+            commands,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(file_name: &str, source: &str) -> File {
+        File {
+            file_name: file_name.to_owned(),
+            source: source.to_owned(),
+        }
+    }
+
+    #[test]
+    fn editing_one_file_keeps_line_ids_of_other_files() {
+        let a = file(
+            "a.yarn",
+            "title: A
+---
+<<declare $gold = 10>>
+Hello from A
+You have {$gold} gold
+-> B
+===
+",
+        );
+        let b = file(
+            "b.yarn",
+            "title: B
+---
+Hello from B
+===
+",
+        );
+        let mut compiler = Compiler::new();
+        compiler.add_file(a).add_file(b);
+        let previous = compiler.compile().unwrap();
+
+        let edited_b = file(
+            "b.yarn",
+            "title: B
+---
+A new first line
+Hello from B
+You still have {$gold} gold
+===
+",
+        );
+        let result = compiler.recompile_file(&previous, edited_b).unwrap();
+
+        let line_ids_of = |compilation: &Compilation, file_name: &str| {
+            let mut ids: Vec<_> = compilation
+                .string_table
+                .iter()
+                .filter(|(_, string_info)| string_info.file_name == file_name)
+                .map(|(id, string_info)| (id.clone(), string_info.text.clone()))
+                .collect();
+            ids.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            ids
+        };
+        assert_eq!(
+            line_ids_of(&previous, "a.yarn"),
+            line_ids_of(&result, "a.yarn")
+        );
+        assert_eq!(line_ids_of(&result, "b.yarn").len(), 3);
+
+        let program = result.program.unwrap();
+        assert!(program.nodes.contains_key("A"));
+        assert!(program.nodes.contains_key("B"));
+        assert!(program.initial_values.contains_key("$gold"));
+        let titles: Vec<_> = result
+            .nodes
+            .iter()
+            .map(|node| node.title.as_str())
+            .collect();
+        assert_eq!(titles, ["A", "B"]);
+        assert!(compiler.files[1].source.contains("A new first line"));
+    }
+
+    #[test]
+    fn detects_node_title_collisions_with_other_files() {
+        let mut compiler = Compiler::new();
+        compiler
+            .add_file(file("a.yarn", "title: A\n---\nHello\n===\n"))
+            .add_file(file("b.yarn", "title: B\n---\nHello\n===\n"));
+        let previous = compiler.compile().unwrap();
+
+        let result = compiler.recompile_file(&previous, file("b.yarn", "title: A\n---\nHi\n===\n"));
+
+        let diagnostics = result.unwrap_err().0;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file_name.as_deref(), Some("b.yarn"));
+        assert!(compiler.files[1].source.starts_with("title: B"));
+    }
+
+    #[test]
+    fn detects_line_id_collisions_with_other_files() {
+        let mut compiler = Compiler::new();
+        compiler
+            .add_file(file("a.yarn", "title: A\n---\nHello #line:hello\n===\n"))
+            .add_file(file("b.yarn", "title: B\n---\nHi #line:hi\n===\n"));
+        let previous = compiler.compile().unwrap();
+
+        let result = compiler.recompile_file(
+            &previous,
+            file("b.yarn", "title: B\n---\nHi #line:hello\n===\n"),
+        );
+
+        let diagnostics = result.unwrap_err().0;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Duplicate line ID line:hello");
+        assert_eq!(diagnostics[0].file_name.as_deref(), Some("b.yarn"));
+        assert_eq!(diagnostics[0].start_line, 2);
+        assert!(compiler.files[1].source.contains("#line:hi"));
+    }
+
+    #[test]
+    fn keeps_files_unchanged_when_recompilation_fails() {
+        let mut compiler = Compiler::new();
+        compiler.add_file(file("a.yarn", "title: A\n---\nHello\n===\n"));
+        let previous = compiler.compile().unwrap();
+
+        let result =
+            compiler.recompile_file(&previous, file("a.yarn", "title: A\n---\n<<if>>\n===\n"));
+        assert!(result.is_err());
+        let result =
+            compiler.recompile_file(&previous, file("b.yarn", "title: B\n---\n<<if>>\n===\n"));
+        assert!(result.is_err());
+
+        assert_eq!(compiler.files.len(), 1);
+        assert_eq!(compiler.files[0].source, "title: A\n---\nHello\n===\n");
+    }
+}
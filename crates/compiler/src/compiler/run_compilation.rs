@@ -4,20 +4,41 @@ use crate::prelude::*;
 use crate::string_table_manager::StringTableManager;
 use crate::visitors::*;
 use crate::Result;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 
 /// Compile Yarn code, as specified by a compilation job.
 pub(crate) fn compile(compiler: &Compiler) -> Result<Compilation> {
+    let chars: Vec<Vec<u32>> = compiler
+        .files
+        .iter()
+        .map(|file| file.source.chars().map(|c| c as u32).collect())
+        .collect();
+    let chars: Vec<_> = chars.iter().map(|c| c.as_slice()).collect();
+    let initial = CompilationIntermediate::from_job(compiler, chars);
+    run_compilation_steps(initial)
+}
+
+/// Runs all compilation steps on the given state. Files that were already parsed
+/// are not parsed again, see [`ParsedFiles`].
+pub(crate) fn run_compilation_steps(initial: CompilationIntermediate) -> Result<Compilation> {
     let compiler_steps: Vec<&CompilationStep> = vec![
         &register_initial_variables,
         &parse_files,
         &register_strings,
         &validate_unique_node_names,
+        &get_node_infos,
+        &find_commands,
+        &check_markup,
         &break_on_job_with_only_strings,
         &get_declarations,
+        &compile_smart_variables,
         &check_types,
+        &find_unused_variables,
         &find_tracking_nodes,
+        &find_unreachable_nodes,
+        &find_jump_cycles,
         &create_declarations_for_tracking_nodes,
+        &create_declarations_for_once_blocks,
         &add_tracking_declarations,
         &resolve_deferred_type_diagnostic,
         &break_on_job_with_only_declarations,
@@ -25,13 +46,6 @@ pub(crate) fn compile(compiler: &Compiler) -> Result<Compilation> {
         &add_initial_value_registrations,
     ];
 
-    let chars: Vec<Vec<u32>> = compiler
-        .files
-        .iter()
-        .map(|file| file.source.chars().map(|c| c as u32).collect())
-        .collect();
-    let chars: Vec<_> = chars.iter().map(|c| c.as_slice()).collect();
-    let initial = CompilationIntermediate::from_job(compiler, chars);
     let intermediate = compiler_steps.into_iter().fold(initial, |state, step| {
         if state.early_break {
             state
@@ -50,6 +64,8 @@ type CompilationStep = dyn Fn(CompilationIntermediate) -> CompilationIntermediat
 
 pub(crate) struct CompilationIntermediate<'input> {
     pub(crate) job: &'input Compiler,
+    /// Usually the job's compilation type, but [`ParsedFiles::check`] overrides it
+    pub(crate) compilation_type: CompilationType,
     pub(crate) file_chars: Vec<&'input [u32]>,
     pub(crate) result: Option<Result<Compilation>>,
     /// All variable declarations that we've encountered, PLUS the ones we knew about before
@@ -58,11 +74,18 @@ pub(crate) struct CompilationIntermediate<'input> {
     pub(crate) derived_variable_declarations: Vec<Declaration>,
     pub(crate) potential_issues: Vec<DeferredTypeDiagnostic>,
     pub(crate) parsed_files: Vec<FileParseResult<'input>>,
-    pub(crate) tracking_nodes: HashSet<String>,
+    /// Sorted so that the generated tracking declarations come out in the same order on every compilation
+    pub(crate) tracking_nodes: BTreeSet<String>,
+    pub(crate) node_infos: Vec<NodeInfo>,
+    pub(crate) commands: Vec<CommandInfo>,
+    /// The node names and indices within their node of all `<<once>>` blocks
+    pub(crate) once_blocks: Vec<(String, usize)>,
     pub(crate) string_table: StringTableManager,
     pub(crate) diagnostics: Vec<Diagnostic>,
     pub(crate) file_tags: HashMap<String, Vec<String>>,
     pub(crate) known_types: KnownTypes,
+    /// The compiled expressions of all smart variables, by variable name
+    pub(crate) smart_variables: HashMap<String, Node>,
     pub(crate) early_break: bool,
 }
 
@@ -70,6 +93,7 @@ impl<'input> CompilationIntermediate<'input> {
     pub(crate) fn from_job(compiler: &'input Compiler, chars: Vec<&'input [u32]>) -> Self {
         Self {
             job: compiler,
+            compilation_type: compiler.compilation_type.clone(),
             file_chars: chars,
             result: Default::default(),
             known_variable_declarations: Default::default(),
@@ -77,10 +101,14 @@ impl<'input> CompilationIntermediate<'input> {
             potential_issues: Default::default(),
             parsed_files: Default::default(),
             tracking_nodes: Default::default(),
+            node_infos: Default::default(),
+            commands: Default::default(),
+            once_blocks: Default::default(),
             string_table: Default::default(),
             diagnostics: Default::default(),
             file_tags: Default::default(),
             known_types: Default::default(),
+            smart_variables: Default::default(),
             early_break: Default::default(),
         }
     }
@@ -221,6 +221,8 @@ pub(crate) fn get_declarations_from_library(library: &Library) -> Vec<Declaratio
                 .map(Some)
                 .collect();
             function_type.parameters = parameters;
+            function_type.optional_parameter_count =
+                function.parameter_types().len() - function.required_parameter_count();
             let return_type = Type::try_from(function.return_type()).unwrap();
             function_type.set_return_type(return_type);
             Declaration::new(name, function_type).with_source_file_name(DeclarationSource::External)
@@ -265,8 +267,71 @@ mod tests {
                         character: 5
                     }
                 )
-                .with_severity(DiagnosticSeverity::Warning),
+                .with_severity(DiagnosticSeverity::Warning)
+                .with_code(DiagnosticCode::MixedIndentation),
             diagnostics[0]
         );
+        assert_eq!("YS0002", diagnostics[0].code.unwrap().as_str());
+    }
+
+    #[test]
+    fn counts_mixed_indentation_in_characters_after_multi_byte_characters() {
+        let mut diagnostics = Vec::new();
+        let mixed_indentation_input = File {
+            file_name: "test.yarn".to_owned(),
+            source: "title: Start
+---
+-> Grüße, Jürgen! 👋
+\t   Schön, dich zu sehen! 😊
+==="
+            .to_owned(),
+        };
+        let chars: Vec<_> = mixed_indentation_input
+            .source
+            .chars()
+            .map(|c| c as u32)
+            .collect();
+        let _parsed_file = parse_syntax_tree(&mixed_indentation_input, &chars, &mut diagnostics);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Some(DiagnosticCode::MixedIndentation), diagnostics[0].code);
+        assert_eq!(
+            Some(
+                Position {
+                    line: 3,
+                    character: 0
+                }..Position {
+                    line: 3,
+                    character: 5
+                }
+            ),
+            diagnostics[0].range
+        );
+        assert_eq!(Some("\t   "), diagnostics[0].context.as_deref());
+    }
+
+    #[test]
+    fn counts_newlines_in_commands_in_characters_after_multi_byte_characters() {
+        let mut diagnostics = Vec::new();
+        let input = File {
+            file_name: "test.yarn".to_owned(),
+            source: "title: Start
+---
+Grüße 👋 <<wait
+für 2>>
+==="
+            .to_owned(),
+        };
+        let chars: Vec<_> = input.source.chars().map(|c| c as u32).collect();
+        let _parsed_file = parse_syntax_tree(&input, &chars, &mut diagnostics);
+        let diagnostic = diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.message == "Newlines are not allowed in commands")
+            .unwrap();
+        let range = diagnostic.range.clone().unwrap();
+        // `Grüße 👋 <<` is 10 characters, but 15 bytes long
+        assert_eq!(2, range.start.line);
+        assert_eq!(10, range.start.character);
+        // `für 2` is 5 characters, but 6 bytes long
+        assert_eq!(5, range.end.character);
     }
 }
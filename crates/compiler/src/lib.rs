@@ -31,9 +31,10 @@ pub mod prelude {
         token_ext::*,
     };
     pub use crate::{
-        compiler::{CompilationType, Compiler, File},
-        listeners::{Diagnostic, DiagnosticSeverity, DiagnosticVec},
+        compiler::{tag_lines, CompilationType, Compiler, File, ParsedFiles, SourceCodePoints},
+        listeners::{Diagnostic, DiagnosticCode, DiagnosticSeverity, DiagnosticVec},
         output::*,
+        parser::{tokenize, TokenInfo},
     };
     pub(crate) use yarnspinner_core::prelude::*;
 }
@@ -5,7 +5,7 @@ use antlr_rust::parser_rule_context::ParserRuleContext;
 use antlr_rust::token::Token;
 use antlr_rust::tree::{ParseTreeListener, ParseTreeVisitorCompat};
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use yarnspinner_core::prelude::*;
 
@@ -27,6 +27,8 @@ pub(crate) struct CompilerListener<'input> {
     pub(crate) tracking_nodes: Rc<RefCell<HashSet<String>>>,
     pub(crate) diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
     pub(crate) types: KnownTypes,
+    /// The values of all compile-time constants, which are emitted in place of the variables referring to them.
+    pub(crate) constants: HashMap<String, YarnValue>,
     /// The current node to which instructions are being added.
     pub(crate) current_node: Option<Node>,
     /// The current debug information that describes [`current_node`].
@@ -34,7 +36,7 @@ pub(crate) struct CompilerListener<'input> {
     /// Whether we are currently parsing the
     /// current node as a 'raw text' node, or as a fully syntactic node.
     is_current_node_raw_text: bool,
-    file: FileParseResult<'input>,
+    pub(crate) file: FileParseResult<'input>,
     label_count: usize,
 }
 
@@ -42,11 +44,13 @@ impl<'input> CompilerListener<'input> {
     pub(crate) fn new(
         tracking_nodes: HashSet<String>,
         types: KnownTypes,
+        constants: HashMap<String, YarnValue>,
         file: FileParseResult<'input>,
     ) -> Self {
         Self {
             file,
             types,
+            constants,
             tracking_nodes: Rc::new(RefCell::new(tracking_nodes)),
             current_node: Default::default(),
             current_debug_info: Default::default(),
@@ -171,10 +175,12 @@ impl<'input> YarnSpinnerParserListener<'input> for CompilerListener<'input> {
             for statement in ctx.statement_all() {
                 visitor.visit(statement.as_ref());
             }
+            visitor.diagnose_unclosed_once_blocks(ctx);
         } else {
             // We are a rawText node. Don't compile it; instead, note the string
             let current_node = self.current_node.as_mut().unwrap();
-            current_node.source_text_string_id = get_line_id_for_node_name(&current_node.name).0;
+            current_node.source_text_string_id =
+                get_line_id_for_node_name(&current_node.name).to_string();
         }
     }
 
@@ -194,8 +200,11 @@ impl<'input> YarnSpinnerParserListener<'input> for CompilerListener<'input> {
         if let Some(track) = track {
             CodeGenerationVisitor::generate_tracking_code(self, track);
         }
-        // We have exited the body; emit a 'stop' opcode here.
-        self.emit(Emit::from_op_code(OpCode::Stop).with_source(Position {
+        // We have exited the body; emit a 'return' opcode here.
+        // ## Implementation note
+        // The original emits a 'stop' opcode, but that would also end any node waiting for a `<<detour>>` to return.
+        // 'return' stops execution just the same when the node wasn't entered through `<<detour>>`.
+        self.emit(Emit::from_op_code(OpCode::Return).with_source(Position {
             line: (ctx.stop().line as usize).saturating_sub(1),
             character: 0,
         }));
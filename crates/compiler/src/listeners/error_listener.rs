@@ -16,6 +16,9 @@ use std::rc::Rc;
 use yarnspinner_core::prelude::*;
 
 mod diagnostic;
+#[cfg(feature = "serde")]
+mod diagnostic_json;
+
 pub(crate) struct LexerErrorListener {
     pub(crate) diagnostics: RefCell<Vec<Diagnostic>>,
     file_name: String,
@@ -52,7 +55,8 @@ impl<'input, T: Recognizer<'input>> ErrorListener<'input, T> for LexerErrorListe
         self.diagnostics.borrow_mut().push(
             Diagnostic::from_message(msg)
                 .with_range(range)
-                .with_file_name(&self.file_name),
+                .with_file_name(&self.file_name)
+                .with_code(DiagnosticCode::SyntaxError),
         );
     }
 }
@@ -95,7 +99,8 @@ impl<'input, T: Recognizer<'input>> ErrorListener<'input, T> for ParserErrorList
         };
         let mut diagnostic = Diagnostic::from_message(msg)
             .with_file_name(&self.file.file_name)
-            .with_range(range);
+            .with_range(range)
+            .with_code(DiagnosticCode::SyntaxError);
         if let Some(offending_symbol) = offending_symbol {
             let mut string = String::new();
 
@@ -111,8 +116,9 @@ impl<'input, T: Recognizer<'input>> ErrorListener<'input, T> for ParserErrorList
             let start = offending_symbol.get_start();
             let stop = offending_symbol.get_stop();
             if start >= 0 && stop >= 0 {
-                // the end point of the error in "line space"
-                let end = (stop - start) + column + 1;
+                // the end point of the error in "line space".
+                // Counted in characters, as the token's start and stop are byte offsets into the file.
+                let end = offending_symbol.get_text().chars().count() as isize + column;
                 for i in 0..end {
                     // move over until we are at the point we need to be
                     if i >= column && i < end {
@@ -125,7 +131,7 @@ impl<'input, T: Recognizer<'input>> ErrorListener<'input, T> for ParserErrorList
 
             let line = offending_symbol.get_line_as_usize().saturating_sub(1);
             let column = offending_symbol.get_column_as_usize();
-            let length = offending_symbol.get_text().len();
+            let length = offending_symbol.get_text().chars().count();
             diagnostic = diagnostic
                 .with_context(string)
                 .with_start_line(line)
@@ -45,6 +45,10 @@ pub struct Diagnostic {
 
     /// The line the context starts on.
     pub start_line: usize,
+
+    /// A stable identifier for the kind of issue, which tools can use to filter or suppress specific diagnostics.
+    /// [`None`] for issues that have not been assigned a code yet.
+    pub code: Option<DiagnosticCode>,
 }
 
 impl Diagnostic {
@@ -56,6 +60,7 @@ impl Diagnostic {
             context: Default::default(),
             severity: Default::default(),
             start_line: Default::default(),
+            code: Default::default(),
         }
     }
 
@@ -102,6 +107,66 @@ impl Diagnostic {
         self.severity = severity;
         self
     }
+
+    pub(crate) fn with_code(mut self, code: DiagnosticCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Renders a plain-text, rustc-style snippet of the diagnostic, showing the offending line of `source`
+    /// with carets under the [`Diagnostic::range`], e.g.
+    ///
+    /// ```text
+    /// warning[YS0003]: Variable $gold is declared but never used
+    ///  --> test.yarn:3:11
+    ///   |
+    /// 3 | <<declare $gold = 0>>
+    ///   |           ^^^^^
+    /// ```
+    ///
+    /// `source` is expected to be the full contents of the file the diagnostic was reported for.
+    /// In contrast to the [`Display`] implementation, this does not rely on [`Diagnostic::context`]
+    /// and does not emit any terminal styling.
+    /// Ranges spanning multiple lines are underlined until the end of their first line.
+    pub fn render(&self, source: &str) -> String {
+        let severity = match self.severity {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+        };
+        let mut rendered = match self.code {
+            Some(code) => format!("{severity}[{code}]: {}\n", self.message),
+            None => format!("{severity}: {}\n", self.message),
+        };
+        let file_name = self.file_name.as_deref().unwrap_or("<unknown file>");
+        let Some(range) = self.range.as_ref() else {
+            rendered.push_str(&format!(" --> {file_name}\n"));
+            return rendered;
+        };
+        let line_number = (range.start.line + 1).to_string();
+        let gutter = " ".repeat(line_number.len());
+        let line = source.lines().nth(range.start.line).unwrap_or_default();
+        let line_length = line.chars().count();
+        let start = range.start.character.min(line_length);
+        let end = if range.end.line > range.start.line {
+            line_length
+        } else {
+            range.end.character.min(line_length)
+        };
+        let underline_length = end.saturating_sub(start).max(1);
+
+        rendered.push_str(&format!(
+            "{gutter}--> {file_name}:{line_number}:{}\n",
+            range.start.character + 1
+        ));
+        rendered.push_str(&format!("{gutter} |\n"));
+        rendered.push_str(&format!("{line_number} | {line}\n"));
+        rendered.push_str(&format!(
+            "{gutter} | {}{}\n",
+            " ".repeat(start),
+            "^".repeat(underline_length)
+        ));
+        rendered
+    }
 }
 
 impl Display for Diagnostic {
@@ -114,7 +179,7 @@ impl Display for Diagnostic {
         let snippet = Snippet {
             title: Some(Annotation {
                 label: Some(label),
-                id: None,
+                id: self.code.map(|code| code.as_str()),
                 annotation_type,
             }),
             footer: vec![],
@@ -171,11 +236,25 @@ fn convert_absolute_range_to_relative(diagnostic: &Diagnostic) -> (usize, usize)
 pub trait DiagnosticVec {
     /// Returns `true` if any of the [`Diagnostic`]s in the vector are of [`DiagnosticSeverity::Error`].
     fn has_errors(&self) -> bool;
+    /// Returns the [`Diagnostic`]s in the vector that are of [`DiagnosticSeverity::Error`].
+    fn errors(&self) -> impl Iterator<Item = &Diagnostic>;
+    /// Returns the [`Diagnostic`]s in the vector that are of [`DiagnosticSeverity::Warning`].
+    fn warnings(&self) -> impl Iterator<Item = &Diagnostic>;
 }
 
 impl DiagnosticVec for Vec<Diagnostic> {
     fn has_errors(&self) -> bool {
-        self.iter().any(|d| d.severity == DiagnosticSeverity::Error)
+        self.errors().next().is_some()
+    }
+
+    fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+    }
+
+    fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Warning)
     }
 }
 
@@ -206,3 +285,95 @@ pub enum DiagnosticSeverity {
     /// but do not cause the compilation process to fail.
     Warning,
 }
+
+/// A stable, machine-readable identifier for a kind of [`Diagnostic`], e.g. `YS0002` for mixed indentation.
+///
+/// The codes never change meaning once assigned, so they can be used to filter or suppress diagnostics.
+/// The variant names are not part of that guarantee, use [`DiagnosticCode::as_str`] to persist a code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum DiagnosticCode {
+    /// `YS0001`: The source code does not follow the Yarn syntax.
+    SyntaxError,
+    /// `YS0002`: A line is indented with both tabs and spaces.
+    MixedIndentation,
+    /// `YS0003`: A variable is declared but never used.
+    UnusedVariable,
+    /// `YS0004`: A node cannot be reached from any entry point.
+    UnreachableNode,
+    /// `YS0005`: Nodes jump to each other without ever showing anything.
+    JumpCycle,
+    /// `YS0006`: More than one node has the same title.
+    DuplicateNodeName,
+    /// `YS0007`: The markup of a line has a tag that is never closed, or closes a tag that is not open.
+    UnbalancedMarkup,
+    /// `YS0008`: The markup of a line uses an attribute that neither the game nor the runtime handles.
+    UnknownMarkupAttribute,
+}
+
+impl DiagnosticCode {
+    /// Returns the code as it is shown to users, e.g. `YS0001`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SyntaxError => "YS0001",
+            Self::MixedIndentation => "YS0002",
+            Self::UnusedVariable => "YS0003",
+            Self::UnreachableNode => "YS0004",
+            Self::JumpCycle => "YS0005",
+            Self::DuplicateNodeName => "YS0006",
+            Self::UnbalancedMarkup => "YS0007",
+            Self::UnknownMarkupAttribute => "YS0008",
+        }
+    }
+}
+
+impl Display for DiagnosticCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_aligns_caret_under_range() {
+        let source = "title: test\n---\n<<declare $gold = 0>>\n===\n";
+        let diagnostic = Diagnostic::from_message("Variable $gold is declared but never used")
+            .with_file_name("test.yarn")
+            .with_range(
+                Position {
+                    line: 2,
+                    character: 10,
+                }..Position {
+                    line: 2,
+                    character: 15,
+                },
+            )
+            .with_severity(DiagnosticSeverity::Warning)
+            .with_code(DiagnosticCode::UnusedVariable);
+
+        let rendered = diagnostic.render(source);
+
+        assert_eq!(
+            rendered,
+            "warning[YS0003]: Variable $gold is declared but never used
+ --> test.yarn:3:11
+  |
+3 | <<declare $gold = 0>>
+  |           ^^^^^
+"
+        );
+        let lines: Vec<_> = rendered.lines().collect();
+        let code_column = lines[3].find("$gold").unwrap();
+        let caret_column = lines[4].find('^').unwrap();
+        assert_eq!(code_column, caret_column);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::prelude::*;
+use std::ops::Range;
+use yarnspinner_core::prelude::*;
+
+/// The JSON shape of a [`Diagnostic`]. Kept separate from the [`Diagnostic`] struct
+/// so that the output stays stable for editors even if the struct changes.
+#[derive(Serialize)]
+struct DiagnosticJson<'a> {
+    file: Option<&'a str>,
+    range: Option<&'a Range<Position>>,
+    severity: &'static str,
+    code: Option<&'static str>,
+    message: &'a str,
+}
+
+impl<'a> From<&'a Diagnostic> for DiagnosticJson<'a> {
+    fn from(diagnostic: &'a Diagnostic) -> Self {
+        Self {
+            file: diagnostic.file_name.as_deref(),
+            range: diagnostic.range.as_ref(),
+            severity: match diagnostic.severity {
+                DiagnosticSeverity::Error => "error",
+                DiagnosticSeverity::Warning => "warning",
+            },
+            code: diagnostic.code.map(|code| code.as_str()),
+            message: &diagnostic.message,
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Serializes this diagnostic as a JSON object for editor integrations, e.g.
+    ///
+    /// ```json
+    /// {"file":"a.yarn","range":{"start":{"line":2,"character":0},"end":{"line":2,"character":5}},"severity":"warning","code":"YS0003","message":"..."}
+    /// ```
+    ///
+    /// `file`, `range` and `code` are `null` when unknown. Lines and characters are zero-indexed.
+    ///
+    /// Only available with the `serde` feature.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&DiagnosticJson::from(self))
+            .expect("Failed to serialize diagnostic to JSON. This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new")
+    }
+
+    /// Serializes multiple diagnostics as a JSON array of the objects described in [`Diagnostic::to_json`],
+    /// e.g. the [`Compilation::warnings`] or the diagnostics of a [`CompilerError`].
+    ///
+    /// Only available with the `serde` feature.
+    pub fn to_json_array<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> String {
+        let diagnostics: Vec<_> = diagnostics.into_iter().map(DiagnosticJson::from).collect();
+        serde_json::to_string(&diagnostics)
+            .expect("Failed to serialize diagnostics to JSON. This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_warning_with_range() {
+        let diagnostic = Diagnostic::from_message("Variable $gold is declared but never used")
+            .with_file_name("test.yarn")
+            .with_range(
+                Position {
+                    line: 2,
+                    character: 10,
+                }..Position {
+                    line: 2,
+                    character: 15,
+                },
+            )
+            .with_severity(DiagnosticSeverity::Warning)
+            .with_code(DiagnosticCode::UnusedVariable);
+
+        assert_eq!(
+            diagnostic.to_json(),
+            r#"{"file":"test.yarn","range":{"start":{"line":2,"character":10},"end":{"line":2,"character":15}},"severity":"warning","code":"YS0003","message":"Variable $gold is declared but never used"}"#
+        );
+        assert_eq!(
+            Diagnostic::to_json_array([&diagnostic, &diagnostic]),
+            format!("[{0},{0}]", diagnostic.to_json())
+        );
+    }
+}
@@ -1,8 +1,9 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/Utils.cs>
 
-use crate::parser::generated::yarnspinnerparser::Line_statementContext;
+use crate::parser::generated::yarnspinnerparser::{Line_statementContext, NodeContext};
+use crate::prelude::generated::yarnspinnerlexer;
 use crate::prelude::generated::yarnspinnerparser::{
-    Line_statementContextAttrs, YarnSpinnerParserContextType,
+    Line_statementContextAttrs, NodeContextAttrs, YarnSpinnerParserContextType,
 };
 use crate::prelude::generated::yarnspinnerparserlistener::YarnSpinnerParserListener;
 use crate::prelude::*;
@@ -11,21 +12,34 @@ use antlr_rust::int_stream::IntStream;
 use antlr_rust::parser_rule_context::ParserRuleContext;
 use antlr_rust::token::Token;
 use antlr_rust::token_stream::TokenStream;
-use antlr_rust::tree::ParseTreeListener;
-use rand::{rngs::SmallRng, Rng, SeedableRng};
+use antlr_rust::tree::{ParseTree, ParseTreeListener};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 
 pub(crate) struct UntaggedLineListener<'input> {
     existing_line_tags: Vec<LineId>,
     file: FileParseResult<'input>,
+    current_node_name: String,
+    /// How often each text has been tagged so far in each node, keyed by node name and text
+    occurrences: HashMap<(String, String), usize>,
     pub(crate) rewritten_lines: Rc<RefCell<Vec<String>>>,
     pub(crate) rewrote_anything: Rc<AtomicBool>,
 }
 
 impl<'input> UntaggedLineListener<'input> {
-    pub fn new(existing_line_tags: Vec<LineId>, file: FileParseResult<'input>) -> Self {
+    pub fn new(mut existing_line_tags: Vec<LineId>, file: FileParseResult<'input>) -> Self {
+        // Tags further down in the file must not be generated for the lines before them either
+        let tokens = file.tokens();
+        existing_line_tags.extend(
+            (0..tokens.size())
+                .map(|index| tokens.get(index))
+                .filter(|token| token.get_token_type() == yarnspinnerlexer::HASHTAG_TEXT)
+                .map(|token| token.get_text().to_owned())
+                .filter(|text| text.starts_with("line:"))
+                .map(LineId::from),
+        );
         let original_source = file
             .tokens()
             .get_all_text()
@@ -35,20 +49,43 @@ impl<'input> UntaggedLineListener<'input> {
         Self {
             existing_line_tags,
             file,
+            current_node_name: Default::default(),
+            occurrences: Default::default(),
             rewritten_lines: Rc::new(RefCell::new(original_source)),
             rewrote_anything: Default::default(),
         }
     }
 
     /// Generates a new unique line tag that is not present in `existing_line_tags`.
-    fn generate_string(&self) -> LineId {
-        let mut rng = SmallRng::from_entropy();
+    ///
+    /// ## Implementation notes
+    ///
+    /// The original implementation picks a random number. We instead derive it from a hash of the node name,
+    /// the text of the line and how many identical lines came before it in the node, so that tagging the same file
+    /// always produces the same tags. The tags have the same shape as the original ones, i.e. `line:` followed by 7 hex digits.
+    fn generate_string(&mut self, text: &str) -> LineId {
+        let occurrence = self
+            .occurrences
+            .entry((self.current_node_name.clone(), text.to_owned()))
+            .or_default();
+        let hash = fnv1a_hash(
+            [
+                self.current_node_name.as_bytes(),
+                text.as_bytes(),
+                occurrence.to_string().as_bytes(),
+            ]
+            .join(&0),
+        );
+        *occurrence += 1;
+
+        // Fold the hash into 24 bits and probe linearly in the unlikely case of a collision
+        let mut line = (hash >> 24) ^ (hash & 0xFFFFFF);
         loop {
-            let line: usize = rng.gen_range(0..0x1000000);
-            let tag = LineId(format!("line:{}", line));
+            let tag = LineId::from(format!("line:{line:07x}"));
             if !self.existing_line_tags.contains(&tag) {
                 return tag;
             }
+            line = (line + 1) & 0xFFFFFF;
         }
     }
 }
@@ -59,6 +96,16 @@ impl<'input> ParseTreeListener<'input, YarnSpinnerParserContextType>
 }
 
 impl<'input> YarnSpinnerParserListener<'input> for UntaggedLineListener<'input> {
+    fn enter_node(&mut self, ctx: &NodeContext<'input>) {
+        self.current_node_name = ctx
+            .header_all()
+            .iter()
+            .find(|header| header.header_key.as_ref().unwrap().get_text() == "title")
+            .and_then(|header| header.header_value.as_ref().map(|value| value.get_text()))
+            .unwrap_or_default()
+            .to_owned();
+    }
+
     fn exit_line_statement(&mut self, ctx: &Line_statementContext<'input>) {
         // We're looking at a complete line statement.
 
@@ -94,7 +141,8 @@ impl<'input> YarnSpinnerParserListener<'input> for UntaggedLineListener<'input>
         let previous_token = tokens.get(previous_token_index);
 
         // Generate a new, unique line ID.
-        let new_line_id = self.generate_string();
+        let text = ctx.line_formatted_text().unwrap().get_text();
+        let new_line_id = self.generate_string(text.trim());
         // Record that we've used this new line ID, so that we don't
         // accidentally use it twice.
         self.existing_line_tags.push(new_line_id.clone());
@@ -140,3 +188,10 @@ fn index_of_previous_token_on_channel(
         .rev()
         .find(|&i| token_stream.get(i).get_channel() == default_token_channel)
 }
+
+/// The 32-bit FNV-1a hash, which unlike the hashers of the standard library is guaranteed to stay the same across Rust versions.
+fn fnv1a_hash(bytes: impl AsRef<[u8]>) -> u32 {
+    bytes.as_ref().iter().fold(0x811C9DC5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    })
+}
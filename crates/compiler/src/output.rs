@@ -1,16 +1,25 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/CompilationResult.cs>
 
 use crate::listeners::*;
-pub use crate::output::{debug_info::*, declaration::*, string_info::*};
+pub use crate::output::{
+    command_info::*, debug_info::*, declaration::*, node_info::*, string_info::*,
+    string_table_csv::*, syntax_tree::*, voice_over_budget::*,
+};
 use crate::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display};
 use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
+mod command_info;
 mod debug_info;
 mod declaration;
+mod node_info;
+mod plain_text;
 mod string_info;
+mod string_table_csv;
+mod syntax_tree;
+mod voice_over_budget;
 
 /// The result of a compilation.
 ///
@@ -86,6 +95,14 @@ pub struct Compilation {
 
     /// The collection of [`DebugInfo`] objects for each node in [`Program`].
     pub debug_info: HashMap<String, DebugInfo>,
+
+    /// Information about every node found in the source code, such as its title and headers,
+    /// in the order the nodes appear in the files.
+    pub nodes: Vec<NodeInfo>,
+
+    /// Every command run by the source code, in the order they appear in the files.
+    /// See [`Compilation::command_names`] for the distinct names of these commands.
+    pub commands: Vec<CommandInfo>,
 }
 
 impl Compilation {
@@ -99,6 +116,8 @@ impl Compilation {
         let mut tags = HashMap::new();
         let mut diagnostics = Vec::new();
         let mut node_debug_infos = HashMap::new();
+        let mut nodes = Vec::new();
+        let mut commands = Vec::new();
 
         for compilation in compilations {
             programs.push(compilation.program.unwrap());
@@ -106,8 +125,11 @@ impl Compilation {
             tags.extend(compilation.file_tags);
             diagnostics.extend(compilation.warnings);
             node_debug_infos.extend(compilation.debug_info);
+            nodes.extend(compilation.nodes);
+            commands.extend(compilation.commands);
         }
-        let combined_program = Program::combine(programs);
+        // Node names were already checked by `validate_unique_node_names`
+        let combined_program = Program::combine(programs).unwrap_or_else(|error| panic!("{error}"));
         let contains_implicit_string_tags = string_table_manager.contains_implicit_string_tags();
         Compilation {
             program: combined_program,
@@ -117,8 +139,65 @@ impl Compilation {
             contains_implicit_string_tags,
             file_tags: tags,
             warnings: diagnostics,
+            nodes,
+            commands,
         }
     }
+
+    /// Returns the distinct names of all commands run by the source code, in no particular order.
+    pub fn command_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.commands
+            .iter()
+            .map(|command| command.name.as_str())
+            .collect::<HashSet<_>>()
+            .into_iter()
+    }
+
+    /// Returns the file-level hashtags of the given file, e.g. `file_header` for a file starting with `#file_header`,
+    /// in the order they were written. Returns an empty slice for files without hashtags or unknown files.
+    pub fn file_tags_for(&self, file_name: &str) -> &[String] {
+        self.file_tags
+            .get(file_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns all file-level hashtags as pairs of file name and tag, ordered by file name.
+    /// See [`Compilation::file_tags_for`] for the tags of a single file.
+    pub fn all_file_tags(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let mut file_names: Vec<_> = self.file_tags.keys().collect();
+        file_names.sort();
+        file_names.into_iter().flat_map(|file_name| {
+            self.file_tags[file_name]
+                .iter()
+                .map(move |tag| (file_name.as_str(), tag.as_str()))
+        })
+    }
+
+    /// Returns every entry of the [`Compilation::string_table`] together with its line ID, e.g. for building a line browser.
+    /// The [`StringInfo`] of each line carries its text, node name and hashtags.
+    ///
+    /// Lines are ordered like the nodes in [`Compilation::nodes`], and by their position in the source within each node.
+    pub fn lines(&self) -> impl Iterator<Item = (&LineId, &StringInfo)> + '_ {
+        let node_order: HashMap<_, _> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.title.as_str(), index))
+            .collect();
+        let mut lines: Vec<_> = self.string_table.iter().collect();
+        lines.sort_by_key(|(line_id, string_info)| {
+            (
+                node_order
+                    .get(string_info.node_name.as_str())
+                    .copied()
+                    .unwrap_or(usize::MAX),
+                string_info.line_number,
+                line_id.as_str(),
+            )
+        });
+        lines.into_iter()
+    }
 }
 
 /// A collection of [`Diagnostic`] objects that describe problems that occurred during compilation.
@@ -157,3 +236,95 @@ impl Display for CompilerError {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_file_tags() {
+        let file = |file_name: &str, source: &str| File {
+            file_name: file_name.to_string(),
+            source: source.to_string(),
+        };
+        let compilation = Compiler::new()
+            .add_file(file(
+                "tagged.yarn",
+                "#chapter_one
+#needs_review
+title: Start
+---
+Hello
+===
+",
+            ))
+            .add_file(file(
+                "untagged.yarn",
+                "title: Other
+---
+Bye
+===
+",
+            ))
+            .compile()
+            .unwrap();
+
+        assert_eq!(
+            compilation.file_tags_for("tagged.yarn"),
+            ["chapter_one", "needs_review"]
+        );
+        assert!(compilation.file_tags_for("untagged.yarn").is_empty());
+        assert!(compilation.file_tags_for("missing.yarn").is_empty());
+        let all_tags: Vec<_> = compilation.all_file_tags().collect();
+        assert_eq!(
+            all_tags,
+            [
+                ("tagged.yarn", "chapter_one"),
+                ("tagged.yarn", "needs_review")
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_lines_in_source_order() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+Hello #line:b #greeting
+-> Hi back #line:a
+<<jump Next>>
+===
+title: Next
+---
+Bye #line:c
+Really bye #line:0
+===
+"
+            .to_string(),
+        };
+        let compilation = Compiler::new().add_file(file).compile().unwrap();
+
+        let lines: Vec<_> = compilation
+            .lines()
+            .map(|(line_id, string_info)| {
+                (
+                    line_id.as_str(),
+                    string_info.node_name.as_str(),
+                    string_info.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("line:b", "Start", "Hello"),
+                ("line:a", "Start", "Hi back"),
+                ("line:c", "Next", "Bye"),
+                ("line:0", "Next", "Really bye"),
+            ]
+        );
+        let (_, first) = compilation.lines().next().unwrap();
+        assert!(first.metadata.contains(&"greeting".to_owned()));
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+
+/// Information about a single use of a command, i.e. a `<<command>>` statement, in the source code.
+/// Useful for checking that a game implements every command that the dialogue may run.
+///
+/// The built-in `stop` command is not included, and neither are `<<jump>>`, `<<set>>` or `<<declare>>`,
+/// which are statements rather than commands.
+///
+/// You do not create instances of this struct yourself. They are
+/// generated by the [`Compiler`] and stored in [`Compilation::commands`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct CommandInfo {
+    /// The name of the command, i.e. the first word of the command text.
+    pub name: String,
+
+    /// The number of arguments the command was called with at this location.
+    /// Text in quotes and inline expressions like `{$gold}` count as a single argument.
+    pub argument_count: usize,
+
+    /// The name of the file this command was found in.
+    pub file_name: String,
+
+    /// The title of the node this command was found in.
+    pub node_name: String,
+
+    /// The 1-indexed line number at which this command was found in the file.
+    pub line_number: usize,
+}
@@ -56,6 +56,19 @@ pub struct Declaration {
     /// If `false`, this declaration appears in the source code.
     pub is_implicit: bool,
 
+    /// A value indicating whether this declaration is a compile-time constant,
+    /// declared via `<<declare $name = value as constant>>`.
+    ///
+    /// References to a constant are replaced by its value during compilation,
+    /// and assigning to a constant is an error.
+    pub is_constant: bool,
+
+    /// The source of the expression computing the value of this declaration if it is a smart variable,
+    /// declared via `<<declare $name = expression>>` or [`Declaration::with_smart_expression`].
+    ///
+    /// Smart variables are read-only and are evaluated anew every time they are read.
+    pub smart_expression: Option<String>,
+
     /// The type of the variable, as represented by an object found
     /// in a variant of [`Type`].
     pub r#type: Type,
@@ -89,6 +102,8 @@ impl Declaration {
             source_file_name: Default::default(),
             source_node_name: Default::default(),
             is_implicit: Default::default(),
+            is_constant: Default::default(),
+            smart_expression: Default::default(),
             range: Default::default(),
         }
     }
@@ -150,6 +165,23 @@ impl Declaration {
         self
     }
 
+    #[doc(hidden)]
+    pub fn with_constant(mut self) -> Self {
+        self.is_constant = true;
+        self
+    }
+
+    /// Turns this declaration into a smart variable whose value is computed from `smart_expression` every time it is read,
+    /// e.g. `Declaration::new("$is_rich", Type::Any).with_smart_expression("$gold > 100")`.
+    /// Pass it to [`Compiler::declare_variable`]. The type of the variable is inferred from the expression
+    /// unless it is given explicitly, in which case the expression is checked against it.
+    ///
+    /// This is the API counterpart of declaring a smart variable in Yarn via `<<declare $is_rich = $gold > 100>>`.
+    pub fn with_smart_expression(mut self, smart_expression: impl Into<String>) -> Self {
+        self.smart_expression = Some(smart_expression.into());
+        self
+    }
+
     #[doc(hidden)]
     pub fn with_range(mut self, range: impl Into<Range<Position>>) -> Self {
         self.range = Some(range.into());
@@ -163,6 +195,8 @@ impl Declaration {
             && self.source_file_name == other.source_file_name
             && self.source_node_name == other.source_node_name
             && self.is_implicit == other.is_implicit
+            && self.is_constant == other.is_constant
+            && self.smart_expression == other.smart_expression
             && self.r#type == other.r#type
             && self.range == other.range
             && match (&self.default_value, &other.default_value) {
@@ -217,7 +251,7 @@ where
         let text: String = self.stop().get_text().to_owned().into();
         let stop = Position {
             line: self.stop().get_line_as_usize().saturating_sub(1),
-            character: self.stop().get_column_as_usize() + text.len(),
+            character: self.stop().get_column_as_usize() + text.chars().count(),
         };
         start..stop
     }
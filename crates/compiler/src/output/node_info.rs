@@ -0,0 +1,40 @@
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+use yarnspinner_core::prelude::*;
+
+/// Information about a node found in the source code, such as its title and headers.
+/// Useful for tools that want to inspect the structure of a Yarn project, e.g. to visualize it as a graph.
+///
+/// You do not create instances of this struct yourself. They are
+/// generated by the [`Compiler`] and stored in [`Compilation::nodes`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct NodeInfo {
+    /// The title of the node.
+    pub title: String,
+
+    /// All headers of the node in the order they were written, including the `title` header.
+    pub headers: Vec<Header>,
+
+    /// The name of the file this node was found in.
+    pub file_name: String,
+
+    /// The 1-indexed line number at which this node starts in the file.
+    pub line_number: usize,
+}
+
+impl NodeInfo {
+    /// Returns the value of the first header with the given key, if any.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.key == key)
+            .map(|header| header.value.as_str())
+    }
+}
@@ -0,0 +1,373 @@
+//! ## Implementation notes
+//!
+//! The compiler does not depend on the runtime, so this mirrors the parts of its markup parser that decide which text of a line is shown:
+//! escape sequences, `[nomarkup]`, the whitespace removed after self-closing tags, and the `character` attribute.
+//! The texts of `select`, `plural` and `ordinal` depend on values that are only known at runtime, so their `other` case stands in for them.
+
+use std::ops::Range;
+use yarnspinner_core::prelude::*;
+
+/// The text of a line as the runtime shows it, created by [`PlainText::parse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PlainText {
+    /// The text without markup.
+    pub(crate) text: String,
+    /// The name of the speaking character, from a `[character name=...]` attribute or a leading `Name:`.
+    pub(crate) character_name: Option<String>,
+    /// The bytes of [`PlainText::text`] spanned by the `character` attribute, e.g. `Alice: ` for `Alice: Hello!`.
+    character_range: Range<usize>,
+}
+
+impl PlainText {
+    /// Removes the markup from the text of a line the way the runtime's markup parser does.
+    /// Tags that the runtime fails to parse, such as a `[` without a matching `]`, are kept as text.
+    pub(crate) fn parse(markup: &str) -> Self {
+        let chars: Vec<char> = markup.chars().collect();
+        let mut text = String::with_capacity(markup.len());
+        let mut open_character: Option<(Option<String>, usize)> = None;
+        let mut character: Option<(Option<String>, Range<usize>)> = None;
+        // Like in the runtime, escaped characters do not count as the character before a tag,
+        // and neither does the start of the line, so a tag at the very start never trims whitespace.
+        let mut last_character = None;
+        let mut index = 0;
+        while let Some(&next) = chars.get(index) {
+            index += 1;
+            if next == '\\' {
+                if let Some(escaped) = chars
+                    .get(index)
+                    .filter(|escaped| MARKUP_ESCAPABLE_CHARACTERS.contains(escaped))
+                {
+                    text.push(*escaped);
+                    index += 1;
+                    continue;
+                }
+            }
+            let tag = (next == '[').then(|| Tag::parse(&chars, index)).flatten();
+            let Some((tag, end)) = tag else {
+                text.push(next);
+                last_character = Some(next);
+                continue;
+            };
+            index = end;
+            let is_replacement = tag.name.as_deref().is_some_and(is_replacement_attribute);
+            if is_replacement {
+                match tag.kind {
+                    TagKind::Open => {
+                        let (contents, close) =
+                            raw_text_up_to_close(&chars, index, tag.name.as_deref());
+                        index = close;
+                        text.push_str(&tag.replacement_text(&contents));
+                    }
+                    TagKind::SelfClosing => text.push_str(&tag.replacement_text("")),
+                    TagKind::Close | TagKind::CloseAll => {}
+                }
+            }
+
+            if last_character.is_some_and(char::is_whitespace) {
+                let trim_whitespace = match tag.property(TRIM_WHITESPACE_PROPERTY) {
+                    Some(value) => value == "true",
+                    None => tag.kind == TagKind::SelfClosing && !is_replacement,
+                };
+                if trim_whitespace && chars.get(index).is_some_and(|c| c.is_whitespace()) {
+                    index += 1;
+                }
+            }
+
+            let is_character = tag.name.as_deref() == Some(CHARACTER_ATTRIBUTE);
+            match tag.kind {
+                TagKind::Open if is_character => {
+                    open_character =
+                        Some((tag.property("name").map(ToOwned::to_owned), text.len()));
+                }
+                TagKind::SelfClosing if is_character => {
+                    let name = tag.property("name").map(ToOwned::to_owned);
+                    character.get_or_insert((name, text.len()..text.len()));
+                }
+                TagKind::Close if is_character => {
+                    if let Some((name, start)) = open_character.take() {
+                        character.get_or_insert((name, start..text.len()));
+                    }
+                }
+                TagKind::CloseAll => {
+                    if let Some((name, start)) = open_character.take() {
+                        character.get_or_insert((name, start..text.len()));
+                    }
+                }
+                _ => {}
+            }
+            last_character = Some('[');
+        }
+
+        let (character_name, character_range) = match character {
+            Some(character) => character,
+            None => match split_character_name(&text) {
+                Some((character_name, spoken_text)) => (
+                    Some(character_name.to_owned()),
+                    0..text.len() - spoken_text.len(),
+                ),
+                None => (None, 0..0),
+            },
+        };
+        Self {
+            text,
+            character_name,
+            character_range,
+        }
+    }
+
+    /// Returns the text without the part spanned by the `character` attribute, i.e. the text that is spoken.
+    pub(crate) fn spoken_text(&self) -> String {
+        let mut spoken_text = self.text.clone();
+        spoken_text.replace_range(self.character_range.clone(), "");
+        spoken_text
+    }
+}
+
+/// The name of the `character` attribute, which the runtime also adds for lines starting with `Name:`.
+const CHARACTER_ATTRIBUTE: &str = "character";
+
+/// The name of the property that overrides whether a tag removes the whitespace after it.
+const TRIM_WHITESPACE_PROPERTY: &str = "trimwhitespace";
+
+/// Whether the runtime replaces the attribute with text on its own instead of keeping the text between its tags.
+fn is_replacement_attribute(name: &str) -> bool {
+    matches!(name, "nomarkup" | "select" | "plural" | "ordinal")
+}
+
+/// Returns the text starting at `start` up to the next `[/name]` or `[/]`, and the index at which that tag starts.
+fn raw_text_up_to_close(chars: &[char], start: usize, name: Option<&str>) -> (String, usize) {
+    let closes = |index: &usize| {
+        chars[*index] == '['
+            && Tag::parse(chars, index + 1).is_some_and(|(tag, _)| {
+                tag.kind == TagKind::CloseAll
+                    || tag.kind == TagKind::Close && tag.name.as_deref() == name
+            })
+    };
+    let close = (start..chars.len()).find(closes).unwrap_or(chars.len());
+    (chars[start..close].iter().collect(), close)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Open,
+    Close,
+    CloseAll,
+    SelfClosing,
+}
+
+/// A markup tag such as `[wave speed=2]`, `[/wave]`, `[/]` or `[pause/]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tag {
+    name: Option<String>,
+    kind: TagKind,
+    properties: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Parses the tag whose content starts at `start`, i.e. after its `[`, and returns it with the index after its `]`.
+    /// Returns [`None`] if the runtime would fail to parse it.
+    fn parse(chars: &[char], start: usize) -> Option<(Self, usize)> {
+        let mut index = start;
+        skip_whitespace(chars, &mut index);
+        if chars.get(index) == Some(&'/') {
+            index += 1;
+            skip_whitespace(chars, &mut index);
+            let (name, kind) = if chars.get(index) == Some(&']') {
+                (None, TagKind::CloseAll)
+            } else {
+                let name = parse_id(chars, &mut index)?;
+                skip_whitespace(chars, &mut index);
+                (Some(name), TagKind::Close)
+            };
+            let tag = Self {
+                name,
+                kind,
+                properties: Vec::new(),
+            };
+            return (chars.get(index) == Some(&']')).then_some((tag, index + 1));
+        }
+
+        let name = parse_id(chars, &mut index)?;
+        let mut properties = Vec::new();
+        skip_whitespace(chars, &mut index);
+        if chars.get(index) == Some(&'=') {
+            index += 1;
+            properties.push((name.clone(), parse_value(chars, &mut index)?));
+        }
+        loop {
+            skip_whitespace(chars, &mut index);
+            let kind = match chars.get(index)? {
+                ']' => TagKind::Open,
+                '/' => {
+                    index += 1;
+                    skip_whitespace(chars, &mut index);
+                    if chars.get(index) != Some(&']') {
+                        return None;
+                    }
+                    TagKind::SelfClosing
+                }
+                _ => {
+                    let property = parse_id(chars, &mut index)?;
+                    skip_whitespace(chars, &mut index);
+                    if chars.get(index) != Some(&'=') {
+                        return None;
+                    }
+                    index += 1;
+                    properties.push((property, parse_value(chars, &mut index)?));
+                    continue;
+                }
+            };
+            let tag = Self {
+                name: Some(name),
+                kind,
+                properties,
+            };
+            return Some((tag, index + 1));
+        }
+    }
+
+    fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The text the runtime would show instead of this replacement attribute, as far as it is known at compile time.
+    fn replacement_text(&self, contents: &str) -> String {
+        match self.name.as_deref() {
+            Some("nomarkup") => contents.to_owned(),
+            Some("plural" | "ordinal") => {
+                let value = self.property("value").unwrap_or_default();
+                self.property("other")
+                    .unwrap_or_default()
+                    .replace('%', value)
+            }
+            _ => self.property("other").unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &[char], index: &mut usize) {
+    while chars.get(*index).is_some_and(|c| c.is_whitespace()) {
+        *index += 1;
+    }
+}
+
+/// Parses a name made of letters, numbers and underscores.
+fn parse_id(chars: &[char], index: &mut usize) -> Option<String> {
+    skip_whitespace(chars, index);
+    let start = *index;
+    while chars
+        .get(*index)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+    {
+        *index += 1;
+    }
+    (*index > start).then(|| chars[start..*index].iter().collect())
+}
+
+/// Parses a property value, either in double quotes with `\` escaping the next character, or up to the next whitespace, `/` or `]`.
+/// Values are kept as text, e.g. `{0}` for a substitution.
+fn parse_value(chars: &[char], index: &mut usize) -> Option<String> {
+    skip_whitespace(chars, index);
+    let mut value = String::new();
+    if chars.get(*index) == Some(&'"') {
+        *index += 1;
+        loop {
+            let next = *chars.get(*index)?;
+            *index += 1;
+            match next {
+                '"' => return Some(value),
+                '\\' => {
+                    value.push(*chars.get(*index)?);
+                    *index += 1;
+                }
+                _ => value.push(next),
+            }
+        }
+    }
+    while let Some(&next) = chars
+        .get(*index)
+        .filter(|c| !c.is_whitespace() && !matches!(c, '/' | ']'))
+    {
+        value.push(next);
+        *index += 1;
+    }
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(markup: &str) -> (String, Option<String>, String) {
+        let plain_text = PlainText::parse(markup);
+        let spoken_text = plain_text.spoken_text();
+        (plain_text.text, plain_text.character_name, spoken_text)
+    }
+
+    fn expected(
+        text: &str,
+        character_name: Option<&str>,
+        spoken_text: &str,
+    ) -> (String, Option<String>, String) {
+        (
+            text.to_owned(),
+            character_name.map(ToOwned::to_owned),
+            spoken_text.to_owned(),
+        )
+    }
+
+    #[test]
+    fn finds_character_names_like_the_runtime() {
+        assert_eq!(
+            parse("[b]Sally[/b]: Hello"),
+            expected("Sally: Hello", Some("Sally"), "Hello")
+        );
+        assert_eq!(parse("Alice:Hi"), expected("Alice:Hi", Some("Alice"), "Hi"));
+        assert_eq!(
+            parse("It is 10:30 now."),
+            expected("It is 10:30 now.", Some("It is 10"), "30 now.")
+        );
+        assert_eq!(parse("\\[x\\]: y"), expected("[x]: y", Some("[x]"), "y"));
+        assert_eq!(
+            parse("[character name=\"Bob\"]Bob: [/character]Hi"),
+            expected("Bob: Hi", Some("Bob"), "Hi")
+        );
+        assert_eq!(
+            parse("[character name=Bob/]Hi: there"),
+            expected("Hi: there", Some("Bob"), "Hi: there")
+        );
+        assert_eq!(parse("Hello"), expected("Hello", None, "Hello"));
+    }
+
+    #[test]
+    fn removes_markup_like_the_runtime() {
+        let text = |markup| PlainText::parse(markup).text;
+
+        assert_eq!(text("[wave=2]hi[/wave] [ b ]t[ / b ]"), "hi t");
+        assert_eq!(text("[a x=\"q]\"]t[/a]"), "t");
+        assert_eq!(text("a\\qb\\[c\\"), "a\\qb[c\\");
+        assert_eq!(text("x [nomarkup][b]\\[ a[/nomarkup] y"), "x [b]\\[ a y");
+        assert_eq!(text("x [nomarkup]y[/] z"), "x y z");
+        assert_eq!(
+            text("A [plural value={0} one=\"% coin\" other=\"% coins\"/] B"),
+            "A {0} coins B"
+        );
+    }
+
+    #[test]
+    fn trims_whitespace_after_self_closing_tags_like_the_runtime() {
+        let text = |markup| PlainText::parse(markup).text;
+
+        assert_eq!(text("[pause/] Hi"), " Hi");
+        assert_eq!(text("A [pause/] B"), "A B");
+        assert_eq!(text("A[pause/] B"), "A B");
+        assert_eq!(text("A [b/] [c/] B"), "A  B");
+        assert_eq!(text("A [b] B[/b]"), "A  B");
+        assert_eq!(text("A [nomarkup/] B"), "A  B");
+        assert_eq!(text("A [pause trimwhitespace=false/] B"), "A  B");
+        assert_eq!(text("A[pause trimwhitespace=true/] B"), "A B");
+    }
+}
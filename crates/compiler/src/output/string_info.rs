@@ -1,5 +1,6 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/StringInfo.cs>
 
+use crate::output::plain_text::PlainText;
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 
@@ -44,3 +45,22 @@ pub struct StringInfo {
     /// string besides the `#line:` hashtag.
     pub metadata: Vec<String>,
 }
+
+impl StringInfo {
+    /// Returns the name of the speaking character, e.g. `Alice` for `Alice: Hello!`, the way the runtime finds it:
+    /// from a `[character name="Alice"]` attribute, or else everything before the first `:` of the text without markup.
+    /// Like in the runtime, `Alice:Hello!` is spoken by `Alice`, but `The time is 10:30` is also spoken by `The time is 10`.
+    ///
+    /// ## Implementation notes
+    ///
+    /// Not part of the original implementation. Mirrors [`Line::character_name`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/struct.Line.html#method.character_name),
+    /// see [`split_character_name`](yarnspinner_core::prelude::split_character_name).
+    pub fn character_name(&self) -> Option<String> {
+        self.plain_text().character_name
+    }
+
+    /// Returns the text the way the runtime shows it, without markup.
+    pub(crate) fn plain_text(&self) -> PlainText {
+        PlainText::parse(&self.text)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::prelude::*;
+use sha2::{Digest, Sha256};
+
+impl Compilation {
+    /// Exports the [`Compilation::string_table`] as CSV, e.g. to hand it to translators.
+    ///
+    /// The CSV has the columns `language`, `id`, `text`, `character`, `file`, `node`, `lineNumber`, `lock` and `comment`.
+    /// Apart from `character`, these are the columns of the strings files of the upstream tools and the Bevy plugin.
+    /// - `language` is the passed `language` for every row, i.e. the language the Yarn files were written in.
+    /// - `character` contains the name of the speaking character if the line starts with one, see [`StringInfo::character_name`].
+    /// - `lock` is computed by [`string_lock`], so that translations can be detected as out of date.
+    /// - `comment` lists the hashtags of the line other than its ID, see [`line_metadata_comment`].
+    ///
+    /// Rows are sorted by line ID so that the output stays stable between compilations.
+    pub fn string_table_to_csv(&self, language: &str) -> String {
+        let mut entries: Vec<_> = self.string_table.iter().collect();
+        entries.sort_by(|(lhs, _), (rhs, _)| lhs.as_str().cmp(rhs.as_str()));
+
+        let mut csv =
+            String::from("language,id,text,character,file,node,lineNumber,lock,comment\n");
+        for (line_id, string_info) in entries {
+            let line_number = string_info.line_number.to_string();
+            let lock = string_lock(&string_info.text);
+            let comment = line_metadata_comment(&string_info.metadata);
+            let character_name = string_info.character_name().unwrap_or_default();
+            let row = [
+                language,
+                line_id.as_str(),
+                string_info.text.as_str(),
+                character_name.as_str(),
+                string_info.file_name.as_str(),
+                string_info.node_name.as_str(),
+                line_number.as_str(),
+                lock.as_str(),
+                comment.as_str(),
+            ]
+            .map(escape_csv_field)
+            .join(",");
+            csv.push_str(&row);
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// The prefix of the comment built by [`line_metadata_comment`].
+pub const LINE_METADATA_PREFIX: &str = "Line metadata: ";
+
+/// Computes the lock of a line's text, i.e. the first 8 hex digits of its SHA-256 hash.
+///
+/// Translations keep the lock of the text they were translated from,
+/// so a lock that differs from the one of the current text marks a translation as out of date.
+///
+/// Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/462c735766a4c4881cd1ef1f15de28c83b2ba0a8/Editor/Importers/YarnImporter.cs#L149>
+pub fn string_lock(text: &str) -> String {
+    const MAX_CHARS: usize = 8;
+    let hash = Sha256::digest(text);
+    format!("{hash:x}").chars().take(MAX_CHARS).collect()
+}
+
+/// Generates the comment for the metadata (i.e. hashtags) of a line, as written to the `comment` column of a strings file.
+/// The line ID is part of the metadata but skipped.
+///
+/// ## Return value
+/// [`LINE_METADATA_PREFIX`], followed by each piece of metadata separated by whitespace.
+/// If there is no metadata besides the line ID, returns an empty string instead.
+///
+/// Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/462c735766a4c4881cd1ef1f15de28c83b2ba0a8/Editor/Importers/YarnProjectImporter.cs#L652>
+pub fn line_metadata_comment(metadata: impl IntoIterator<Item = impl AsRef<str>>) -> String {
+    let metadata: Vec<_> = metadata
+        .into_iter()
+        .filter(|metadata| !metadata.as_ref().starts_with("line:"))
+        .collect();
+    if metadata.is_empty() {
+        String::new()
+    } else {
+        let metadata: Vec<_> = metadata.iter().map(AsRef::as_ref).collect();
+        format!("{LINE_METADATA_PREFIX}{}", metadata.join(" "))
+    }
+}
+
+/// Quotes a field if it contains characters that have a special meaning in CSV.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_string_table_as_csv() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+Alice: Hello, Bob! #line:b #happy
+Bob: Hi. #line:a
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+        let csv = result.string_table_to_csv("en");
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("language,id,text,character,file,node,lineNumber,lock,comment")
+        );
+        let lock = string_lock("Bob: Hi.");
+        assert_eq!(
+            lines.next(),
+            Some(format!("en,line:a,Bob: Hi.,Bob,test.yarn,Start,4,{lock},").as_str())
+        );
+        let lock = string_lock("Alice: Hello, Bob!");
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!(
+                    "en,line:b,\"Alice: Hello, Bob!\",Alice,test.yarn,Start,3,{lock},Line metadata: happy"
+                )
+                .as_str()
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn computes_lock_from_text() {
+        assert_eq!(string_lock(""), "e3b0c442");
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+
+/// The syntax tree of a single Yarn file, as produced by [`Compiler::parse`].
+///
+/// The tree mirrors the grammar of the Yarn language: every [`SyntaxNode`] is either
+/// an instance of a grammar rule such as `node`, `header` or `line_statement`, or a token.
+/// Whitespace and comments are not part of the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SyntaxTree {
+    /// The name of the file this tree was parsed from.
+    pub file_name: String,
+
+    /// The root of the tree, which is always an instance of the `dialogue` rule.
+    pub root: SyntaxNode,
+}
+
+impl SyntaxTree {
+    /// Returns the nodes of the file, i.e. the children of the root that are instances of the `node` rule.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.root.children_of_rule("node")
+    }
+}
+
+/// A single element of a [`SyntaxTree`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SyntaxNode {
+    /// The name of the grammar rule this element is an instance of, e.g. `line_statement`.
+    /// [`None`] if this element is a token.
+    pub rule: Option<String>,
+
+    /// The text of all tokens in this element, without whitespace between them.
+    pub text: String,
+
+    /// The 1-indexed line number at which this element starts in the file.
+    pub line_number: usize,
+
+    /// The elements this element consists of. Empty for tokens.
+    pub children: Vec<SyntaxNode>,
+}
+
+impl SyntaxNode {
+    /// Returns `true` if this element is a token rather than an instance of a grammar rule.
+    pub fn is_token(&self) -> bool {
+        self.rule.is_none()
+    }
+
+    /// Returns the direct children of this element that are instances of the given grammar rule.
+    pub fn children_of_rule<'a>(&'a self, rule: &'a str) -> impl Iterator<Item = &'a SyntaxNode> {
+        self.children
+            .iter()
+            .filter(move |child| child.rule.as_deref() == Some(rule))
+    }
+}
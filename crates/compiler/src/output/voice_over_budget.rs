@@ -0,0 +1,192 @@
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use yarnspinner_core::prelude::*;
+
+/// The word and character counts of the lines in a [`Compilation::string_table`], e.g. for voice-over teams
+/// to estimate how long recording them will take. Created by [`Compilation::voice_over_budget`].
+///
+/// All lists are sorted by line ID or name, so that the output stays stable between compilations and can be diffed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VoiceOverBudget {
+    /// The counts of every line and option, sorted by line ID.
+    pub lines: Vec<LineLength>,
+    /// The summed up counts of the lines in each node, sorted by node name.
+    pub nodes: Vec<LengthTotals>,
+    /// The summed up counts of the lines spoken by each character, sorted by character name.
+    /// Lines without a character name are not included.
+    pub characters: Vec<LengthTotals>,
+}
+
+/// The word and character counts of a single line in a [`VoiceOverBudget`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LineLength {
+    /// The ID of the line.
+    pub line_id: LineId,
+    /// The name of the node the line was found in.
+    pub node_name: String,
+    /// The speaking character, see [`StringInfo::character_name`].
+    pub character_name: Option<String>,
+    /// The number of whitespace-separated words that are spoken, i.e. without the character name and markup.
+    /// Substitutions such as `{0}` count as one word each.
+    pub word_count: usize,
+    /// The number of characters that are spoken, i.e. without the character name and markup, including spaces between words.
+    pub character_count: usize,
+}
+
+/// The summed up counts of a group of lines in a [`VoiceOverBudget`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LengthTotals {
+    /// The name of the node or character.
+    pub name: String,
+    /// The number of lines in the group.
+    pub line_count: usize,
+    /// The sum of [`LineLength::word_count`] of the lines in the group.
+    pub word_count: usize,
+    /// The sum of [`LineLength::character_count`] of the lines in the group.
+    pub character_count: usize,
+}
+
+impl LengthTotals {
+    fn add(&mut self, line: &LineLength) {
+        self.line_count += 1;
+        self.word_count += line.word_count;
+        self.character_count += line.character_count;
+    }
+}
+
+impl Compilation {
+    /// Counts the spoken words and characters of every line and option in the [`Compilation::string_table`],
+    /// and sums them up per node and per character. See [`VoiceOverBudget`].
+    pub fn voice_over_budget(&self) -> VoiceOverBudget {
+        let mut lines: Vec<_> = self
+            .string_table
+            .iter()
+            .map(|(line_id, string_info)| {
+                let plain_text = string_info.plain_text();
+                let spoken_text = plain_text.spoken_text();
+                let spoken_text = spoken_text.trim();
+                LineLength {
+                    line_id: line_id.clone(),
+                    node_name: string_info.node_name.clone(),
+                    character_name: plain_text.character_name,
+                    word_count: spoken_text.split_whitespace().count(),
+                    character_count: spoken_text.chars().count(),
+                }
+            })
+            .collect();
+        lines.sort_by(|lhs, rhs| lhs.line_id.as_str().cmp(rhs.line_id.as_str()));
+
+        let mut nodes = BTreeMap::new();
+        let mut characters = BTreeMap::new();
+        for line in &lines {
+            nodes
+                .entry(line.node_name.clone())
+                .or_insert_with(LengthTotals::default)
+                .add(line);
+            if let Some(character_name) = &line.character_name {
+                characters
+                    .entry(character_name.clone())
+                    .or_insert_with(LengthTotals::default)
+                    .add(line);
+            }
+        }
+        let with_names = |totals: BTreeMap<String, LengthTotals>| {
+            totals
+                .into_iter()
+                .map(|(name, totals)| LengthTotals { name, ..totals })
+                .collect()
+        };
+        VoiceOverBudget {
+            lines,
+            nodes: with_names(nodes),
+            characters: with_names(characters),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_words_without_markup() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+---
+Alice: I am [wave]very[/wave] happy to see you! #line:a
+Bob: Me too.[pause/] #line:b
+The end. #line:c
+It is 10:30 now. #line:e
+-> Alice: [b]Bye[/b] #line:d
+==="
+            .to_string(),
+        };
+        let budget = Compiler::new()
+            .add_file(file)
+            .compile()
+            .unwrap()
+            .voice_over_budget();
+
+        let word_counts: Vec<_> = budget
+            .lines
+            .iter()
+            .map(|line| (line.line_id.as_str(), line.word_count))
+            .collect();
+        assert_eq!(
+            word_counts,
+            [
+                ("line:a", 7),
+                ("line:b", 2),
+                ("line:c", 2),
+                ("line:d", 1),
+                ("line:e", 2)
+            ]
+        );
+        assert_eq!(budget.lines[0].character_count, 27);
+        assert_eq!(budget.lines[0].character_name.as_deref(), Some("Alice"));
+        assert_eq!(budget.lines[2].character_name, None);
+        // Like the runtime, everything before the first colon is the character name
+        assert_eq!(budget.lines[4].character_name.as_deref(), Some("It is 10"));
+
+        assert_eq!(
+            budget.nodes,
+            [LengthTotals {
+                name: "Start".to_owned(),
+                line_count: 5,
+                word_count: 14,
+                character_count: 27 + 7 + 8 + 3 + 7,
+            }]
+        );
+        let characters: Vec<_> = budget
+            .characters
+            .iter()
+            .map(|totals| (totals.name.as_str(), totals.line_count, totals.word_count))
+            .collect();
+        assert_eq!(
+            characters,
+            [("Alice", 2, 8), ("Bob", 1, 2), ("It is 10", 1, 2)]
+        );
+    }
+}
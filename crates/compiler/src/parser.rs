@@ -3,6 +3,8 @@
 mod actual_types;
 pub(crate) mod generated;
 mod indent_aware_lexer;
+mod token_info;
 
 pub(crate) use actual_types::*;
 pub(crate) use indent_aware_lexer::IndentAwareYarnSpinnerLexer as YarnSpinnerLexer;
+pub use token_info::*;
@@ -6700,7 +6700,7 @@ pub trait Declare_statementContextAttrs<'input>:
     {
         self.get_token(OPERATOR_ASSIGNMENT, 0)
     }
-    fn value(&self) -> Option<Rc<ValueContextAll<'input>>>
+    fn expression(&self) -> Option<Rc<ExpressionContextAll<'input>>>
     where
         Self: Sized,
     {
@@ -6774,9 +6774,9 @@ where
                     .base
                     .match_token(OPERATOR_ASSIGNMENT, &mut recog.err_handler)?;
 
-                /*InvokeRule value*/
+                /*InvokeRule expression*/
                 recog.base.set_state(294);
-                recog.value()?;
+                recog.expression_rec(0)?;
 
                 recog.base.set_state(297);
                 recog.err_handler.sync(&mut recog.base)?;
@@ -7415,7 +7415,7 @@ const _serializedATN: &'static str =
 	\x04\x02\x02\u{122}\u{11a}\x03\x02\x02\x02\u{122}\u{123}\x03\x02\x02\x02\
 	\u{123}\x31\x03\x02\x02\x02\u{124}\u{125}\x07\x11\x02\x02\u{125}\u{126}\
 	\x07\x47\x02\x02\u{126}\u{127}\x05\x1a\x0e\x02\u{127}\u{128}\x07\x21\x02\
-	\x02\u{128}\u{12b}\x05\x18\x0d\x02\u{129}\u{12a}\x07\x39\x02\x02\u{12a}\
+	\x02\u{128}\u{12b}\x05\x16\x0c\x02\u{129}\u{12a}\x07\x39\x02\x02\u{12a}\
 	\u{12c}\x07\x3b\x02\x02\u{12b}\u{129}\x03\x02\x02\x02\u{12b}\u{12c}\x03\
 	\x02\x02\x02\u{12c}\u{12d}\x03\x02\x02\x02\u{12d}\u{12e}\x07\x4d\x02\x02\
 	\u{12e}\x33\x03\x02\x02\x02\u{12f}\u{130}\x07\x11\x02\x02\u{130}\u{131}\
@@ -321,7 +321,8 @@ where
                     .with_context(get_newline_indentation_text(current_token))
                     .with_start_line(current_token.line as usize)
                     .with_file_name(self.file_name.clone())
-                    .with_severity(DiagnosticSeverity::Warning),
+                    .with_severity(DiagnosticSeverity::Warning)
+                    .with_code(DiagnosticCode::MixedIndentation),
             );
         }
 
@@ -354,7 +355,7 @@ where
     fn diagnose_newlines_in_commands(&mut self, token: &CommonToken<'input>) {
         if token.get_text().contains('\n') {
             let line_len = token.get_text().lines().count();
-            let last_line_len = token.get_text().lines().last().unwrap().len();
+            let last_line_len = token.get_text().lines().last().unwrap().chars().count();
             self.diagnostics.borrow_mut().push(
                 Diagnostic::from_message("Newlines are not allowed in commands")
                     .with_range(
@@ -382,7 +383,7 @@ fn get_newline_indentation_range(token: &CommonToken<'_>) -> Range<Position> {
     let start = Position { line, character: 0 };
     let stop = Position {
         line,
-        character: token.get_text().len(),
+        character: token.get_text().chars().count(),
     };
 
     start..stop
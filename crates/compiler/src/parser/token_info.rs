@@ -0,0 +1,122 @@
+use super::generated::yarnspinnerlexer;
+use super::YarnSpinnerLexer;
+use crate::prelude::*;
+use antlr_rust::token::{CommonToken, Token, TOKEN_EOF};
+use antlr_rust::{InputStream, TokenSource};
+use std::ops::Range;
+
+/// A single token produced by the lexer, as returned by [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TokenInfo {
+    /// The symbolic name of the token's type in the grammar, e.g. `SHORTCUT_ARROW` or `INDENT`.
+    pub token_type: String,
+    /// The source text of the token.
+    /// Empty for the tokens inserted by the lexer to track indentation, i.e. `INDENT`, `DEDENT` and `BLANK_LINE_FOLLOWING_OPTION`.
+    pub text: String,
+    /// The range of the token in the source. Tokens inserted by the lexer have an empty range.
+    pub range: Range<Position>,
+}
+
+/// Runs the lexer used by the [`Compiler`] over the given Yarn source and returns all tokens it produces, e.g. for syntax highlighting.
+///
+/// This includes the synthetic `INDENT`, `DEDENT` and `BLANK_LINE_FOLLOWING_OPTION` tokens that structure options,
+/// but not the ones the grammar skips entirely, such as most whitespace. Lexing never fails;
+/// invalid input is still split into tokens and only reported by the parser.
+pub fn tokenize(source: &str) -> Vec<TokenInfo> {
+    let mut lexer = YarnSpinnerLexer::new(InputStream::new(source), "<input>".to_owned());
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        if token.get_token_type() == TOKEN_EOF {
+            break;
+        }
+        tokens.push(TokenInfo::from_token(&token));
+    }
+    tokens
+}
+
+impl TokenInfo {
+    fn from_token(token: &CommonToken<'_>) -> Self {
+        let token_type = yarnspinnerlexer::_SYMBOLIC_NAMES
+            .get(token.get_token_type() as usize)
+            .copied()
+            .flatten()
+            .unwrap_or("<INVALID>")
+            .to_owned();
+        let text = token.get_text().to_string();
+        let start = Position {
+            line: token.get_line_as_usize().saturating_sub(1),
+            character: token.get_column_as_usize(),
+        };
+        let end = match text.rsplit_once('\n') {
+            Some((before_last_line, last_line)) => Position {
+                line: start.line + before_last_line.matches('\n').count() + 1,
+                character: last_line.chars().count(),
+            },
+            None => Position {
+                line: start.line,
+                character: start.character + text.chars().count(),
+            },
+        };
+        Self {
+            token_type,
+            text,
+            range: start..end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_shortcut_arrows_and_indentation() {
+        let tokens = tokenize(
+            "title: Start
+---
+Hello
+-> Yes
+    Great
+-> No
+===
+",
+        );
+
+        let arrows: Vec<_> = tokens
+            .iter()
+            .filter(|token| token.token_type == "SHORTCUT_ARROW")
+            .collect();
+        assert_eq!(arrows.len(), 2);
+        assert_eq!(arrows[0].text, "->");
+        assert_eq!(
+            arrows[0].range,
+            Position {
+                line: 3,
+                character: 0
+            }..Position {
+                line: 3,
+                character: 2
+            }
+        );
+        assert_eq!(
+            arrows[1].range.start,
+            Position {
+                line: 5,
+                character: 0
+            }
+        );
+
+        let indent = tokens
+            .iter()
+            .position(|token| token.token_type == "INDENT")
+            .unwrap();
+        let dedent = tokens
+            .iter()
+            .position(|token| token.token_type == "DEDENT")
+            .unwrap();
+        assert!(indent < dedent);
+        assert!(tokens[indent].text.is_empty());
+    }
+}
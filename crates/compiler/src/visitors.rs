@@ -1,14 +1,20 @@
 mod code_generation_visitor;
+mod command_visitor;
 mod constant_value_visitor;
 mod declaration_visitor;
 mod hashable_interval;
+mod jump_graph_visitor;
 mod last_line_before_options_visitor;
+mod markup_visitor;
+mod node_info_visitor;
 mod node_tracking_visitor;
 mod string_table_generator_visitor;
 mod type_check_visitor;
+mod variable_usage_visitor;
 
 pub(crate) use self::{
-    code_generation_visitor::*, declaration_visitor::*, hashable_interval::*,
-    last_line_before_options_visitor::*, node_tracking_visitor::*,
-    string_table_generator_visitor::*, type_check_visitor::*,
+    code_generation_visitor::*, command_visitor::*, declaration_visitor::*, hashable_interval::*,
+    jump_graph_visitor::*, last_line_before_options_visitor::*, markup_visitor::*,
+    node_info_visitor::*, node_tracking_visitor::*, string_table_generator_visitor::*,
+    type_check_visitor::*, variable_usage_visitor::*,
 };
@@ -5,6 +5,7 @@ use crate::prelude::generated::yarnspinnerlexer;
 use crate::prelude::generated::yarnspinnerparser::*;
 use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
 use crate::prelude::*;
+use crate::visitors::{get_detour_destination, get_hashtag_texts};
 use antlr_rust::parser_rule_context::ParserRuleContext;
 use antlr_rust::token::Token;
 use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat, Tree};
@@ -17,6 +18,14 @@ use yarnspinner_core::types::Type;
 pub(crate) struct CodeGenerationVisitor<'a, 'input: 'a> {
     compiler_listener: &'a mut CompilerListener<'input>,
     tracking_enabled: Option<String>,
+    /// The labels marking the ends of the `<<once>>` blocks we are currently inside of, innermost last,
+    /// together with the body they were opened in, see [`CodeGenerationVisitor::current_body`]
+    once_end_labels: Vec<(String, usize)>,
+    /// The number of `<<once>>` blocks of the node visited so far, used to name their variables
+    once_count: usize,
+    /// Identifies the `<<if>>` clause or option whose statements are being visited, or 0 for the node itself
+    current_body: usize,
+    body_count: usize,
     _dummy: (),
 }
 
@@ -28,6 +37,10 @@ impl<'a, 'input: 'a> CodeGenerationVisitor<'a, 'input> {
         Self {
             compiler_listener,
             tracking_enabled: tracking_enabled.into(),
+            once_end_labels: Default::default(),
+            once_count: Default::default(),
+            current_body: Default::default(),
+            body_count: Default::default(),
             _dummy: Default::default(),
         }
     }
@@ -57,6 +70,178 @@ impl<'a, 'input: 'a> CodeGenerationVisitor<'a, 'input> {
         }
     }
 
+    /// Reports every `<<once>>` block of the node that was never closed with an `<<endonce>>`.
+    pub(crate) fn diagnose_unclosed_once_blocks(&mut self, ctx: &impl ParserRuleContext<'input>) {
+        if self.once_end_labels.is_empty() {
+            return;
+        }
+        let message = format!(
+            "{} <<once>> block(s) missing a closing <<endonce>>",
+            self.once_end_labels.len()
+        );
+        self.compiler_listener.diagnostics.borrow_mut().push(
+            Diagnostic::from_message(message)
+                .with_file_name(self.compiler_listener.file.name.clone())
+                .with_parser_context(ctx, self.compiler_listener.file.tokens()),
+        );
+    }
+
+    /// Skips to the end of the block if it has already run, and otherwise remembers that it did.
+    fn generate_code_for_once(&mut self, ctx: &Command_statementContext<'input>) {
+        let node_name = &self.compiler_listener.current_node.as_ref().unwrap().name;
+        let variable_name = Library::generate_unique_once_variable(node_name, self.once_count);
+        self.once_count += 1;
+        let run_label = self.compiler_listener.register_label("once_run");
+        let end_label = self.compiler_listener.register_label("once_end");
+        let token = ctx.start();
+
+        self.compiler_listener.emit(
+            Emit::from_op_code(OpCode::PushVariable)
+                .with_token(token.deref())
+                .with_operand(variable_name.clone()),
+        );
+        self.compiler_listener.emit(
+            Emit::from_op_code(OpCode::JumpIfFalse)
+                .with_token(token.deref())
+                .with_operand(run_label.clone()),
+        );
+        self.compiler_listener
+            .emit(Emit::from_op_code(OpCode::Pop).with_token(token.deref()));
+        self.compiler_listener.emit(
+            Emit::from_op_code(OpCode::JumpTo)
+                .with_token(token.deref())
+                .with_operand(end_label.clone()),
+        );
+
+        let current_node = self.compiler_listener.current_node.as_mut().unwrap();
+        current_node
+            .labels
+            .insert(run_label, current_node.instructions.len() as i32);
+        self.compiler_listener
+            .emit(Emit::from_op_code(OpCode::Pop).with_token(token.deref()));
+        self.compiler_listener.emit(
+            Emit::from_op_code(OpCode::PushBool)
+                .with_token(token.deref())
+                .with_operand(true),
+        );
+        self.compiler_listener.emit(
+            Emit::from_op_code(OpCode::StoreVariable)
+                .with_token(token.deref())
+                .with_operand(variable_name),
+        );
+        self.compiler_listener
+            .emit(Emit::from_op_code(OpCode::Pop).with_token(token.deref()));
+
+        self.once_end_labels.push((end_label, self.current_body));
+    }
+
+    fn generate_code_for_endonce(&mut self, ctx: &Command_statementContext<'input>) {
+        let message = match self.once_end_labels.last() {
+            Some((_, body)) if *body == self.current_body => {
+                let (end_label, _) = self.once_end_labels.pop().unwrap();
+                self.insert_label_here(end_label);
+                return;
+            }
+            Some(_) => "Found <<endonce>> in a different <<if>> clause or option than its <<once>>",
+            None => "Found <<endonce>> without a matching <<once>>",
+        };
+        self.compiler_listener.diagnostics.borrow_mut().push(
+            Diagnostic::from_message(message)
+                .with_file_name(self.compiler_listener.file.name.clone())
+                .with_parser_context(ctx, self.compiler_listener.file.tokens()),
+        );
+    }
+
+    /// Visits the statements of an `<<if>>` clause or an option.
+    /// A `<<once>>` block started in there must also end in there, as it would otherwise only be skipped in some branches.
+    fn generate_code_for_body(
+        &mut self,
+        ctx: &impl ParserRuleContext<'input>,
+        statements: &[Rc<StatementContext<'input>>],
+    ) {
+        let outer_body = self.current_body;
+        self.body_count += 1;
+        self.current_body = self.body_count;
+        for statement in statements {
+            self.visit(statement.as_ref());
+        }
+
+        let unclosed_count = self
+            .once_end_labels
+            .iter()
+            .rev()
+            .take_while(|(_, body)| *body == self.current_body)
+            .count();
+        if unclosed_count > 0 {
+            let message = format!(
+                "{unclosed_count} <<once>> block(s) missing a closing <<endonce>> before the end of the <<if>> clause or option containing them"
+            );
+            self.compiler_listener.diagnostics.borrow_mut().push(
+                Diagnostic::from_message(message)
+                    .with_file_name(self.compiler_listener.file.name.clone())
+                    .with_parser_context(ctx, self.compiler_listener.file.tokens()),
+            );
+            // Keep the jumps to the end of the blocks valid
+            let first_unclosed = self.once_end_labels.len() - unclosed_count;
+            for (end_label, _) in self.once_end_labels.split_off(first_unclosed) {
+                self.insert_label_here(end_label);
+            }
+        }
+        self.current_body = outer_body;
+    }
+
+    fn insert_label_here(&mut self, label: String) {
+        let current_node = self.compiler_listener.current_node.as_mut().unwrap();
+        current_node
+            .labels
+            .insert(label, current_node.instructions.len() as i32);
+    }
+
+    /// Runs the destination node and continues after the `<<detour>>` once it returns.
+    /// Inline expressions have already been evaluated onto the stack by the caller.
+    fn generate_code_for_detour(
+        &mut self,
+        ctx: &Command_statementContext<'input>,
+        destination: &str,
+        expression_count: usize,
+    ) {
+        let token = ctx.start();
+        match (destination, expression_count) {
+            // The destination is computed by an expression, whose result is already on the stack
+            ("{0}", 1) => {}
+            (destination, 0)
+                if !destination.is_empty() && !destination.contains(char::is_whitespace) =>
+            {
+                self.compiler_listener.emit(
+                    Emit::from_op_code(OpCode::PushString)
+                        .with_token(token.deref())
+                        .with_operand(destination.to_owned()),
+                );
+            }
+            _ => {
+                self.compiler_listener.diagnostics.borrow_mut().push(
+                    Diagnostic::from_message(
+                        "<<detour>> expects the name of a node or an expression in braces, e.g. <<detour Shop>> or <<detour {$next_node}>>",
+                    )
+                    .with_file_name(self.compiler_listener.file.name.clone())
+                    .with_parser_context(ctx, self.compiler_listener.file.tokens()),
+                );
+                return;
+            }
+        }
+        self.compiler_listener
+            .emit(Emit::from_op_code(OpCode::DetourToNode).with_token(token.deref()));
+    }
+
+    /// Ends the current node, returning to the node that detoured to it, if any.
+    fn generate_code_for_return(&mut self, ctx: &Command_statementContext<'input>) {
+        if let Some(tracking_enabled) = self.tracking_enabled.clone() {
+            Self::generate_tracking_code(self.compiler_listener, tracking_enabled);
+        }
+        self.compiler_listener
+            .emit(Emit::from_op_code(OpCode::Return).with_token(ctx.start().deref()));
+    }
+
     // [sic] really ought to make this emit like a list of opcodes actually
     pub(crate) fn generate_tracking_code(compiler: &mut CompilerListener, variable_name: String) {
         // pushing the var and the increment onto the stack
@@ -103,11 +288,18 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
         let line_id_tag = get_line_id_tag(&ctx.hashtag_all())
             .expect("Internal error: line should have an implicit or explicit line ID tag, but none was found. This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
         let line_id = line_id_tag.text.as_ref().unwrap().get_text().to_owned();
+        // ## Implementation note
+        // Not in the original: the `lastline` tag added by the `LastLineBeforeOptionsVisitor`
+        // is passed to the runtime as an additional operand so that it does not need the string table metadata.
+        let is_last_line_before_options = get_hashtag_texts(&ctx.hashtag_all())
+            .iter()
+            .any(|tag| tag == "lastline");
         self.compiler_listener.emit(
             Emit::from_op_code(OpCode::RunLine)
                 .with_token(ctx.start().deref())
                 .with_operand(line_id)
-                .with_operand(expression_count),
+                .with_operand(expression_count)
+                .with_operand(is_last_line_before_options),
         );
     }
 
@@ -264,6 +456,28 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
 
     fn visit_variable(&mut self, ctx: &VariableContext<'input>) -> Self::Return {
         let variable_name = ctx.VAR_ID().unwrap().get_text();
+        if let Some(value) = self
+            .compiler_listener
+            .constants
+            .get(&variable_name)
+            .cloned()
+        {
+            // Constants are not stored anywhere at runtime, so we emit their value directly.
+            let emit = match value {
+                YarnValue::Number(number) => {
+                    Emit::from_op_code(OpCode::PushFloat).with_operand(number)
+                }
+                YarnValue::String(string) => {
+                    Emit::from_op_code(OpCode::PushString).with_operand(string)
+                }
+                YarnValue::Boolean(boolean) => {
+                    Emit::from_op_code(OpCode::PushBool).with_operand(boolean)
+                }
+            };
+            self.compiler_listener
+                .emit(emit.with_token(ctx.start().deref()));
+            return;
+        }
         self.compiler_listener.emit(
             Emit::from_op_code(OpCode::PushVariable)
                 .with_token(ctx.start().deref())
@@ -431,10 +645,21 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
         );
 
         // [sic] TODO: look into replacing this as it seems a bit odd
-        match composed_string.as_str() {
+        match composed_string.trim() {
+            // Not part of the original implementation: content between `<<once>>` and `<<endonce>>`
+            // only runs the first time it is reached, remembered in a generated variable.
+            "once" => self.generate_code_for_once(ctx),
+            "endonce" => self.generate_code_for_endonce(ctx),
+            // Not part of the original implementation: `<<detour>>` runs another node and continues here once it returns,
+            // `<<return>>` ends the current node early.
+            "return" => self.generate_code_for_return(ctx),
+            text if get_detour_destination(text).is_some() => {
+                let destination = get_detour_destination(text).unwrap_or_default();
+                self.generate_code_for_detour(ctx, destination, expression_count)
+            }
             "stop" => {
                 // "stop" is a special command that immediately stops
-                // execution
+                // execution, including all nodes waiting for a `<<detour>>` to return
                 self.compiler_listener.emit(
                     Emit::from_op_code(OpCode::Stop).with_token(formatted_text.start().deref()),
                 );
@@ -483,11 +708,11 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
             // This line statement may have a condition on it. If it does,
             // emit code that evaluates the condition, and add a flag on the
             // 'Add Option' instruction that indicates that a condition exists.
-            let has_line_condition = if let Some(expression) = shortcut
+            let line_condition = shortcut
                 .line_statement()
                 .and_then(|ctx| ctx.line_condition())
-                .and_then(|ctx| ctx.expression())
-            {
+                .and_then(|ctx| ctx.expression());
+            let has_line_condition = if let Some(expression) = line_condition.as_ref() {
                 // Evaluate the condition, and leave it on the stack
                 self.visit(expression.as_ref());
                 true
@@ -509,15 +734,30 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
                 .expect("Internal error: no line ID provided. This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
             let line_id = line_id_tag.text.as_ref().unwrap().get_text().to_owned();
 
+            // ## Implementation note
+            // Not in the original: the source of the condition and the option's hashtags are passed along
+            // as additional operands so that the runtime can tell the game why an option is unavailable.
+            let condition_source = line_condition
+                .map(|expression| {
+                    expression.get_text_with_whitespace(self.compiler_listener.file.tokens())
+                })
+                .unwrap_or_default();
+            let tags = get_hashtag_texts(&line_statement.hashtag_all())
+                .into_iter()
+                .filter(|tag| !tag.starts_with("line:"));
+
             // And add this option to the list.
-            self.compiler_listener.emit(
-                Emit::from_op_code(OpCode::AddOption)
-                    .with_token(line_statement.start().deref())
-                    .with_operand(line_id)
-                    .with_operand(option_destination_label)
-                    .with_operand(expression_count)
-                    .with_operand(has_line_condition),
-            );
+            let mut emit = Emit::from_op_code(OpCode::AddOption)
+                .with_token(line_statement.start().deref())
+                .with_operand(line_id)
+                .with_operand(option_destination_label)
+                .with_operand(expression_count)
+                .with_operand(has_line_condition)
+                .with_operand(condition_source);
+            for tag in tags {
+                emit = emit.with_operand(tag);
+            }
+            self.compiler_listener.emit(emit);
         }
         // All of the options that we intend to show are now ready to go.
         let token = ctx.stop();
@@ -539,9 +779,7 @@ impl<'a, 'input: 'a> YarnSpinnerParserVisitorCompat<'input> for CodeGenerationVi
             );
 
             // Run through all the children statements of the shortcut option
-            for child in shortcut.statement_all() {
-                self.visit(child.as_ref());
-            }
+            self.generate_code_for_body(shortcut.as_ref(), &shortcut.statement_all());
 
             // Jump to the end of this shortcut option group.
             self.compiler_listener.emit(
@@ -675,9 +913,7 @@ impl<'a, 'input: 'a> CodeGenerationVisitor<'a, 'input> {
         }
 
         // running through all of the children statements
-        for child in children {
-            self.visit(child.as_ref());
-        }
+        self.generate_code_for_body(ctx, children);
 
         self.compiler_listener.emit(
             Emit::from_op_code(OpCode::JumpTo)
@@ -0,0 +1,117 @@
+use crate::prelude::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use antlr_rust::parser_rule_context::ParserRuleContext;
+use antlr_rust::token::Token;
+use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat, Tree};
+
+/// A visitor that collects every command run in a file.
+pub(crate) struct CommandVisitor {
+    pub(crate) commands: Vec<CommandInfo>,
+    /// The node names and indices within their node of all `<<once>>` commands
+    pub(crate) once_blocks: Vec<(String, usize)>,
+    file_name: String,
+    current_node_name: String,
+    once_count_in_node: usize,
+    _dummy: (),
+}
+
+impl CommandVisitor {
+    pub(crate) fn new(file_name: impl Into<String>) -> Self {
+        Self {
+            commands: Default::default(),
+            once_blocks: Default::default(),
+            file_name: file_name.into(),
+            current_node_name: Default::default(),
+            once_count_in_node: Default::default(),
+            _dummy: (),
+        }
+    }
+}
+
+impl<'input> ParseTreeVisitorCompat<'input> for CommandVisitor {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'input> YarnSpinnerParserVisitorCompat<'input> for CommandVisitor {
+    fn visit_node(&mut self, ctx: &NodeContext<'input>) -> Self::Return {
+        self.current_node_name = ctx
+            .header_all()
+            .iter()
+            .find(|header| header.header_key.as_ref().unwrap().get_text() == "title")
+            .and_then(|header| header.header_value.as_ref().map(|value| value.get_text()))
+            .unwrap_or_default()
+            .to_owned();
+        self.once_count_in_node = 0;
+        ParseTreeVisitorCompat::visit_children(self, ctx);
+    }
+
+    fn visit_command_statement(&mut self, ctx: &Command_statementContext<'input>) -> Self::Return {
+        let Some(formatted_text) = ctx.command_formatted_text() else {
+            return;
+        };
+        // Replace inline expressions by a placeholder so that they count as a single argument,
+        // no matter how many spaces they contain.
+        let text: String = formatted_text
+            .get_children()
+            .map(|child| {
+                if child.get_child_count() == 0 {
+                    child.get_text()
+                } else {
+                    "0".to_owned()
+                }
+            })
+            .collect();
+        let mut words = split_command_text(&text)
+            .into_iter()
+            .map(|component| component.text);
+        let Some(name) = words.next() else {
+            return;
+        };
+        // `stop`, `once`, `endonce`, `detour` and `return` are handled by the compiler itself and never reach the game
+        match name.as_str() {
+            "stop" | "endonce" | "detour" | "return" => return,
+            "once" => {
+                self.once_blocks
+                    .push((self.current_node_name.clone(), self.once_count_in_node));
+                self.once_count_in_node += 1;
+                return;
+            }
+            _ => {}
+        }
+        self.commands.push(CommandInfo {
+            name,
+            argument_count: words.count(),
+            file_name: self.file_name.clone(),
+            node_name: self.current_node_name.clone(),
+            line_number: ctx.start().line as usize,
+        });
+    }
+}
+
+/// Returns the destination of a `<<detour>>` command, given the text of the command.
+/// The destination is empty if it is missing, and `{0}` if it is an inline expression.
+pub(crate) fn get_detour_destination(command_text: &str) -> Option<&str> {
+    let destination = command_text.trim().strip_prefix("detour")?;
+    (destination.is_empty() || destination.starts_with(char::is_whitespace))
+        .then(|| destination.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_detour_destinations() {
+        assert_eq!(get_detour_destination("detour Shop"), Some("Shop"));
+        assert_eq!(get_detour_destination(" detour  {0} "), Some("{0}"));
+        assert_eq!(get_detour_destination("detour"), Some(""));
+        assert_eq!(get_detour_destination("detours Shop"), None);
+        assert_eq!(get_detour_destination("fade_out"), None);
+    }
+}
@@ -1,5 +1,6 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/DeclarationVisitor.cs>
 
+use crate::parser_rule_context_ext::ParserRuleContextExt;
 use crate::prelude::generated::yarnspinnerparser::*;
 use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
 use crate::prelude::*;
@@ -7,6 +8,7 @@ use crate::visitors::constant_value_visitor::ConstantValueVisitor;
 use antlr_rust::token::Token;
 use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat};
 use regex::Regex;
+use std::rc::Rc;
 use yarnspinner_core::prelude::*;
 use yarnspinner_core::types::*;
 
@@ -115,6 +117,25 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
         // Get the name of the variable we're declaring
         let variable_context = ctx.variable().unwrap();
         let variable_name = variable_context.get_text();
+        let is_constant = ctx
+            .declaration_type
+            .as_ref()
+            .is_some_and(|declaration_type| declaration_type.get_text() == CONSTANT_KEYWORD);
+        // Any declaration whose value is not a literal declares a smart variable,
+        // whose value is computed from its expression every time it is read.
+        let value_context = get_literal_value_context(ctx);
+        if is_constant && value_context.is_none() {
+            let msg = format!(
+                "Constant {variable_name} must be declared with a constant value, but `{}` is an expression",
+                ctx.expression().unwrap().get_text_with_whitespace(self.file.tokens())
+            );
+            self.diagnostics.push(
+                Diagnostic::from_message(msg)
+                    .with_file_name(&self.file.name)
+                    .with_parser_context(ctx, self.file.tokens()),
+            );
+            return;
+        }
 
         // Does this variable name already exist in our declarations?
         let existing_explicit_declaration = self
@@ -122,6 +143,25 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
             .into_iter()
             .find(|d| !d.is_implicit && d.name == variable_name);
         if let Some(existing_explicit_declaration) = existing_explicit_declaration {
+            if is_constant && existing_explicit_declaration.is_constant {
+                // Repeating a constant, e.g. to keep multiple files self-contained, is fine as long as the value stays the same.
+                let value = value_context
+                    .and_then(|value_context| self.get_declared_value(value_context.as_ref()));
+                if value.map(|value| value.raw_value) != existing_explicit_declaration.default_value
+                {
+                    let msg = format!(
+                        "Constant {} has already been declared with a different value in {}",
+                        existing_explicit_declaration.name,
+                        existing_explicit_declaration.source_file_name,
+                    );
+                    self.diagnostics.push(
+                        Diagnostic::from_message(msg)
+                            .with_file_name(&self.file.name)
+                            .with_parser_context(ctx, self.file.tokens()),
+                    );
+                }
+                return;
+            }
             // Then this is an error, because you can't have two explicit declarations for the same variable.
             let line = existing_explicit_declaration
                 .source_file_line()
@@ -140,15 +180,15 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
         }
 
         // Figure out the value and its type
-        let mut constant_value_visitor =
-            ConstantValueVisitor::new(self.diagnostics.clone(), self.file.clone());
-        let value_context = ctx.value().unwrap();
-        let value = constant_value_visitor.visit(value_context.as_ref());
-        self.diagnostics
-            .extend_from_slice(&constant_value_visitor.diagnostics);
+        let value = value_context
+            .as_ref()
+            .and_then(|value_context| self.get_declared_value(value_context.as_ref()));
 
         // Did the source code name an explicit type?
-        if let Some(declaration_type) = ctx.declaration_type.as_ref() {
+        // Constants always take the type of their value.
+        let explicit_type = if let Some(declaration_type) =
+            ctx.declaration_type.as_ref().filter(|_| !is_constant)
+        {
             let explicit_type = match keyword_to_type(declaration_type.get_text()) {
                 Some(builtin_type) => builtin_type,
 
@@ -174,8 +214,9 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
 
             // Check that the type we've found is compatible with the
             // type of the value that was provided - if it doesn't,
-            // that's a type error
-            if let Some(value) = value.as_ref() {
+            // that's a type error.
+            // The expressions of smart variables are checked once they are compiled in `compile_smart_variables`.
+            if let (Some(value), Some(value_context)) = (value.as_ref(), value_context.as_ref()) {
                 if !value.r#type.is_sub_type_of(&explicit_type) {
                     let msg = format!(
                         "Type {} does not match value {} ({})",
@@ -191,10 +232,28 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
                     return;
                 }
             }
-        }
+            Some(explicit_type)
+        } else {
+            None
+        };
         // We're done creating the declaration!
         let description = get_document_comments(self.file.tokens(), ctx);
         let description_as_option = (!description.is_empty()).then_some(description);
+        if value_context.is_none() {
+            // The type of a smart variable is only known once its expression has been compiled by `compile_smart_variables`.
+            let expression = ctx
+                .expression()
+                .unwrap()
+                .get_text_with_whitespace(self.file.tokens());
+            let declaration = Declaration::new(variable_name, explicit_type.unwrap_or(Type::Any))
+                .with_smart_expression(expression)
+                .with_description_optional(description_as_option)
+                .with_source_file_name(self.file.name.clone())
+                .with_source_node_name_optional(self.current_node_name.clone())
+                .with_range(variable_context.range());
+            self.new_declarations.push(declaration);
+            return;
+        }
         if let Some(value) = value.as_ref() {
             let declaration = Declaration::new(variable_name, value.r#type.clone())
                 .with_default_value(value.raw_value.clone())
@@ -202,11 +261,51 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for DeclarationVisitor<'inpu
                 .with_source_file_name(self.file.name.clone())
                 .with_source_node_name_optional(self.current_node_name.clone())
                 .with_range(variable_context.range());
+            let declaration = if is_constant {
+                declaration.with_constant()
+            } else {
+                declaration
+            };
             self.new_declarations.push(declaration);
         }
     }
 }
 
+impl<'input> DeclarationVisitor<'input> {
+    /// Evaluates the literal value a `<<declare>>` statement assigns.
+    fn get_declared_value(
+        &mut self,
+        value_context: &ValueContextAll<'input>,
+    ) -> Option<InternalValue> {
+        let mut constant_value_visitor =
+            ConstantValueVisitor::new(self.diagnostics.clone(), self.file.clone());
+        let value = constant_value_visitor.visit(value_context);
+        self.diagnostics
+            .extend_from_slice(&constant_value_visitor.diagnostics);
+        value.0
+    }
+}
+
+/// Returns the value of a `<<declare>>` statement if it is a literal,
+/// or `None` if the statement declares a smart variable.
+fn get_literal_value_context<'input>(
+    ctx: &Declare_statementContext<'input>,
+) -> Option<Rc<ValueContextAll<'input>>> {
+    let expression = ctx.expression().unwrap();
+    let ExpressionContextAll::ExpValueContext(expression) = expression.as_ref() else {
+        return None;
+    };
+    let value = expression.value().unwrap();
+    let is_literal = !matches!(
+        value.as_ref(),
+        ValueContextAll::ValueVarContext(_) | ValueContextAll::ValueFuncContext(_)
+    );
+    is_literal.then_some(value)
+}
+
+/// The keyword used in place of a type to declare a compile-time constant, as in `<<declare $name = value as constant>>`.
+const CONSTANT_KEYWORD: &str = "constant";
+
 fn keyword_to_type(keyword: &str) -> Option<Type> {
     match keyword {
         "string" => Some(Type::String),
@@ -219,6 +318,7 @@ fn keyword_to_type(keyword: &str) -> Option<Type> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use yarnspinner_core::prelude::OpCode;
 
     #[test]
     fn recognizes_variable_declarations() {
@@ -230,6 +330,7 @@ mod tests {
 <<declare $bar = \"2\">>
 <<declare $baz to true>>
 <<declare $quux = \"hello there\" as string>>
+{$foo} {$bar} {$baz} {$quux}
 ==="
             .to_string(),
         };
@@ -238,6 +339,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile()
         .unwrap();
@@ -313,6 +418,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reports_declaration_ranges_in_characters() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $café = \"crème\">>
+{$café}
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert_eq!(
+            result.declarations[0].range,
+            Some(
+                Position {
+                    line: 2,
+                    character: 10,
+                }..Position {
+                    line: 2,
+                    character: 15,
+                }
+            )
+        );
+    }
+
     #[test]
     fn catches_type_errors() {
         let file = File {
@@ -328,6 +460,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile();
 
@@ -364,4 +500,141 @@ mod tests {
                 )
         );
     }
+
+    #[test]
+    fn inlines_constants() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $max_gold = 100 as constant>>
+You can carry up to {$max_gold} gold, which is {$max_gold / 2} more than before.
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert!(result.declarations[0].is_constant);
+        let instructions: Vec<_> = result
+            .program
+            .unwrap()
+            .nodes
+            .into_values()
+            .flat_map(|node| node.instructions)
+            .collect();
+        assert!(instructions
+            .iter()
+            .all(|instruction| instruction.opcode != OpCode::PushVariable as i32));
+        assert!(instructions
+            .iter()
+            .all(|instruction| instruction.opcode != OpCode::StoreVariable as i32));
+    }
+
+    #[test]
+    fn rejects_conflicting_constants() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $max_gold = 100 as constant>>
+<<declare $max_gold = 200 as constant>>
+{$max_gold}
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile();
+
+        let diagnostics = result.unwrap_err().0;
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("different value")));
+    }
+
+    #[test]
+    fn rejects_assignments_to_constants() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $max_gold = 100 as constant>>
+<<set $max_gold to 200>>
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile();
+
+        let diagnostics = result.unwrap_err().0;
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("is a constant")));
+    }
+
+    #[test]
+    fn compiles_smart_variables() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $gold = 0>>
+<<declare $is_rich = $gold > 100>>
+<<if $is_rich>>
+    You are rich.
+<<endif>>
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile().unwrap();
+
+        assert!(result.warnings.is_empty());
+        let is_rich = result
+            .declarations
+            .iter()
+            .find(|declaration| declaration.name == "$is_rich")
+            .unwrap();
+        assert_eq!(is_rich.r#type, Type::Boolean);
+        assert_eq!(is_rich.smart_expression.as_deref(), Some("$gold > 100"));
+        let program = result.program.unwrap();
+        assert!(program.smart_variables.contains_key("$is_rich"));
+        assert!(!program.initial_values.contains_key("$is_rich"));
+    }
+
+    #[test]
+    fn rejects_smart_variables_of_the_wrong_type() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $gold = 0>>
+<<declare $is_rich = $gold > 100 as number>>
+{$is_rich}
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile();
+
+        let diagnostics = result.unwrap_err().0;
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("does not match expression")));
+    }
+
+    #[test]
+    fn rejects_constants_computed_from_expressions() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<declare $gold = 0>>
+<<declare $max_gold = $gold * 2 as constant>>
+{$max_gold}
+==="
+            .to_string(),
+        };
+        let result = Compiler::new().add_file(file).compile();
+
+        let diagnostics = result.unwrap_err().0;
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic
+            .message
+            .contains("must be declared with a constant value")));
+    }
 }
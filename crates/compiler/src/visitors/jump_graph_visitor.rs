@@ -0,0 +1,159 @@
+use crate::parser::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use crate::visitors::get_detour_destination;
+use antlr_rust::token::Token;
+use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat, Tree};
+use std::rc::Rc;
+
+/// The node header that marks a node as an intentional entry point into the dialogue,
+/// e.g. `entry: true`.
+pub(crate) const ENTRY_POINT_HEADER: &str = "entry";
+
+/// The name of the node that is treated as an entry point even without an [`ENTRY_POINT_HEADER`],
+/// as it is the conventional start node of a dialogue.
+pub(crate) const DEFAULT_START_NODE: &str = "Start";
+
+/// A visitor that walks a file and collects the static `<<jump>>` and `<<detour>>` destinations of each node in it.
+#[derive(Clone, Default)]
+pub(crate) struct JumpGraphVisitor<'input> {
+    pub(crate) nodes: Vec<JumpGraphNode<'input>>,
+    current_node: Option<JumpGraphNode<'input>>,
+    _dummy: (),
+}
+
+/// A node in the graph formed by `<<jump>>` statements.
+#[derive(Clone)]
+pub(crate) struct JumpGraphNode<'input> {
+    pub(crate) name: String,
+    pub(crate) title_header: Rc<HeaderContextAll<'input>>,
+    /// Whether the node is either the [`DEFAULT_START_NODE`] or explicitly marked as an entry point.
+    pub(crate) is_entry_point: bool,
+    /// The names of the nodes this node jumps to, in order of appearance.
+    pub(crate) destinations: Vec<String>,
+    /// Whether this node contains a `<<jump>>` to an expression, whose destination we cannot know statically.
+    pub(crate) has_dynamic_jump: bool,
+    /// The node this node unconditionally jumps to before running any lines, options or commands, if any.
+    pub(crate) immediate_destination: Option<String>,
+}
+
+/// Builds the jump graph of all nodes in the given files. Each node is paired with the file it was found in.
+pub(crate) fn build_jump_graph<'a, 'input>(
+    parsed_files: &'a [FileParseResult<'input>],
+) -> Vec<(JumpGraphNode<'input>, &'a FileParseResult<'input>)> {
+    let mut nodes = Vec::new();
+    for file in parsed_files {
+        let mut visitor = JumpGraphVisitor::default();
+        visitor.visit(file.tree.as_ref());
+        nodes.extend(visitor.nodes.into_iter().map(|node| (node, file)));
+    }
+    nodes
+}
+
+impl<'input> ParseTreeVisitorCompat<'input> for JumpGraphVisitor<'input> {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'input> YarnSpinnerParserVisitorCompat<'input> for JumpGraphVisitor<'input> {
+    fn visit_node(&mut self, ctx: &NodeContext<'input>) -> Self::Return {
+        let mut title_header = None;
+        let mut is_entry_point = false;
+        for header in ctx.header_all() {
+            let key = header.header_key.as_ref().unwrap().get_text();
+            let value = header
+                .header_value
+                .as_ref()
+                .map(|value| value.get_text())
+                .unwrap_or_default();
+            match key {
+                "title" => {
+                    title_header = Some(header.clone());
+                }
+                ENTRY_POINT_HEADER => {
+                    is_entry_point = value.trim() == "true";
+                }
+                _ => {}
+            }
+        }
+        // Nodes without a title are reported elsewhere
+        let Some(title_header) = title_header else {
+            return;
+        };
+        let name = title_header
+            .header_value
+            .as_ref()
+            .map(|value| value.get_text().to_owned())
+            .unwrap_or_default();
+        self.current_node = Some(JumpGraphNode {
+            is_entry_point: is_entry_point || name == DEFAULT_START_NODE,
+            name,
+            title_header,
+            destinations: Default::default(),
+            has_dynamic_jump: false,
+            immediate_destination: ctx
+                .body()
+                .and_then(|body| get_immediate_destination(&body.statement_all())),
+        });
+        if let Some(body) = ctx.body() {
+            self.visit(body.as_ref());
+        }
+        self.nodes.extend(self.current_node.take());
+    }
+
+    fn visit_jumpToNodeName(&mut self, ctx: &JumpToNodeNameContext<'input>) -> Self::Return {
+        if let Some(node) = self.current_node.as_mut() {
+            let destination = ctx.destination.as_ref().unwrap().get_text().to_owned();
+            node.destinations.push(destination);
+        }
+    }
+
+    fn visit_jumpToExpression(&mut self, _ctx: &JumpToExpressionContext<'input>) -> Self::Return {
+        if let Some(node) = self.current_node.as_mut() {
+            node.has_dynamic_jump = true;
+        }
+    }
+
+    fn visit_command_statement(&mut self, ctx: &Command_statementContext<'input>) -> Self::Return {
+        let (Some(node), Some(formatted_text)) =
+            (self.current_node.as_mut(), ctx.command_formatted_text())
+        else {
+            return;
+        };
+        let Some(destination) = get_detour_destination(&formatted_text.get_text()) else {
+            return;
+        };
+        let has_expression = formatted_text
+            .get_children()
+            .any(|child| child.get_child_count() > 0);
+        if has_expression {
+            node.has_dynamic_jump = true;
+        } else if !destination.is_empty() {
+            node.destinations.push(destination.to_owned());
+        }
+    }
+}
+
+/// Returns the destination of the first `<<jump>>` in the given statements
+/// if it is reached without running any lines, options, commands or conditional code before it.
+fn get_immediate_destination(statements: &[Rc<StatementContextAll<'_>>]) -> Option<String> {
+    for statement in statements {
+        if statement.set_statement().is_some()
+            || statement.declare_statement().is_some()
+            || statement.call_statement().is_some()
+        {
+            continue;
+        }
+        return match statement.jump_statement()?.as_ref() {
+            Jump_statementContextAll::JumpToNodeNameContext(ctx) => {
+                Some(ctx.destination.as_ref()?.get_text().to_owned())
+            }
+            _ => None,
+        };
+    }
+    None
+}
@@ -0,0 +1,221 @@
+use crate::parser::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// The attributes that the runtime handles on its own, so they are never unknown.
+const BUILT_IN_ATTRIBUTES: [&str; 5] = ["nomarkup", "select", "plural", "ordinal", "character"];
+
+/// A visitor that reports markup in lines that the runtime would not parse as intended.
+/// Markup that the runtime rejects, such as a `[/b]` without a matching `[b]`, is reported as an error,
+/// markup that it silently ignores, such as a `[b]` without a matching `[/b]`, as a warning.
+/// If the known attributes are given, attributes that are neither among them nor built in are warned about as well.
+pub(crate) struct MarkupVisitor {
+    file_name: String,
+    source_lines: Vec<Vec<char>>,
+    known_attributes: Option<HashSet<String>>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    _dummy: (),
+}
+
+impl MarkupVisitor {
+    pub(crate) fn new(
+        file_name: impl Into<String>,
+        source: &str,
+        known_attributes: Option<&[String]>,
+    ) -> Self {
+        Self {
+            file_name: file_name.into(),
+            source_lines: source.lines().map(|line| line.chars().collect()).collect(),
+            known_attributes: known_attributes.map(|names| {
+                names
+                    .iter()
+                    .cloned()
+                    .chain(BUILT_IN_ATTRIBUTES.map(str::to_owned))
+                    .collect()
+            }),
+            diagnostics: Default::default(),
+            _dummy: (),
+        }
+    }
+}
+
+impl<'input> ParseTreeVisitorCompat<'input> for MarkupVisitor {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'input> YarnSpinnerParserVisitorCompat<'input> for MarkupVisitor {
+    fn visit_line_statement(&mut self, ctx: &Line_statementContext<'input>) -> Self::Return {
+        let Some(text) = ctx.line_formatted_text() else {
+            return;
+        };
+        // Check the text as it was written, as the parse tree drops the whitespace inside of inline expressions
+        let range = text.range();
+        let Some(source_line) = self.source_lines.get(range.start.line) else {
+            return;
+        };
+        let end = if range.end.line == range.start.line {
+            range.end.character.min(source_line.len())
+        } else {
+            source_line.len()
+        };
+        let Some(text) = source_line.get(range.start.character..end) else {
+            return;
+        };
+        for (message, tag, severity, code) in
+            find_unbalanced_markup(text, self.known_attributes.as_ref())
+        {
+            let position = |character| Position {
+                line: range.start.line,
+                character: range.start.character + character,
+            };
+            self.diagnostics.push(
+                Diagnostic::from_message(message)
+                    .with_file_name(&self.file_name)
+                    .with_range(position(tag.start)..position(tag.end))
+                    .with_severity(severity)
+                    .with_code(code),
+            );
+        }
+    }
+}
+
+/// Returns a message, the range of the offending tag, the severity and the code for every markup tag in the line that is not closed,
+/// closes a tag that is not open, has no name, or is missing its `]`. Ranges are in characters.
+/// If `known_attributes` is given, tags opening an attribute that is not in it are returned as well.
+///
+/// Tags that are not closed or unknown are only warnings, as the runtime ignores them or passes them on as they are. For the other problems, the runtime fails to parse the line,
+/// e.g. with [`MarkupParseError::UnmatchedCloseMarker`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/markup/enum.MarkupParseError.html),
+/// so they are errors.
+///
+/// Follows the rules of the runtime's markup parser: self-closing tags like `[pause/]` need no closing tag,
+/// `[/]` closes all open tags, and everything between `[nomarkup]` and `[/nomarkup]` is not markup.
+/// Inline expressions such as `{$name}` and escaped characters such as `\[` are skipped.
+fn find_unbalanced_markup(
+    text: &[char],
+    known_attributes: Option<&HashSet<String>>,
+) -> Vec<(String, Range<usize>, DiagnosticSeverity, DiagnosticCode)> {
+    let mut problems = Vec::new();
+    let mut open_tags: Vec<(String, Range<usize>)> = Vec::new();
+    let mut is_in_nomarkup = false;
+    let mut index = 0;
+    while index < text.len() {
+        match text[index] {
+            '\\' => index += 2,
+            '{' => index = skip_expression(text, index),
+            '[' => {
+                let start = index;
+                let Some(end) = find_tag_end(text, start + 1) else {
+                    problems.push((
+                        "Markup tag is never finished with a ]".to_owned(),
+                        start..text.len(),
+                        DiagnosticSeverity::Error,
+                        DiagnosticCode::UnbalancedMarkup,
+                    ));
+                    break;
+                };
+                index = end + 1;
+                let tag = start..index;
+                let content: String = text[start + 1..end].iter().collect();
+                let content = content.trim();
+                if is_in_nomarkup {
+                    if content == "/nomarkup" {
+                        is_in_nomarkup = false;
+                        open_tags.retain(|(name, _)| name != "nomarkup");
+                    }
+                    continue;
+                }
+                if let Some(name) = content.strip_prefix('/') {
+                    let name = name.trim();
+                    if name.is_empty() {
+                        open_tags.clear();
+                    } else if let Some(position) =
+                        open_tags.iter().rposition(|(open, _)| open == name)
+                    {
+                        open_tags.remove(position);
+                    } else {
+                        problems.push((
+                            format!("Markup tag [/{name}] closes [{name}], which is not open"),
+                            tag,
+                            DiagnosticSeverity::Error,
+                            DiagnosticCode::UnbalancedMarkup,
+                        ));
+                    }
+                    continue;
+                }
+                let is_self_closing = content.ends_with('/');
+                let name: String = content
+                    .trim_end_matches('/')
+                    .chars()
+                    .take_while(|c| !c.is_whitespace() && *c != '=')
+                    .collect();
+                if name.is_empty() {
+                    if !is_self_closing {
+                        problems.push((
+                            "Markup tag has no name".to_owned(),
+                            tag,
+                            DiagnosticSeverity::Error,
+                            DiagnosticCode::UnbalancedMarkup,
+                        ));
+                    }
+                    continue;
+                }
+                if known_attributes.is_some_and(|known| !known.contains(&name)) {
+                    problems.push((
+                        format!(
+                            "Markup attribute [{name}] is not handled by the game or the runtime"
+                        ),
+                        tag.clone(),
+                        DiagnosticSeverity::Warning,
+                        DiagnosticCode::UnknownMarkupAttribute,
+                    ));
+                }
+                if is_self_closing {
+                    continue;
+                }
+                is_in_nomarkup = name == "nomarkup";
+                open_tags.push((name, tag));
+            }
+            _ => index += 1,
+        }
+    }
+    problems.extend(open_tags.into_iter().map(|(name, tag)| {
+        (
+            format!("Markup tag [{name}] is never closed"),
+            tag,
+            DiagnosticSeverity::Warning,
+            DiagnosticCode::UnbalancedMarkup,
+        )
+    }));
+    problems.sort_by_key(|(_, tag, _, _)| tag.start);
+    problems
+}
+
+/// Returns the index after the `}` that ends the expression starting at `start`.
+fn skip_expression(text: &[char], start: usize) -> usize {
+    text[start..]
+        .iter()
+        .position(|c| *c == '}')
+        .map_or(text.len(), |end| start + end + 1)
+}
+
+/// Returns the index of the `]` that ends the tag whose content starts at `start`, if any.
+fn find_tag_end(text: &[char], start: usize) -> Option<usize> {
+    let mut index = start;
+    while index < text.len() {
+        match text[index] {
+            ']' => return Some(index),
+            '{' => index = skip_expression(text, index),
+            '\\' => index += 2,
+            _ => index += 1,
+        }
+    }
+    None
+}
@@ -0,0 +1,64 @@
+use crate::prelude::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use antlr_rust::parser_rule_context::ParserRuleContext;
+use antlr_rust::token::Token;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+
+/// A visitor that collects the title and headers of every node in a file.
+pub(crate) struct NodeInfoVisitor {
+    pub(crate) node_infos: Vec<NodeInfo>,
+    file_name: String,
+    _dummy: (),
+}
+
+impl NodeInfoVisitor {
+    pub(crate) fn new(file_name: impl Into<String>) -> Self {
+        Self {
+            node_infos: Default::default(),
+            file_name: file_name.into(),
+            _dummy: (),
+        }
+    }
+}
+
+impl<'input> ParseTreeVisitorCompat<'input> for NodeInfoVisitor {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'input> YarnSpinnerParserVisitorCompat<'input> for NodeInfoVisitor {
+    fn visit_node(&mut self, ctx: &NodeContext<'input>) -> Self::Return {
+        let headers: Vec<_> = ctx
+            .header_all()
+            .iter()
+            .map(|header| Header {
+                key: header.header_key.as_ref().unwrap().get_text().to_owned(),
+                value: header
+                    .header_value
+                    .as_ref()
+                    .map(|value| value.get_text())
+                    .unwrap_or_default()
+                    .to_owned(),
+            })
+            .collect();
+        // Nodes without a title are reported elsewhere
+        let Some(title) = headers
+            .iter()
+            .find(|header| header.key == "title")
+            .map(|header| header.value.clone())
+        else {
+            return;
+        };
+        self.node_infos.push(NodeInfo {
+            title,
+            headers,
+            file_name: self.file_name.clone(),
+            line_number: ctx.start().line as usize,
+        });
+    }
+}
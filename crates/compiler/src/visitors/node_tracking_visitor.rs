@@ -9,6 +9,8 @@ use std::collections::HashSet;
 pub(crate) struct NodeTrackingVisitor {
     pub(crate) tracking_nodes: HashSet<String>,
     pub(crate) ignoring_nodes: HashSet<String>,
+    /// Whether `visited` or `visited_count` is called anywhere, even with a node name that is not a static string
+    pub(crate) calls_visit_functions: bool,
     _dummy: Option<String>,
 }
 
@@ -84,6 +86,7 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for NodeTrackingVisitor {
         if !["visited", "visited_count"].contains(&function_name.as_str()) {
             return None;
         }
+        self.calls_visit_functions = true;
         // we aren't bothering to test anything about the value itself
         // if it isn't a static string we'll get back null so can ignore it
         // if the func has more than one parameter later on it will cause an error so again can ignore
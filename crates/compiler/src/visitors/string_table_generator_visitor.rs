@@ -130,7 +130,7 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisi
         );
 
         if line_id.is_none() {
-            add_hashtag_child(ctx, string_id.0);
+            add_hashtag_child(ctx, string_id.as_str());
         }
     }
 }
@@ -256,6 +256,10 @@ a {1 + 3} cool expression
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile()
         .unwrap();
@@ -314,6 +318,10 @@ a {very} cool expression
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile();
 
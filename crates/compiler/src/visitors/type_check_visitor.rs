@@ -132,6 +132,12 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for TypeCheckVisitor<'input>
         None
     }
 
+    fn visit_declare_statement(&mut self, _ctx: &Declare_statementContext<'input>) -> Self::Return {
+        // The values of declarations are checked by the `DeclarationVisitor`,
+        // and the expressions of smart variables when they are compiled in `compile_smart_variables`.
+        None
+    }
+
     fn visit_expParens(&mut self, ctx: &ExpParensContext<'input>) -> Self::Return {
         // Parens expressions have the type of their inner expression
         let r#type = self.visit(ctx.expression().unwrap().as_ref());
@@ -321,19 +327,32 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for TypeCheckVisitor<'input>
         };
         // Check each parameter of the function
         let supplied_parameters = ctx.function_call().unwrap().expression_all();
+        let required_parameter_count = function_type.required_parameter_count();
         let expected_parameter_types = function_type.parameters;
 
-        if supplied_parameters.len() != expected_parameter_types.len() {
+        if supplied_parameters.len() < required_parameter_count
+            || supplied_parameters.len() > expected_parameter_types.len()
+        {
             // Wrong number of parameters supplied
             let parameters = if expected_parameter_types.len() == 1 {
                 "parameter"
             } else {
                 "parameters"
             };
+            let expected_parameter_count =
+                if required_parameter_count == expected_parameter_types.len() {
+                    expected_parameter_types.len().to_string()
+                } else {
+                    format!(
+                        "between {} and {}",
+                        required_parameter_count,
+                        expected_parameter_types.len()
+                    )
+                };
             let diagnostic = Diagnostic::from_message(format!(
                 "Function \"{}\" expects {} {}, but received {}",
                 function_name,
-                expected_parameter_types.len(),
+                expected_parameter_count,
                 parameters,
                 supplied_parameters.len()
             ))
@@ -452,6 +471,28 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for TypeCheckVisitor<'input>
         }
         let mut expression_type = self.visit(expression_context.as_ref());
         let variable_name = variable_context.get_text();
+        if self
+            .declarations()
+            .any(|decl| decl.is_constant && decl.name == variable_name)
+        {
+            let diagnostic = Diagnostic::from_message(format!(
+                "{variable_name} is a constant and cannot be assigned a new value"
+            ))
+            .with_file_name(&self.file.name)
+            .with_parser_context(ctx, self.file.tokens());
+            self.diagnostics.push(diagnostic);
+        }
+        if self
+            .declarations()
+            .any(|decl| decl.smart_expression.is_some() && decl.name == variable_name)
+        {
+            let diagnostic = Diagnostic::from_message(format!(
+                "{variable_name} is a smart variable and cannot be assigned a new value"
+            ))
+            .with_file_name(&self.file.name)
+            .with_parser_context(ctx, self.file.tokens());
+            self.diagnostics.push(diagnostic);
+        }
         let terms: &[Term] = &[
             variable_context.clone().into(),
             expression_context.clone().into(),
@@ -633,6 +674,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile()
         .unwrap();
@@ -658,6 +703,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile();
 
@@ -745,6 +794,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile()
         .unwrap();
@@ -768,6 +821,10 @@ mod tests {
             library: Default::default(),
             compilation_type: CompilationType::FullCompilation,
             variable_declarations: vec![],
+            detect_jump_cycles: false,
+            treat_warnings_as_errors: false,
+            skip_unused_visit_tracking: false,
+            markup_attributes: None,
         }
         .compile();
 
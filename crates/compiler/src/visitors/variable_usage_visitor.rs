@@ -0,0 +1,32 @@
+use crate::prelude::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat};
+use std::collections::HashSet;
+
+/// A visitor that collects the names of all variables that are read or written in a parse tree.
+/// Declaring a variable does not count as using it.
+#[derive(Clone, Default)]
+pub(crate) struct VariableUsageVisitor {
+    pub(crate) used_variables: HashSet<String>,
+    _dummy: (),
+}
+
+impl<'input> ParseTreeVisitorCompat<'input> for VariableUsageVisitor {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'input> YarnSpinnerParserVisitorCompat<'input> for VariableUsageVisitor {
+    fn visit_declare_statement(&mut self, _ctx: &Declare_statementContext<'input>) -> Self::Return {
+        // The default value of a declaration is a constant, and the variables read by the expression
+        // of a smart variable are collected from its compiled instructions instead.
+    }
+
+    fn visit_variable(&mut self, ctx: &VariableContext<'input>) -> Self::Return {
+        self.used_variables.insert(ctx.get_text());
+    }
+}
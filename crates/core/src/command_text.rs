@@ -0,0 +1,175 @@
+//! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/5944b0e03d319303cd185b08140772a5804a2762/Runtime/DialogueRunner.cs#L1169>
+//!
+//! ## Implementation notes
+//! Shared by the compiler, which lists the commands of a project, and the runtime, which parses the commands it runs,
+//! so that both split commands the same way.
+
+use crate::prelude::*;
+
+/// A single whitespace-separated part of a command, as returned by [`split_command_text`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandComponent {
+    /// The text of the component, without surrounding quotes and with escape sequences resolved.
+    pub text: String,
+    /// Whether the component was surrounded by double quotes.
+    pub is_quoted: bool,
+}
+
+impl CommandComponent {
+    fn unquoted(text: String) -> Self {
+        Self {
+            text,
+            is_quoted: false,
+        }
+    }
+
+    fn quoted(text: String) -> Self {
+        Self {
+            text,
+            is_quoted: true,
+        }
+    }
+
+    /// Infers the type of a command parameter from its text. Quoted text is always a string.
+    pub fn into_parameter(self) -> YarnValue {
+        if self.is_quoted {
+            return self.text.into();
+        }
+        match self.text.as_str() {
+            "true" => true.into(),
+            "false" => false.into(),
+            text => match text.parse::<f32>() {
+                // Don't treat words like "inf" or "NaN" as numbers
+                Ok(number) if number.is_finite() => number.into(),
+                _ => self.text.into(),
+            },
+        }
+    }
+}
+
+/// Splits input into a number of non-empty sub-strings, separated
+/// by whitespace, and grouping double-quoted strings into a single
+/// sub-string.
+///
+/// This method behaves similarly to the [`String::split`] method with
+/// the empty results filtered out, with the following differences:
+///
+/// - Text that appears inside a pair of double-quote characters will not be split.
+/// - Text that appears after a double-quote character and
+///   before the end of the input will not be split (that is, an
+///   unterminated double-quoted string will be treated as though it
+///   had been terminated at the end of the input.)
+/// - When inside a pair of double-quote characters, the string
+///   `\\` will be converted to `\`, and the string `\"` will be converted to `"`.
+pub fn split_command_text(input: &str) -> Vec<CommandComponent> {
+    let mut chars = input.chars().peekable();
+    let mut results = Vec::new();
+    let mut current_component = String::new();
+    while let Some(mut char) = chars.next() {
+        match char {
+            _ if char.is_whitespace() => {
+                if !current_component.is_empty() {
+                    // We've reached the end of a run of visible
+                    // characters. Add this run to the result list and
+                    // prepare for the next one.
+                    results.push(CommandComponent::unquoted(std::mem::take(
+                        &mut current_component,
+                    )));
+                } else {
+                    // We encountered a whitespace character, but
+                    // didn't have any characters queued up. Skip this
+                    // character.
+                }
+            }
+            '\"' => {
+                // We've entered a quoted string!
+                loop {
+                    char = match chars.next() {
+                        Some(c) => c,
+                        None => {
+                            // Oops, we ended the input while parsing a
+                            // quoted string! Dump our current word
+                            // immediately and return.
+                            results.push(CommandComponent::quoted(current_component));
+                            return results;
+                        }
+                    };
+                    match char {
+                        '\\' => {
+                            // Possibly an escaped character!
+                            match chars.peek() {
+                                Some('\\') | Some('\"') => {
+                                    // It's an escaped character! Consume it and add it to the current component.
+                                    let next = chars.next().unwrap();
+                                    current_component.push(next);
+                                }
+                                _ => {
+                                    // Oops, an invalid escape. Add the \ and
+                                    // whatever is after it.
+                                    current_component.push(char);
+                                }
+                            }
+                        }
+                        '\"' => {
+                            // The end of a string!
+                            break;
+                        }
+                        _ => {
+                            // Any other character. Add it to the buffer.
+                            current_component.push(char);
+                        }
+                    }
+                }
+                results.push(CommandComponent::quoted(std::mem::take(
+                    &mut current_component,
+                )));
+            }
+            _ => {
+                current_component.push(char);
+            }
+        }
+    }
+    if !current_component.is_empty() {
+        results.push(CommandComponent::unquoted(current_component));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/5944b0e03d319303cd185b08140772a5804a2762/Tests/Runtime/DialogueRunnerTests/DialogueRunnerTests.cs#L465>
+    use super::*;
+
+    #[test]
+    fn split_command_text_splits_text_correctly() {
+        for (input, expected_components) in [
+            ("one two three four", vec!["one", "two", "three", "four"]),
+            ("one \"two three\" four", vec!["one", "two three", "four"]),
+            ("one \"two three four", vec!["one", "two three four"]),
+            (
+                "one \"two \\\"three\" four",
+                vec!["one", "two \"three", "four"],
+            ),
+            (
+                "one \\two three four",
+                vec!["one", "\\two", "three", "four"],
+            ),
+            (
+                "one \"two \\\\ three\" four",
+                vec!["one", "two \\ three", "four"],
+            ),
+            (
+                "one \"two \\1 three\" four",
+                vec!["one", "two \\1 three", "four"],
+            ),
+            ("one      two", vec!["one", "two"]),
+        ] {
+            let parsed_components: Vec<_> = split_command_text(input)
+                .into_iter()
+                .map(|component| component.text)
+                .collect();
+
+            assert_eq!(expected_components, parsed_components);
+        }
+    }
+}
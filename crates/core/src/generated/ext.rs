@@ -1,6 +1,7 @@
 //! Contains extensions to generated types that in the original implementation are sprinkled around the repo via partial classes
 
 use crate::prelude::*;
+use prost::Message;
 use std::fmt::{Debug, Display};
 use thiserror::Error;
 
@@ -115,23 +116,167 @@ impl Program {
     ///
     /// The new program will contain every node from every input program.
     /// Returns [`None`] if the input is empty.
-    pub fn combine(programs: Vec<Program>) -> Option<Self> {
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProgramCombineError::DuplicateNodeNames`] listing every node name that appears in more than one of the programs.
+    pub fn combine(programs: Vec<Program>) -> Result<Option<Self>, ProgramCombineError> {
         if programs.is_empty() {
-            return None;
+            return Ok(None);
         }
         let mut output = Program::default();
+        let mut duplicate_node_names = Vec::new();
         for program in programs {
             for (node_name, node) in program.nodes {
-                assert!(
-                    !output.nodes.contains_key(&node_name),
-                    "This program already contains a node named {node_name}",
-                );
-                output.nodes.insert(node_name, node);
+                if output.nodes.contains_key(&node_name) {
+                    duplicate_node_names.push(node_name);
+                } else {
+                    output.nodes.insert(node_name, node);
+                }
             }
             output.initial_values.extend(program.initial_values);
+            output.smart_variables.extend(program.smart_variables);
+        }
+        if !duplicate_node_names.is_empty() {
+            duplicate_node_names.sort();
+            duplicate_node_names.dedup();
+            return Err(ProgramCombineError::DuplicateNodeNames {
+                node_names: duplicate_node_names,
+            });
         }
-        Some(output)
+        Ok(Some(output))
     }
+
+    /// Lists the instructions of every node in a human-readable, assembly-like format for debugging codegen.
+    ///
+    /// Nodes are sorted by name. Every instruction is printed with its index, opcode and operands.
+    /// Labels are printed on their own line before the instruction they point to,
+    /// and jumps to a label additionally note the index of their target, e.g.
+    ///
+    /// ```text
+    /// Start:
+    ///   L0_option_0:
+    ///     0  RUN_LINE "line:1" 0
+    ///     1  JUMP_TO "L1_end" ; -> 2
+    ///   L1_end:
+    ///     2  STOP
+    /// ```
+    pub fn disassemble(&self) -> String {
+        let mut nodes: Vec<_> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut output = String::new();
+        for node in nodes {
+            output.push_str(&format!("{}:\n", node.name));
+            let mut labels: Vec<_> = node.labels.iter().collect();
+            labels.sort_by_key(|(name, index)| (**index, name.as_str()));
+            for (index, instruction) in node.instructions.iter().enumerate() {
+                for (label, _) in labels.iter().filter(|(_, i)| **i as usize == index) {
+                    output.push_str(&format!("  {label}:\n"));
+                }
+                let opcode = OpCode::try_from(instruction.opcode)
+                    .map(|opcode| opcode.as_str_name().to_owned())
+                    .unwrap_or_else(|_| format!("<invalid opcode {}>", instruction.opcode));
+                output.push_str(&format!("    {index:<3}{opcode}"));
+                for operand in &instruction.operands {
+                    match &operand.value {
+                        Some(OperandValue::StringValue(s)) => output.push_str(&format!(" {s:?}")),
+                        Some(OperandValue::FloatValue(f)) => output.push_str(&format!(" {f}")),
+                        Some(OperandValue::BoolValue(b)) => output.push_str(&format!(" {b}")),
+                        None => output.push_str(" <empty>"),
+                    }
+                }
+                if instruction.opcode == OpCode::JumpTo as i32 {
+                    let target =
+                        instruction
+                            .operands
+                            .first()
+                            .and_then(|operand| match &operand.value {
+                                Some(OperandValue::StringValue(label)) => node.labels.get(label),
+                                _ => None,
+                            });
+                    if let Some(target) = target {
+                        output.push_str(&format!(" ; -> {target}"));
+                    }
+                }
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// The version of the binary format produced by [`Program::to_bytes`].
+    /// Bumped whenever the format changes in a way that older programs can no longer be read.
+    pub const BINARY_FORMAT_VERSION: u32 = 2;
+
+    /// The bytes every program encoded by [`Program::to_bytes`] starts with.
+    const BINARY_FORMAT_MAGIC: [u8; 4] = *b"YSPG";
+
+    /// Encodes this program into a versioned binary format,
+    /// so that it can be written to disk and loaded again later via [`Program::from_bytes`] without recompiling it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.encoded_len());
+        bytes.extend_from_slice(&Self::BINARY_FORMAT_MAGIC);
+        bytes.extend_from_slice(&Self::BINARY_FORMAT_VERSION.to_le_bytes());
+        self.encode(&mut bytes)
+            .expect("Encoding into a Vec cannot run out of space");
+        bytes
+    }
+
+    /// Decodes a program previously encoded by [`Program::to_bytes`].
+    ///
+    /// Fails if the bytes were not produced by [`Program::to_bytes`] or were produced with a different [`Program::BINARY_FORMAT_VERSION`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramDecodeError> {
+        let (magic, bytes) =
+            split_first_chunk::<4>(bytes).ok_or(ProgramDecodeError::NotAProgram)?;
+        if magic != Self::BINARY_FORMAT_MAGIC {
+            return Err(ProgramDecodeError::NotAProgram);
+        }
+        let (version, bytes) =
+            split_first_chunk::<4>(bytes).ok_or(ProgramDecodeError::NotAProgram)?;
+        let version = u32::from_le_bytes(version);
+        if version != Self::BINARY_FORMAT_VERSION {
+            return Err(ProgramDecodeError::UnsupportedVersion {
+                found: version,
+                expected: Self::BINARY_FORMAT_VERSION,
+            });
+        }
+        Ok(Self::decode(bytes)?)
+    }
+}
+
+fn split_first_chunk<const N: usize>(bytes: &[u8]) -> Option<([u8; N], &[u8])> {
+    let chunk = bytes.get(..N)?.try_into().ok()?;
+    Some((chunk, &bytes[N..]))
+}
+
+/// Represents a failure to decode a [`Program`] with [`Program::from_bytes`].
+#[derive(Error, Debug)]
+pub enum ProgramDecodeError {
+    /// The bytes were not produced by [`Program::to_bytes`].
+    #[error("The given bytes do not contain a compiled Yarn program")]
+    NotAProgram,
+    /// The program was encoded with a different version of the binary format.
+    #[error("The program was encoded with binary format version {found}, but only version {expected} is supported. Please recompile it.")]
+    UnsupportedVersion {
+        /// The version the program was encoded with.
+        found: u32,
+        /// The version this crate is able to read, i.e. [`Program::BINARY_FORMAT_VERSION`].
+        expected: u32,
+    },
+    /// The program content itself is malformed.
+    #[error(transparent)]
+    InvalidContent(#[from] prost::DecodeError),
+}
+
+/// Represents a failure to merge programs with [`Program::combine`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProgramCombineError {
+    /// More than one of the programs contains a node with the same name.
+    #[error("Cannot combine programs that contain nodes with the same name: {}", .node_names.join(", "))]
+    DuplicateNodeNames {
+        /// The sorted names of the nodes that are contained in more than one program.
+        node_names: Vec<String>,
+    },
 }
 
 impl Instruction {
@@ -146,3 +291,90 @@ impl Instruction {
             .unwrap_or_else(|e| panic!("Failed to convert operand {index}: {e:?}",))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instruction(opcode: OpCode, operands: Vec<Operand>) -> Instruction {
+        Instruction {
+            opcode: opcode as i32,
+            operands,
+        }
+    }
+
+    #[test]
+    fn disassembles_trivial_node() {
+        let node = Node {
+            name: "Start".to_owned(),
+            instructions: vec![
+                instruction(
+                    OpCode::RunLine,
+                    vec!["line:1".to_owned().into(), 0_usize.into()],
+                ),
+                instruction(OpCode::JumpTo, vec!["L0_end".to_owned().into()]),
+                instruction(OpCode::Stop, vec![]),
+            ],
+            labels: [("L0_end".to_owned(), 2)].into_iter().collect(),
+            ..Default::default()
+        };
+        let program = Program {
+            nodes: [("Start".to_owned(), node)].into_iter().collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            program.disassemble(),
+            "Start:
+    0  RUN_LINE \"line:1\" 0
+    1  JUMP_TO \"L0_end\" ; -> 2
+  L0_end:
+    2  STOP
+"
+        );
+    }
+
+    fn program_with_nodes(node_names: &[&str]) -> Program {
+        Program {
+            nodes: node_names
+                .iter()
+                .map(|&name| {
+                    let node = Node {
+                        name: name.to_owned(),
+                        ..Default::default()
+                    };
+                    (name.to_owned(), node)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn combines_programs_with_distinct_nodes() {
+        let combined = Program::combine(vec![
+            program_with_nodes(&["Start"]),
+            program_with_nodes(&["Epilogue"]),
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(combined.nodes.len(), 2);
+    }
+
+    #[test]
+    fn reports_duplicate_node_names_when_combining() {
+        let result = Program::combine(vec![
+            program_with_nodes(&["Start", "Shop", "Dlc.Intro"]),
+            program_with_nodes(&["Dlc.Intro", "Shop"]),
+            program_with_nodes(&["Shop"]),
+        ]);
+
+        assert_eq!(
+            result.unwrap_err(),
+            ProgramCombineError::DuplicateNodeNames {
+                node_names: vec!["Dlc.Intro".to_owned(), "Shop".to_owned()],
+            }
+        );
+    }
+}
@@ -21,6 +21,15 @@ pub struct Program {
         ::prost::alloc::string::String,
         Operand,
     >,
+    /// Not part of the original.
+    /// The collection of smart variables, i.e. read-only variables whose value
+    /// is computed every time they are read. Each node is named after its
+    /// variable and leaves the variable's value on the stack when run.
+    #[prost(map = "string, message", tag = "4")]
+    pub smart_variables: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        Node,
+    >,
 }
 /// A collection of instructions
 use crate::prelude::*;
@@ -152,7 +161,7 @@ pub mod instruction {
         Pop = 11,
         /// Calls a function in the client. Pops as many arguments as the
         /// client indicates the function receives, and the result (if any)
-        /// is pushed to the stack.		
+        /// is pushed to the stack.
         /// opA = string: name of the function
         CallFunc = 12,
         /// Pushes the contents of a variable onto the stack.
@@ -163,12 +172,25 @@ pub mod instruction {
         /// opA = name of variable
         StoreVariable = 14,
         /// Stops execution of the program.
+        /// Not part of the original: this includes all nodes waiting for a
+        /// DETOUR_TO_NODE to return.
         /// No operands.
         Stop = 15,
         /// Pops a string off the top of the stack, and runs the node with
         /// that name.
         /// No operands.
         RunNode = 16,
+        /// Not part of the original.
+        /// Pops a string off the top of the stack, and runs the node with
+        /// that name. Execution continues after this instruction once that
+        /// node reaches a RETURN instruction or its end.
+        /// No operands.
+        DetourToNode = 17,
+        /// Not part of the original.
+        /// Returns to the instruction after the most recent DETOUR_TO_NODE, or
+        /// stops execution of the program if there is none.
+        /// No operands.
+        Return = 18,
     }
     impl OpCode {
         /// String value of the enum field names used in the ProtoBuf definition.
@@ -194,6 +216,8 @@ pub mod instruction {
                 OpCode::StoreVariable => "STORE_VARIABLE",
                 OpCode::Stop => "STOP",
                 OpCode::RunNode => "RUN_NODE",
+                OpCode::DetourToNode => "DETOUR_TO_NODE",
+                OpCode::Return => "RETURN",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
@@ -216,6 +240,8 @@ pub mod instruction {
                 "STORE_VARIABLE" => Some(Self::StoreVariable),
                 "STOP" => Some(Self::Stop),
                 "RUN_NODE" => Some(Self::RunNode),
+                "DETOUR_TO_NODE" => Some(Self::DetourToNode),
+                "RETURN" => Some(Self::Return),
                 _ => None,
             }
         }
@@ -6,11 +6,13 @@
 //! - If you wish to write an adapter crate for an engine yourself, use the [`yarnspinner`](https://crates.io/crates/yarnspinner) crate.
 
 #![warn(missing_docs, missing_debug_implementations)]
+mod command_text;
 mod feature_gates;
 mod generated;
 mod internal_value;
 mod library;
 mod line_id;
+mod markup_rules;
 mod operator;
 mod position;
 pub mod types;
@@ -20,14 +22,16 @@ mod yarn_value;
 pub mod prelude {
     //! Types and functions used all throughout the runtime and compiler.
     pub use crate::{
+        command_text::*,
         feature_gates::*,
         generated::{
             instruction::OpCode, operand::Value as OperandValue, Header, Instruction,
-            InvalidOpCodeError, Node, Operand, Program,
+            InvalidOpCodeError, Node, Operand, Program, ProgramCombineError, ProgramDecodeError,
         },
         internal_value::*,
         library::*,
         line_id::*,
+        markup_rules::*,
         operator::*,
         position::*,
         types::Type,
@@ -64,17 +64,33 @@ impl Library {
         format!("$Yarn.Internal.Visiting.{node_name}")
     }
 
+    /// Generates the name of the variable that remembers whether a `<<once>>` block of a node has already run.
+    /// `index` counts the `<<once>>` blocks of the node in the order they appear in, starting at 0,
+    /// so that editing the lines around a block does not reset it in existing save games.
+    pub fn generate_unique_once_variable(node_name: &str, index: usize) -> String {
+        format!("$Yarn.Internal.Once.{node_name}.{index}")
+    }
+
     /// Creates a [`Library`] with the standard functions that are included in Yarn Spinner.
     /// These are:
     /// - `string`: Converts a value to a string.
     /// - `number`: Converts a value to a number.
     /// - `bool`: Converts a value to a boolean.
+    /// - `assert`: Panics if the condition is false. An optional second argument is used as the panic message,
+    ///   e.g. `assert($gold >= 0, "gold must never be negative")`.
     /// - Comparison operators for numbers, strings, and booleans. (`==`, `!=`, `<`, `<=`, `>`, `>=`)
     pub fn standard_library() -> Self {
         let mut library = yarn_library!(
             "string" => <String as From<YarnValue >>::from,
             "number" => |value: YarnValue| f32::try_from(value).expect("Failed to convert a Yarn value to a number"),
             "bool" => |value: YarnValue| bool::try_from(value).expect("Failed to convert a Yarn value to a bool"),
+            "assert" => |condition: bool, message: Option<String>| {
+                match message {
+                    Some(message) => assert!(condition, "{message}"),
+                    None => assert!(condition, "Assertion failed"),
+                }
+                true
+            },
         );
         for r#type in [Type::Number, Type::String, Type::Boolean] {
             library.add_methods(r#type);
@@ -122,6 +138,32 @@ impl Library {
         self
     }
 
+    /// Adds many functions at once, e.g. ones collected programmatically. Functions with different signatures
+    /// can be put into the same collection by boxing them with [`boxed_yarn_fn`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// # use yarnspinner_core::prelude::*;
+    /// let mut library = Library::new();
+    /// library.add_functions([
+    ///     ("double", boxed_yarn_fn(|x: f32| x * 2.0)),
+    ///     ("shout", boxed_yarn_fn(|s: String| s.to_uppercase())),
+    /// ]);
+    /// ```
+    pub fn add_functions<N>(
+        &mut self,
+        functions: impl IntoIterator<Item = (N, Box<dyn UntypedYarnFn>)>,
+    ) -> &mut Self
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        for (name, function) in functions {
+            self.0.add_boxed(name, function);
+        }
+        self
+    }
+
     /// Returns `true` if the library contains a function with the given name.
     pub fn contains_function(&self, name: &str) -> bool {
         self.0.contains_function(name)
@@ -188,3 +230,31 @@ macro_rules! yarn_library {
     };
 }
 pub use yarn_library;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_many_functions_at_once() {
+        let functions = vec![
+            ("add_one".to_owned(), boxed_yarn_fn(|x: f32| x + 1.0)),
+            ("is_empty".to_owned(), boxed_yarn_fn(|s: &str| s.is_empty())),
+            (
+                "greet".to_owned(),
+                boxed_yarn_fn(|name: String| format!("Hello, {name}")),
+            ),
+        ];
+        let mut library = Library::new();
+        library.add_functions(functions);
+
+        let call =
+            |name: &str, argument: YarnValue| library.get(name).unwrap().call(vec![argument]);
+        assert_eq!(call("add_one", 1.0.into()), YarnValue::Number(2.0));
+        assert_eq!(call("is_empty", "".into()), YarnValue::Boolean(true));
+        assert_eq!(
+            call("greet", "Alice".into()),
+            YarnValue::String("Hello, Alice".to_owned())
+        );
+    }
+}
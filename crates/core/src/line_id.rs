@@ -1,28 +1,104 @@
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
 
 /// The unique ID of a line in a Yarn script. In a Yarn script, line IDs look like this:
 /// ```text
 /// Darth Vader: I am your father! #line:123
 /// Luke: Noooooo #line:nooooo
 /// ```
+///
+/// Line IDs are interned: creating two [`LineId`]s from the same text yields two handles to the same allocation,
+/// so cloning and comparing them is cheap. A text is released once no [`LineId`] refers to it anymore.
+///
+/// Create one with [`LineId::from`] and read its text with [`LineId::as_str`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(feature = "bevy", reflect_value(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
-    reflect(Serialize, Deserialize)
+    reflect_value(Serialize, Deserialize)
 )]
-pub struct LineId(pub String);
+pub struct LineId(Arc<str>);
+
+impl LineId {
+    /// Returns the text of this line ID, e.g. `"line:123"`.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
 
 impl<T> From<T> for LineId
 where
     String: From<T>,
 {
     fn from(s: T) -> Self {
-        Self(s.into())
+        Self(intern(&String::from(s)))
+    }
+}
+
+/// The texts of all line IDs currently in use. Only the lookup when creating a [`LineId`] takes the lock,
+/// cloning and dropping one does not.
+#[derive(Default)]
+struct Interner {
+    texts: HashSet<Arc<str>>,
+    /// The number of texts at which the ones no [`LineId`] refers to anymore are dropped.
+    prune_at: usize,
+}
+
+impl Interner {
+    /// Pruning only when the set has grown to twice its size after the last pruning keeps interning amortized O(1).
+    const MIN_PRUNE_AT: usize = 1024;
+
+    fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(existing) = self.texts.get(text) {
+            return existing.clone();
+        }
+        if self.texts.len() >= self.prune_at {
+            // The set itself holds one reference, so a count of 1 means no `LineId` is left
+            self.texts.retain(|text| Arc::strong_count(text) > 1);
+            self.prune_at = (self.texts.len() * 2).max(Self::MIN_PRUNE_AT);
+        }
+        let text: Arc<str> = Arc::from(text);
+        self.texts.insert(text.clone());
+        text
+    }
+}
+
+/// The number of independently locked parts of the interner.
+/// Texts are spread over them by their hash, so threads creating different line IDs rarely wait for each other.
+const SHARD_COUNT: usize = 16;
+
+fn shard(text: &str) -> MutexGuard<'static, Interner> {
+    static SHARDS: OnceLock<[Mutex<Interner>; SHARD_COUNT]> = OnceLock::new();
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    let index = (hasher.finish() % SHARD_COUNT as u64) as usize;
+    SHARDS.get_or_init(Default::default)[index]
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+fn intern(text: &str) -> Arc<str> {
+    shard(text).intern(text)
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LineId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LineId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
     }
 }
 
@@ -37,3 +113,43 @@ impl Display for LineId {
         self.0.fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_storage_between_equal_ids() {
+        let from_str = LineId::from("line:interned");
+        let from_string = LineId::from(format!("line:{}", "interned"));
+
+        assert_eq!(from_str, from_string);
+        assert!(Arc::ptr_eq(&from_str.0, &from_string.0));
+        assert!(!Arc::ptr_eq(&from_str.0, &LineId::from("line:other").0));
+    }
+
+    #[test]
+    fn shares_storage_between_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| LineId::from("line:shared")))
+            .collect();
+        let ids: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert!(ids.iter().all(|id| Arc::ptr_eq(&id.0, &ids[0].0)));
+    }
+
+    #[test]
+    fn releases_ids_that_are_no_longer_used() {
+        let mut interner = Interner::default();
+        let kept = interner.intern("line:kept");
+        for index in 0..10 * Interner::MIN_PRUNE_AT {
+            interner.intern(&format!("line:dropped_{index}"));
+        }
+
+        assert!(interner.texts.len() <= 2 * Interner::MIN_PRUNE_AT);
+        assert!(Arc::ptr_eq(&kept, &interner.intern("line:kept")));
+    }
+}
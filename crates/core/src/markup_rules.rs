@@ -0,0 +1,38 @@
+//! ## Implementation notes
+//! Shared by the runtime, whose markup parser applies these rules, and the compiler, which needs them to tell which text of a line
+//! is shown and who speaks it without parsing the line at runtime.
+
+/// The characters that a backslash escapes in markup, e.g. `\[` for a literal `[`.
+/// A backslash followed by any other character is kept as it is.
+pub const MARKUP_ESCAPABLE_CHARACTERS: [char; 9] = ['[', ']', '{', '}', '\\', '#', '<', '>', '/'];
+
+/// Splits the text of a line, after its markup was parsed, into the name of the speaking character and the text that is spoken,
+/// e.g. `Alice` and `Hello!` for `Alice: Hello!`. Returns [`None`] if the text has no `:`.
+///
+/// Everything before the first `:` is the name as written, and the whitespace after the colon belongs to neither part.
+/// This is how the runtime's markup parser finds the `character` attribute of lines that do not set one explicitly,
+/// so `Alice:Hi` is spoken by `Alice`, but `It is 10:30` is also spoken by `It is 10`.
+pub fn split_character_name(text: &str) -> Option<(&str, &str)> {
+    let (character_name, spoken_text) = text.split_once(':')?;
+    Some((character_name, spoken_text.trim_start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_at_first_colon() {
+        assert_eq!(
+            split_character_name("Alice: Hello!"),
+            Some(("Alice", "Hello!"))
+        );
+        assert_eq!(split_character_name("Alice:Hi"), Some(("Alice", "Hi")));
+        assert_eq!(split_character_name("Alice :  x"), Some(("Alice ", "x")));
+        assert_eq!(
+            split_character_name("It is 10:30 now."),
+            Some(("It is 10", "30 now."))
+        );
+        assert_eq!(split_character_name("Hello"), None);
+    }
+}
@@ -16,5 +16,6 @@ pub struct Position {
 
     /// The zero-indexed character number of this position.
     /// Careful: This represents a unicode code point, not a byte, i.e. what you'd get with `string.chars().nth(character)`.
+    /// All positions reported by the compiler use this unit. Note that it differs from the UTF-16 code units used by the Language Server Protocol.
     pub character: usize,
 }
@@ -30,6 +30,10 @@ pub struct FunctionType {
     /// (also known as the function's *arity*).
     pub parameters: Vec<Option<Type>>,
 
+    /// How many of the trailing [`FunctionType::parameters`] may be omitted by the caller.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub optional_parameter_count: usize,
+
     #[cfg_attr(feature = "bevy", reflect(ignore))]
     ///The type of value that this function returns.
     // Needs to be on the heap because of type recursion
@@ -49,6 +53,11 @@ impl FunctionType {
         self
     }
 
+    /// The number of parameters a caller has to supply at minimum.
+    pub fn required_parameter_count(&self) -> usize {
+        self.parameters.len() - self.optional_parameter_count
+    }
+
     /// Adds a parameter type to this function signature
     pub fn add_parameter(&mut self, parameter: impl Into<Option<Type>>) -> &mut Self {
         self.parameters.push(parameter.into());
@@ -58,10 +67,19 @@ impl FunctionType {
 
 impl Display for FunctionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let required_parameter_count = self.required_parameter_count();
         let parameters = self
             .parameters
             .iter()
-            .map(TypeFormat::format)
+            .enumerate()
+            .map(|(i, parameter)| {
+                let parameter = parameter.format();
+                if i < required_parameter_count {
+                    parameter
+                } else {
+                    format!("{parameter}?")
+                }
+            })
             .collect::<Vec<_>>()
             .join(", ");
         let return_type = self.return_type.as_ref().format();
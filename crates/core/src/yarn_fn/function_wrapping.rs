@@ -13,6 +13,7 @@ use yarnspinner_macros::all_tuples;
 ///   - [`String`] (for a reference, [`&str`] may be used instead of `&String`)
 ///   - [`YarnValue`], which means that a parameter may be any of the above types
 ///   - Tuples of the above types.
+///   - An [`Option`] of the above types, which makes the parameter optional. Optional parameters must come last.
 /// - It must return a value.
 /// - Its return type must be one of the following types:
 ///   - [`bool`]
@@ -39,6 +40,11 @@ pub trait YarnFn<Marker>: Clone + Send + Sync {
     fn call(&self, input: Vec<YarnValue>) -> Self::Out;
     /// The [`TypeId`]s of the parameters of this function.
     fn parameter_types(&self) -> Vec<TypeId>;
+    /// The number of leading parameters that must always be passed.
+    /// Parameters after these are optional and may be omitted by the caller.
+    fn required_parameter_count(&self) -> usize {
+        self.parameter_types().len()
+    }
     /// The [`TypeId`] of the return type of this function.
     fn return_type(&self) -> TypeId {
         TypeId::of::<Self::Out>()
@@ -54,10 +60,24 @@ pub trait UntypedYarnFn: Debug + Display + Send + Sync {
     fn clone_box(&self) -> Box<dyn UntypedYarnFn>;
     /// The [`TypeId`]s of the parameters of this function.
     fn parameter_types(&self) -> Vec<TypeId>;
+    /// The number of leading parameters that must always be passed.
+    /// Parameters after these are optional and may be omitted by the caller.
+    fn required_parameter_count(&self) -> usize;
     /// The [`TypeId`] of the return type of this function.
     fn return_type(&self) -> TypeId;
 }
 
+/// Type-erases a [`YarnFn`] so that functions with different signatures can be collected together,
+/// e.g. to register them all at once with [`Library::add_functions`].
+pub fn boxed_yarn_fn<Marker, F>(function: F) -> Box<dyn UntypedYarnFn>
+where
+    Marker: 'static,
+    F: YarnFn<Marker> + 'static + Clone,
+    F::Out: IntoYarnValueFromNonYarnValue + 'static + Clone,
+{
+    Box::new(YarnFnWrapper::from(function))
+}
+
 impl Clone for Box<dyn UntypedYarnFn> {
     fn clone(&self) -> Self {
         self.clone_box()
@@ -83,6 +103,10 @@ where
         self.function.parameter_types()
     }
 
+    fn required_parameter_count(&self) -> usize {
+        self.function.required_parameter_count()
+    }
+
     fn return_type(&self) -> TypeId {
         self.function.return_type()
     }
@@ -199,7 +223,11 @@ macro_rules! impl_yarn_fn_tuple {
                 }
 
                 fn parameter_types(&self) -> Vec<TypeId> {
-                    vec![$(TypeId::of::<$param>()),*]
+                    vec![$($param::parameter_type_id()),*]
+                }
+
+                fn required_parameter_count(&self) -> usize {
+                    0 $(+ usize::from(!$param::IS_OPTIONAL))*
                 }
             }
     };
@@ -338,6 +366,27 @@ mod tests {
         accept_yarn_fn(f);
     }
 
+    #[test]
+    fn fills_missing_optional_params_with_none() {
+        fn f(a: usize, b: Option<usize>) -> usize {
+            a + b.unwrap_or(10)
+        }
+        assert_eq!(apply_yarn_fn(f, vec![1.into()]), 11);
+        assert_eq!(apply_yarn_fn(f, vec![1.into(), 2.into()]), 3);
+    }
+
+    #[test]
+    fn reports_optional_params_by_their_inner_type() {
+        fn f(_: bool, _: Option<String>) -> bool {
+            true
+        }
+        assert_eq!(
+            f.parameter_types(),
+            vec![TypeId::of::<bool>(), TypeId::of::<String>()]
+        );
+        assert_eq!(YarnFn::required_parameter_count(&f), 1);
+    }
+
     fn accept_yarn_fn<Marker>(_: impl YarnFn<Marker>) {}
 
     fn apply_yarn_fn<T, Marker>(f: T, input: Vec<YarnValue>) -> T::Out
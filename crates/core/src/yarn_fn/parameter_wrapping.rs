@@ -3,7 +3,7 @@
 //! Inspired by <https://promethia-27.github.io/dependency_injection_like_bevy_from_scratch/chapter2/passing_references.html>
 
 use crate::prelude::*;
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::borrow::Borrow;
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
@@ -50,13 +50,53 @@ impl YarnValueWrapper {
 /// - [`String`] (for a reference, [`&str`] may be used instead of `&String`)
 /// - [`YarnValue`], which means that a parameter may be any of the above types
 /// - Tuples of the above types.
+/// - [`Option`]s of the above types, which mark a trailing parameter as optional.
 pub trait YarnFnParam {
     /// The item type returned when constructing this [`YarnFn`] param. The value of this associated type should be `Self`, instantiated with a new lifetime.
     /// You could think of `YarnFnParam::Item<'new>` as being an operation that changes the lifetime bound to `Self`.
     type Item<'new>: YarnFnParam;
 
+    /// Whether this parameter may be omitted by the caller.
+    #[doc(hidden)]
+    const IS_OPTIONAL: bool = false;
+
     #[doc(hidden)]
     fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a>;
+
+    /// The [`TypeId`] under which this parameter is reported to the compiler.
+    #[doc(hidden)]
+    fn parameter_type_id() -> TypeId
+    where
+        Self: Sized + 'static,
+    {
+        TypeId::of::<Self>()
+    }
+}
+
+/// An optional parameter receives `None` when the caller ran out of arguments before reaching it.
+/// Since arguments are matched by position, optional parameters must come after all required ones.
+impl<T> YarnFnParam for Option<T>
+where
+    T: YarnFnParam,
+{
+    type Item<'new> = Option<T::Item<'new>>;
+
+    const IS_OPTIONAL: bool = true;
+
+    fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a> {
+        if iter.len() == 0 {
+            None
+        } else {
+            Some(T::retrieve(iter))
+        }
+    }
+
+    fn parameter_type_id() -> TypeId
+    where
+        Self: Sized + 'static,
+    {
+        TypeId::of::<T>()
+    }
 }
 
 /// Shorthand way of accessing the associated type [`YarnFnParam::Item`] for a given [`YarnFnParam`].
@@ -10,6 +10,11 @@ use thiserror::Error;
 /// The type implements meaningful conversions between types through [`TryFrom`] and [`From`].
 /// A failure to convert one variant to another will result in an [`YarnValueCastError`].
 ///
+/// Converting a value to another type and back yields the original value, e.g. `true` becomes `1.0` and then `true` again,
+/// with two unavoidable exceptions:
+/// - Every non-zero number converts to `true`, which converts back to `1.0`.
+/// - Strings that are not the canonical form of their number or boolean, like `"1.50"` or `"TRUE"`, come back as `"1.5"` or `"true"`.
+///
 /// ## Implementation Notes
 ///
 /// Corresponds to C#'s [`Convert`](https://docs.microsoft.com/en-us/dotnet/api/system.convert?view=net-5.0) class.
@@ -49,6 +54,66 @@ impl YarnValue {
             (a, b) => a == b,
         }
     }
+
+    /// Returns `true` if the value is a finite [`YarnValue::Number`] without a fractional part, e.g. `5.0` but not `5.5`.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Self::Number(value) if value.is_finite() && value.fract() == 0.0)
+    }
+
+    /// Converts the value to an integer type like [`TryFrom`] does, but rounds numbers with a fractional part
+    /// according to `rounding_mode` instead of rejecting them.
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`YarnValueCastError::OutOfRange`] if the rounded number does not fit into `T`,
+    /// or a parse error if the value is a [`YarnValue::String`] that is not a number.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use yarnspinner_core::prelude::*;
+    /// let value = YarnValue::Number(3.9);
+    /// assert_eq!(value.try_into_rounded::<i32>(RoundingMode::Truncate).unwrap(), 3);
+    /// assert_eq!(value.try_into_rounded::<i32>(RoundingMode::Round).unwrap(), 4);
+    /// ```
+    pub fn try_into_rounded<T>(&self, rounding_mode: RoundingMode) -> Result<T, YarnValueCastError>
+    where
+        T: for<'a> TryFrom<&'a YarnValue, Error = YarnValueCastError>,
+    {
+        let value = f32::try_from(self)?;
+        T::try_from(&YarnValue::Number(rounding_mode.apply(value)))
+    }
+}
+
+/// How [`YarnValue::try_into_rounded`] turns a number with a fractional part into a whole number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum RoundingMode {
+    /// Rounds toward zero, like an `as` cast. See [`f32::trunc`].
+    Truncate,
+    /// Rounds to the nearest whole number, with halfway cases rounded away from zero. See [`f32::round`].
+    Round,
+    /// Rounds toward negative infinity. See [`f32::floor`].
+    Floor,
+    /// Rounds toward positive infinity. See [`f32::ceil`].
+    Ceil,
+}
+
+impl RoundingMode {
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Truncate => value.trunc(),
+            Self::Round => value.round(),
+            Self::Floor => value.floor(),
+            Self::Ceil => value.ceil(),
+        }
+    }
 }
 
 impl<T> From<&T> for YarnValue
@@ -119,11 +184,30 @@ macro_rules! impl_whole_number {
                 }
             }
 
+            /// Fails for numbers with a fractional part and for numbers outside the range of the target type,
+            /// instead of silently truncating or saturating them.
+            /// Use [`YarnValue::try_into_rounded`] to round fractional numbers instead.
             impl TryFrom<&YarnValue> for $from_type {
                 type Error = YarnValueCastError;
 
                 fn try_from(value: &YarnValue) -> Result<Self, Self::Error> {
-                    f32::try_from(value).map(|value| value as $from_type)
+                    let value = f32::try_from(value)?;
+                    if value.fract() != 0.0 && value.is_finite() {
+                        return Err(YarnValueCastError::NotAWholeNumber {
+                            value,
+                            target_type: stringify!($from_type),
+                        });
+                    }
+                    // `MAX` itself may not be representable as `f32`, but `MAX + 1` is always a power of two
+                    let in_range = value >= <$from_type>::MIN as f32
+                        && value < <$from_type>::MAX as f32 + 1.0;
+                    if !in_range {
+                        return Err(YarnValueCastError::OutOfRange {
+                            value,
+                            target_type: stringify!($from_type),
+                        });
+                    }
+                    Ok(value as $from_type)
                 }
             }
 
@@ -180,12 +264,16 @@ impl TryFrom<YarnValue> for bool {
     }
 }
 
+/// Strings are converted case-insensitively, so `"true"`, `"True"` and `"TRUE"` all become `true`.
+/// Other spellings such as `"yes"`, `"no"`, `"1"` or `"0"` are not accepted and result in a [`YarnValueCastError::ParseBoolError`].
 impl TryFrom<&YarnValue> for bool {
     type Error = YarnValueCastError;
 
     fn try_from(value: &YarnValue) -> Result<Self, Self::Error> {
         match value {
             YarnValue::Number(value) => Ok(*value != 0.0),
+            YarnValue::String(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+            YarnValue::String(value) if value.eq_ignore_ascii_case("false") => Ok(false),
             YarnValue::String(value) => value.parse().map_err(Into::into),
             YarnValue::Boolean(value) => Ok(*value),
         }
@@ -214,6 +302,16 @@ pub enum YarnValueCastError {
     ParseIntError(#[from] std::num::ParseIntError),
     #[error(transparent)]
     ParseBoolError(#[from] std::str::ParseBoolError),
+    #[error("{value} cannot be converted to {target_type} because it is not a whole number")]
+    NotAWholeNumber {
+        value: f32,
+        target_type: &'static str,
+    },
+    #[error("{value} is out of range for {target_type}")]
+    OutOfRange {
+        value: f32,
+        target_type: &'static str,
+    },
 }
 
 impl Display for YarnValue {
@@ -225,3 +323,149 @@ impl Display for YarnValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_strings_to_bool_case_insensitively() {
+        assert!(bool::try_from(YarnValue::from("TRUE")).unwrap());
+        assert!(!bool::try_from(YarnValue::from("False")).unwrap());
+    }
+
+    #[test]
+    fn rejects_unparseable_strings_as_bool() {
+        let error = bool::try_from(YarnValue::from("yes")).unwrap_err();
+        assert!(matches!(error, YarnValueCastError::ParseBoolError(_)));
+    }
+
+    #[test]
+    fn converts_whole_numbers_to_integers() {
+        assert_eq!(u8::try_from(YarnValue::Number(255.0)).unwrap(), 255);
+        assert_eq!(i32::try_from(YarnValue::Number(-3.0)).unwrap(), -3);
+        assert_eq!(i8::try_from(YarnValue::from("-128")).unwrap(), -128);
+    }
+
+    #[test]
+    fn rejects_out_of_range_integers() {
+        let error = u8::try_from(YarnValue::Number(300.0)).unwrap_err();
+        assert!(matches!(
+            error,
+            YarnValueCastError::OutOfRange {
+                target_type: "u8",
+                ..
+            }
+        ));
+        assert!(u32::try_from(YarnValue::Number(-1.0)).is_err());
+        assert!(i32::try_from(YarnValue::Number(2_147_483_648.0)).is_err());
+        assert!(i64::try_from(YarnValue::Number(f32::NAN)).is_err());
+        assert!(usize::try_from(YarnValue::Number(f32::INFINITY)).is_err());
+    }
+
+    #[test]
+    fn rejects_fractional_integers() {
+        let error = i32::try_from(YarnValue::Number(3.5)).unwrap_err();
+        assert!(matches!(
+            error,
+            YarnValueCastError::NotAWholeNumber {
+                target_type: "i32",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn rounds_positive_numbers_to_integers() {
+        let value = YarnValue::Number(3.9);
+        let rounded = |mode| value.try_into_rounded::<i32>(mode).unwrap();
+        assert_eq!(rounded(RoundingMode::Truncate), 3);
+        assert_eq!(rounded(RoundingMode::Round), 4);
+        assert_eq!(rounded(RoundingMode::Floor), 3);
+        assert_eq!(rounded(RoundingMode::Ceil), 4);
+    }
+
+    #[test]
+    fn rounds_negative_numbers_to_integers() {
+        let value = YarnValue::Number(-3.9);
+        let rounded = |mode| value.try_into_rounded::<i32>(mode).unwrap();
+        assert_eq!(rounded(RoundingMode::Truncate), -3);
+        assert_eq!(rounded(RoundingMode::Round), -4);
+        assert_eq!(rounded(RoundingMode::Floor), -4);
+        assert_eq!(rounded(RoundingMode::Ceil), -3);
+    }
+
+    #[test]
+    fn rejects_out_of_range_rounded_integers() {
+        let error = YarnValue::Number(255.5)
+            .try_into_rounded::<u8>(RoundingMode::Ceil)
+            .unwrap_err();
+        assert!(matches!(error, YarnValueCastError::OutOfRange { .. }));
+        assert_eq!(
+            YarnValue::Number(255.5)
+                .try_into_rounded::<u8>(RoundingMode::Floor)
+                .unwrap(),
+            255
+        );
+        assert_eq!(
+            YarnValue::Number(-0.5)
+                .try_into_rounded::<u32>(RoundingMode::Truncate)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn detects_integers() {
+        assert!(YarnValue::Number(5.0).is_integer());
+        assert!(!YarnValue::Number(5.5).is_integer());
+        assert!(!YarnValue::Number(f32::INFINITY).is_integer());
+        assert!(!YarnValue::from("5").is_integer());
+    }
+
+    #[test]
+    fn round_trips_numbers() {
+        for number in [0.0, 1.0, -2.5, 1e-7, f32::MAX, f32::INFINITY] {
+            let via_string = String::from(YarnValue::from(number));
+            assert_eq!(f32::try_from(YarnValue::from(via_string)).unwrap(), number);
+
+            let via_bool = bool::try_from(YarnValue::from(number)).unwrap();
+            let expected = if number == 0.0 { 0.0 } else { 1.0 };
+            assert_eq!(f32::try_from(YarnValue::from(via_bool)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn round_trips_bools() {
+        for boolean in [true, false] {
+            let via_number = f32::try_from(YarnValue::from(boolean)).unwrap();
+            assert_eq!(
+                bool::try_from(YarnValue::from(via_number)).unwrap(),
+                boolean
+            );
+
+            let via_string = String::from(YarnValue::from(boolean));
+            assert_eq!(
+                bool::try_from(YarnValue::from(via_string)).unwrap(),
+                boolean
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_canonical_strings() {
+        for string in ["1.5", "-3", "0"] {
+            let via_number = f32::try_from(YarnValue::from(string)).unwrap();
+            assert_eq!(String::from(YarnValue::from(via_number)), string);
+        }
+        for string in ["true", "false"] {
+            let via_bool = bool::try_from(YarnValue::from(string)).unwrap();
+            assert_eq!(String::from(YarnValue::from(via_bool)), string);
+        }
+
+        let via_number = f32::try_from(YarnValue::from("1.50")).unwrap();
+        assert_eq!(String::from(YarnValue::from(via_number)), "1.5");
+        let via_bool = bool::try_from(YarnValue::from("TRUE")).unwrap();
+        assert_eq!(String::from(YarnValue::from(via_bool)), "true");
+    }
+}
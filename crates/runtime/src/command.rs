@@ -6,7 +6,7 @@
 use crate::markup::normalize;
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
-use yarnspinner_core::prelude::YarnValue;
+use yarnspinner_core::prelude::{split_command_text, CommandComponent, YarnValue};
 
 /// A custom command found in a Yarn file within the `<<` and `>>` characters.
 #[derive(Debug, Clone, PartialEq)]
@@ -23,6 +23,7 @@ pub struct Command {
     pub name: String,
 
     /// The parameters passed to the command. Strings that are surrounded by quotes are passed as a single parameter.
+    /// Inline expressions such as `{$gold}` have already been evaluated and substituted at this point.
     ///
     /// ## Examples
     ///
@@ -31,7 +32,9 @@ pub struct Command {
     ///
     /// ## Return value
     ///
-    /// The parameters are returned without underlying type information, so you will have to convert them using `YarnValue::try_into`.
+    /// The type of each parameter is inferred from its text: unquoted numbers become [`YarnValue::Number`],
+    /// unquoted `true` and `false` become [`YarnValue::Boolean`] and everything else, including all quoted text,
+    /// becomes [`YarnValue::String`]. You can still convert them to other types using `YarnValue::try_into`.
     pub parameters: Vec<YarnValue>,
 
     /// The raw, unprocessed command as it appeared in the Yarn file between the `<<` and `>>` characters.
@@ -44,15 +47,18 @@ impl Command {
             Help: You might have passed an expression that evaluates to whitespace, e.g. `{{0}} {{\"  \"}}`. \
             If you think this is a bug, please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
 
-        let mut components = split_command_text(&input);
+        let mut components = split_command_text(&normalize(&input));
         assert!(
             !components.is_empty(),
             "Parsing the command \"{}\" resulted in an empty list of components. \
             This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new",
             input
         );
-        let name = components.remove(0);
-        let parameters = components.into_iter().map(YarnValue::from).collect();
+        let name = components.remove(0).text;
+        let parameters = components
+            .into_iter()
+            .map(CommandComponent::into_parameter)
+            .collect();
         Self {
             name,
             parameters,
@@ -61,126 +67,11 @@ impl Command {
     }
 }
 
-/// Splits input into a number of non-empty sub-strings, separated
-/// by whitespace, and grouping double-quoted strings into a single
-/// sub-string.
-///
-/// This method behaves similarly to the [`String::split`] method with
-/// the empty results filtered out, with the following differences:
-///
-/// - Text that appears inside a pair of double-quote characters will not be split.
-/// - Text that appears after a double-quote character and
-/// before the end of the input will not be split (that is, an
-/// unterminated double-quoted string will be treated as though it
-/// had been terminated at the end of the input.)
-/// - When inside a pair of double-quote characters, the string
-/// `\\` will be converted to `\`, and the string `\"` will be converted to `"`.
-fn split_command_text(input: &str) -> Vec<String> {
-    let input = normalize(input);
-    let mut chars = input.chars().peekable();
-    let mut results = Vec::new();
-    let mut current_component = String::new();
-    while let Some(mut char) = chars.next() {
-        match char {
-            _ if char.is_whitespace() => {
-                if !current_component.is_empty() {
-                    // We've reached the end of a run of visible
-                    // characters. Add this run to the result list and
-                    // prepare for the next one.
-                    results.push(std::mem::take(&mut current_component));
-                } else {
-                    // We encountered a whitespace character, but
-                    // didn't have any characters queued up. Skip this
-                    // character.
-                }
-            }
-            '\"' => {
-                // We've entered a quoted string!
-                loop {
-                    char = match chars.next() {
-                        Some(c) => c,
-                        None => {
-                            // Oops, we ended the input while parsing a
-                            // quoted string! Dump our current word
-                            // immediately and return.
-                            results.push(current_component);
-                            return results;
-                        }
-                    };
-                    match char {
-                        '\\' => {
-                            // Possibly an escaped character!
-                            match chars.peek() {
-                                Some('\\') | Some('\"') => {
-                                    // It's an escaped character! Consume it and add it to the current component.
-                                    let next = chars.next().unwrap();
-                                    current_component.push(next);
-                                }
-                                _ => {
-                                    // Oops, an invalid escape. Add the \ and
-                                    // whatever is after it.
-                                    current_component.push(char);
-                                }
-                            }
-                        }
-                        '\"' => {
-                            // The end of a string!
-                            break;
-                        }
-                        _ => {
-                            // Any other character. Add it to the buffer.
-                            current_component.push(char);
-                        }
-                    }
-                }
-                results.push(std::mem::take(&mut current_component));
-            }
-            _ => {
-                current_component.push(char);
-            }
-        }
-    }
-    if !current_component.is_empty() {
-        results.push(current_component);
-    }
-    results
-}
-
 #[cfg(test)]
 mod tests {
     //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner-Unity/blob/5944b0e03d319303cd185b08140772a5804a2762/Tests/Runtime/DialogueRunnerTests/DialogueRunnerTests.cs#L465>
     use super::*;
 
-    #[test]
-    fn split_command_text_splits_text_correctly() {
-        for (input, expected_components) in [
-            ("one two three four", vec!["one", "two", "three", "four"]),
-            ("one \"two three\" four", vec!["one", "two three", "four"]),
-            ("one \"two three four", vec!["one", "two three four"]),
-            (
-                "one \"two \\\"three\" four",
-                vec!["one", "two \"three", "four"],
-            ),
-            (
-                "one \\two three four",
-                vec!["one", "\\two", "three", "four"],
-            ),
-            (
-                "one \"two \\\\ three\" four",
-                vec!["one", "two \\ three", "four"],
-            ),
-            (
-                "one \"two \\1 three\" four",
-                vec!["one", "two \\1 three", "four"],
-            ),
-            ("one      two", vec!["one", "two"]),
-        ] {
-            let parsed_components = split_command_text(input);
-
-            assert_eq!(expected_components, parsed_components);
-        }
-    }
-
     #[test]
     fn parses_command() {
         for (input, expected_command) in [
@@ -212,10 +103,24 @@ mod tests {
                 "set_sprite ship \"very happy\" 12.3",
                 Command {
                     name: "set_sprite".to_string(),
-                    parameters: vec!["ship".into(), "very happy".into(), "12.3".into()],
+                    parameters: vec!["ship".into(), "very happy".into(), 12.3.into()],
                     raw: "set_sprite ship \"very happy\" 12.3".to_string(),
                 },
             ),
+            (
+                "give_item \"rusty sword\" 2 true \"3\" inf",
+                Command {
+                    name: "give_item".to_string(),
+                    parameters: vec![
+                        "rusty sword".into(),
+                        2.into(),
+                        true.into(),
+                        "3".into(),
+                        "inf".into(),
+                    ],
+                    raw: "give_item \"rusty sword\" 2 true \"3\" inf".to_string(),
+                },
+            ),
             (
                 "!@#$%^&*()⁄€‹›ﬁﬂ‡°·‚‘-=_+",
                 Command {
@@ -0,0 +1,41 @@
+//! Not part of the original implementation, where commands are always dispatched by the game engine integration.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+/// A function that is called with the parameters of a [`Command`] when the [`Dialogue`] runs it.
+/// See [`Dialogue::add_command_handler`].
+pub(crate) type CommandHandler = Box<dyn FnMut(&[YarnValue]) + Send + Sync>;
+
+/// The command handlers registered on a [`Dialogue`], by command name.
+#[derive(Default)]
+pub(crate) struct CommandHandlers(HashMap<String, CommandHandler>);
+
+impl CommandHandlers {
+    pub(crate) fn insert(&mut self, name: String, handler: CommandHandler) {
+        self.0.insert(name, handler);
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        self.0.remove(name).is_some()
+    }
+
+    /// Calls the handler registered for the command, if there is one.
+    /// Returns `false` if there is no handler, in which case the command should be passed on to the caller.
+    pub(crate) fn try_handle(&mut self, command: &Command) -> bool {
+        match self.0.get_mut(&command.name) {
+            Some(handler) => {
+                handler(&command.parameters);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Debug for CommandHandlers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
@@ -0,0 +1,100 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+use yarnspinner_core::prelude::*;
+
+/// Records which lines and nodes a [`Dialogue`] emitted while coverage tracking was enabled via [`Dialogue::enable_coverage_tracking`].
+///
+/// Lines count as visited when they were sent in a [`DialogueEvent::Line`] or presented as one of the [`DialogueEvent::Options`],
+/// regardless of whether the option was selected. Nodes count as visited when a [`DialogueEvent::NodeStart`] was sent for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DialogueCoverage {
+    visited_lines: HashSet<LineId>,
+    visited_nodes: HashSet<String>,
+}
+
+impl DialogueCoverage {
+    pub(crate) fn record(&mut self, events: &[DialogueEvent]) {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => {
+                    self.visited_lines.insert(line.id.clone());
+                }
+                DialogueEvent::Options(options) => {
+                    self.visited_lines
+                        .extend(options.iter().map(|option| option.line.id.clone()));
+                }
+                DialogueEvent::NodeStart(node_name) => {
+                    self.visited_nodes.insert(node_name.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The IDs of all lines that were emitted so far.
+    #[must_use]
+    pub fn visited_lines(&self) -> &HashSet<LineId> {
+        &self.visited_lines
+    }
+
+    /// The names of all nodes that were started so far.
+    #[must_use]
+    pub fn visited_nodes(&self) -> &HashSet<String> {
+        &self.visited_nodes
+    }
+
+    /// Compares the recorded lines and nodes against all lines and nodes in the given [`Program`].
+    #[must_use]
+    pub fn report(&self, program: &Program) -> CoverageReport {
+        let all_lines: HashSet<_> = program
+            .nodes
+            .values()
+            .flat_map(VirtualMachine::line_ids_in_node)
+            .collect();
+        let total_lines = all_lines.len();
+        let mut uncovered_lines: Vec<_> = all_lines
+            .into_iter()
+            .filter(|line_id| !self.visited_lines.contains(line_id))
+            .collect();
+        uncovered_lines.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let mut uncovered_nodes: Vec<_> = program
+            .nodes
+            .keys()
+            .filter(|name| !self.visited_nodes.contains(*name))
+            .cloned()
+            .collect();
+        uncovered_nodes.sort();
+
+        CoverageReport {
+            total_lines,
+            uncovered_lines,
+            uncovered_nodes,
+        }
+    }
+}
+
+/// The result of [`DialogueCoverage::report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CoverageReport {
+    /// The number of lines and options in the [`Program`].
+    pub total_lines: usize,
+    /// The IDs of all lines and options in the [`Program`] that were never emitted, sorted by ID.
+    pub uncovered_lines: Vec<LineId>,
+    /// The names of all nodes in the [`Program`] that were never started, sorted by name.
+    pub uncovered_nodes: Vec<String>,
+}
+
+impl CoverageReport {
+    /// The percentage of lines that were emitted at least once, from `0.0` to `100.0`.
+    /// A [`Program`] without lines is considered fully covered.
+    #[must_use]
+    pub fn line_percentage(&self) -> f32 {
+        if self.total_lines == 0 {
+            return 100.0;
+        }
+        let covered_lines = self.total_lines - self.uncovered_lines.len();
+        covered_lines as f32 / self.total_lines as f32 * 100.0
+    }
+}
@@ -1,9 +1,14 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Dialogue.cs>
 
-use crate::markup::{DialogueTextProcessor, LineParser, MarkupParseError};
+use crate::markup::{
+    CustomTextProcessor, DialogueTextProcessor, LineParser, MarkupAttributeSpan, MarkupParseError,
+    MarkupParseResult,
+};
 use crate::prelude::*;
 use log::error;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::sync::Arc;
 use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
@@ -14,6 +19,7 @@ use yarnspinner_core::prelude::*;
 pub struct Dialogue {
     vm: VirtualMachine,
     language_code: Option<Language>,
+    start_node_name: String,
 }
 
 #[allow(missing_docs)]
@@ -29,7 +35,21 @@ pub enum DialogueError {
         id: LineId,
         language_code: Option<Language>,
     },
-    #[error("{selected_option_id:?} is not a valid option ID (expected a number between 0 and {max_id}.")]
+    #[error("Line \"{id}\" refers to substitution {{{index}}}, but only {substitution_count} substitution(s) were provided")]
+    MissingSubstitution {
+        id: LineId,
+        index: usize,
+        substitution_count: usize,
+    },
+    #[error("Line \"{id}\" never uses substitution {{{index}}}, although {substitution_count} substitution(s) were provided")]
+    UnusedSubstitution {
+        id: LineId,
+        index: usize,
+        substitution_count: usize,
+    },
+    #[error("No substitution was provided for the placeholder {{{name}}}")]
+    MissingNamedSubstitution { name: String },
+    #[error("{selected_option_id:?} is not a valid option ID (expected a number between 0 and {max_id}).")]
     InvalidOptionIdError {
         selected_option_id: OptionId,
         max_id: usize,
@@ -45,6 +65,15 @@ pub enum DialogueError {
     NoProgramLoaded,
     #[error("No node named \"{node_name}\" has been loaded.")]
     InvalidNode { node_name: String },
+    #[error("No node named \"{node_name}\" has been loaded, so it cannot be the start node.")]
+    InvalidStartNode {
+        node_name: String,
+        dialogue: Box<Dialogue>,
+    },
+    #[error("No node in group \"{group_name}\" has been loaded.")]
+    EmptyNodeGroup { group_name: String },
+    #[error("Evaluating \"{name}\" did not leave a value on the stack.")]
+    MissingExpressionValue { name: String },
     #[error(transparent)]
     VariableStorageError(#[from] VariableStorageError),
     #[error("Function \"{function_name}\" not found in library: {library}")]
@@ -66,9 +95,7 @@ impl Dialogue {
         text_provider: Box<dyn TextProvider>,
     ) -> Self {
         let mut library = Library::standard_library();
-        library
-            .add_function("visited", visited(variable_storage.clone()))
-            .add_function("visited_count", visited_count(variable_storage.clone()));
+        add_visit_tracking_functions(&mut library, variable_storage.as_ref());
 
         let dialogue_text_processor = Box::new(DialogueTextProcessor::new());
         let line_parser = LineParser::new()
@@ -79,29 +106,35 @@ impl Dialogue {
         Self {
             vm: VirtualMachine::new(library, variable_storage, line_parser, text_provider),
             language_code: Default::default(),
+            start_node_name: Self::DEFAULT_START_NODE_NAME.to_owned(),
         }
     }
 }
 
+fn add_visit_tracking_functions(library: &mut Library, variable_storage: &dyn VariableStorage) {
+    library
+        .add_function("visited", visited(variable_storage.clone_shallow()))
+        .add_function(
+            "visited_count",
+            visited_count(variable_storage.clone_shallow()),
+        );
+}
+
 fn visited(storage: Box<dyn VariableStorage>) -> yarn_fn_type! { impl Fn(String) -> bool } {
-    move |node: String| -> bool {
-        let name = Library::generate_unique_visited_variable_for_node(&node);
-        if let Ok(YarnValue::Number(count)) = storage.get(&name) {
-            count > 0.0
-        } else {
-            false
-        }
-    }
+    move |node: String| -> bool { get_visit_count(storage.as_ref(), &node) > 0.0 }
 }
 
 fn visited_count(storage: Box<dyn VariableStorage>) -> yarn_fn_type! { impl Fn(String) -> f32 } {
-    move |node: String| {
-        let name = Library::generate_unique_visited_variable_for_node(&node);
-        if let Ok(YarnValue::Number(count)) = storage.get(&name) {
-            count
-        } else {
-            0.0
-        }
+    move |node: String| get_visit_count(storage.as_ref(), &node)
+}
+
+/// Reads the tracking variable of the node. Untracked nodes count as never visited.
+fn get_visit_count(storage: &dyn VariableStorage, node: &str) -> f32 {
+    let name = Library::generate_unique_visited_variable_for_node(node);
+    if let Ok(YarnValue::Number(count)) = storage.get(&name) {
+        count
+    } else {
+        0.0
     }
 }
 
@@ -109,12 +142,29 @@ impl Iterator for Dialogue {
     type Item = Vec<DialogueEvent>;
 
     /// Panicking version of [`Dialogue::continue_`].
+    ///
+    /// Returns [`None`] instead of continuing when no node is selected, e.g. because the dialogue is complete,
+    /// or when the dialogue is [waiting for an option selection](Dialogue::is_waiting_for_option_selection).
     #[must_use = "All dialogue events that are returned by the dialogue must be handled or explicitly ignored"]
     fn next(&mut self) -> Option<Self::Item> {
         self.vm.next()
     }
 }
 
+impl Dialogue {
+    /// Runs the dialogue like its [`Iterator`] implementation, but yields the [`DialogueEvent`]s one at a time instead of in batches,
+    /// so that iterator adapters like [`Iterator::filter`] work on the events directly.
+    ///
+    /// Because the returned iterator borrows the dialogue mutably, it ends whenever an option must be selected.
+    /// See [`DialogueEvents`] for how to continue afterwards.
+    pub fn events(&mut self) -> DialogueEvents<'_> {
+        DialogueEvents {
+            dialogue: self,
+            batch: Vec::new().into_iter(),
+        }
+    }
+}
+
 // Accessors
 impl Dialogue {
     /// The [`Dialogue`]'s locale, as an IETF BCP 47 code.
@@ -144,6 +194,13 @@ impl Dialogue {
         std::mem::replace(&mut self.language_code, language_code)
     }
 
+    /// Builder-style version of [`Dialogue::set_language_code`].
+    #[must_use]
+    pub fn with_language_code(mut self, language_code: impl Into<Option<Language>>) -> Self {
+        self.set_language_code(language_code);
+        self
+    }
+
     /// Gets the [`Library`] that this Dialogue uses to locate functions.
     ///
     /// When the Dialogue is constructed, the Library is initialized with
@@ -173,6 +230,24 @@ impl Dialogue {
         self
     }
 
+    /// Gets what happens when the dialogue is continued while options are pending.
+    /// The default is [`MissingOptionSelection::Error`].
+    #[must_use]
+    pub fn missing_option_selection(&self) -> MissingOptionSelection {
+        self.vm.missing_option_selection
+    }
+
+    /// Sets what happens when the dialogue is continued while options are pending.
+    /// The default is [`MissingOptionSelection::Error`], which requires every [`DialogueEvent::Options`]
+    /// to be answered with [`Dialogue::set_selected_option`].
+    pub fn set_missing_option_selection(
+        &mut self,
+        missing_option_selection: MissingOptionSelection,
+    ) -> &mut Self {
+        self.vm.missing_option_selection = missing_option_selection;
+        self
+    }
+
     /// Gets the currently registered [`TextProvider`].
     pub fn text_provider(&self) -> &dyn TextProvider {
         self.vm.text_provider()
@@ -218,19 +293,123 @@ impl Dialogue {
         self.vm.continue_()
     }
 
+    /// Non-panicking version of the [`Iterator`] implementation of [`Dialogue`].
+    ///
+    /// Returns `Ok(None)` when the dialogue cannot continue because no node is selected, e.g. because the dialogue is complete.
+    /// Otherwise, returns the result of [`Dialogue::continue_`], so errors encountered while running the program,
+    /// such as calling a function that is not in the [`Library`], are returned instead of causing a panic.
+    /// This includes [`DialogueError::ContinueOnOptionSelectionError`] when an option must be selected first,
+    /// in which case [`Dialogue::next`] returns [`None`] instead.
+    pub fn try_next(&mut self) -> Result<Option<Vec<DialogueEvent>>> {
+        self.vm.try_next()
+    }
+
+    /// Executes exactly one instruction of the current program, e.g. to single-step through a node in a debugger.
+    ///
+    /// Returns the events produced by that instruction, which are usually none or a single one, but can be more,
+    /// e.g. [`DialogueEvent::NodeComplete`] followed by [`DialogueEvent::NodeStart`] when jumping to another node.
+    /// The events must be handled in the same way as those returned by [`Dialogue::continue_`], which is equivalent to calling this method
+    /// until the dialogue waits for input or is complete.
+    ///
+    /// Fails under the same conditions as [`Dialogue::continue_`].
+    pub fn step(&mut self) -> Result<DialogueStep> {
+        self.vm.step()
+    }
+
+    /// Returns a snapshot of the VM's operand stack, bottom first, e.g. to inspect a partially evaluated expression
+    /// while single-stepping with [`Dialogue::step`]. The stack is usually empty between calls to [`Dialogue::continue_`].
+    #[must_use]
+    pub fn debug_stack(&self) -> Vec<YarnValue> {
+        self.vm.stack()
+    }
+
+    /// Evaluates an expression compiled with `Compiler::compile_expression` against the current [`VariableStorage`] and [`Library`],
+    /// e.g. for a debug console. The running dialogue is not affected, but functions with side effects will still have them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VariableStorageError::VariableNotFound`] if the expression reads a variable that has neither been set
+    /// nor has an initial value in the loaded [`Program`], and fails if it calls a function missing from the [`Library`].
+    /// Returns [`DialogueError::MissingExpressionValue`] if `expression` does not leave a value on the stack,
+    /// e.g. because it was not compiled from an expression.
+    pub fn evaluate(&mut self, expression: &Node) -> Result<YarnValue> {
+        self.vm.evaluate(expression)
+    }
+
+    /// Returns the currently loaded [`Program`], if any.
+    #[must_use]
+    pub fn program(&self) -> Option<&Program> {
+        self.vm.program.as_deref()
+    }
+
+    /// Returns the type a variable was declared with in the loaded [`Program`], e.g. to show type hints in an editor.
+    /// Returns [`None`] if no program is loaded or it does not declare the variable.
+    ///
+    /// The type is derived from the variable's initial value, which the [`Program`] stores in place of the declaration.
+    /// Smart variables have no initial value, so their type is not known to the runtime and [`None`] is returned for them as well.
+    #[must_use]
+    pub fn variable_type(&self, name: &str) -> Option<Type> {
+        let initial_value = self.program()?.initial_values.get(name)?;
+        initial_value.value.as_ref()?;
+        Some(YarnValue::from(initial_value.clone()).into())
+    }
+
     /// Sets or replaces the [`Dialogue`]'s current [`Program`]. The program is replaced, all current state is reset.
-    pub fn replace_program(&mut self, program: Program) -> &mut Self {
-        self.vm.program.replace(program);
+    ///
+    /// Passing an [`Arc<Program>`] lets multiple dialogues share the same program without copying it.
+    pub fn replace_program(&mut self, program: impl Into<Arc<Program>>) -> &mut Self {
+        self.vm.program.replace(program.into());
         self.vm.reset_state();
         self
     }
 
+    /// Creates a new [`Dialogue`] that shares this dialogue's [`Program`] instead of copying it,
+    /// but runs independently with its own [`VariableStorage`] and [`TextProvider`].
+    /// This makes it cheap to run many sessions of the same program, e.g. one per connected player on a server.
+    ///
+    /// The new dialogue starts without a selected node and copies the [`Library`], language, start node, line hint and missing option selection settings.
+    /// The `visited` and `visited_count` functions are rebound to the new variable storage.
+    /// Command handlers and coverage tracking are not carried over.
+    #[must_use]
+    pub fn new_session(
+        &self,
+        variable_storage: Box<dyn VariableStorage>,
+        text_provider: Box<dyn TextProvider>,
+    ) -> Self {
+        let mut library = self.vm.library.clone();
+        add_visit_tracking_functions(&mut library, variable_storage.as_ref());
+
+        let mut vm = VirtualMachine::new(
+            library,
+            variable_storage,
+            self.vm.line_parser.clone(),
+            text_provider,
+        );
+        vm.program = self.vm.program.clone();
+        vm.line_hints_enabled = self.vm.line_hints_enabled;
+        vm.missing_option_selection = self.vm.missing_option_selection;
+        let mut dialogue = Self {
+            vm,
+            language_code: None,
+            start_node_name: self.start_node_name.clone(),
+        };
+        dialogue.set_language_code(self.language_code.clone());
+        dialogue
+    }
+
     /// Merges the currently set [`Program`] with the given one. If there is no program set, the given one is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both programs contain a node with the same name. Use [`Program::combine`] beforehand to handle this case gracefully.
     pub fn add_program(&mut self, program: Program) -> &mut Self {
         if let Some(existing_program) = self.vm.program.as_mut() {
-            *existing_program = Program::combine(vec![existing_program.clone(), program]).unwrap();
+            *existing_program = Program::combine(vec![(**existing_program).clone(), program])
+                .unwrap_or_else(|error| panic!("{error}"))
+                .unwrap()
+                .into();
         } else {
-            self.vm.program.replace(program);
+            self.vm.program.replace(program.into());
             self.vm.reset_state();
         }
 
@@ -252,6 +431,73 @@ impl Dialogue {
         Ok(self)
     }
 
+    /// The name of the node that [`Dialogue::set_node_to_start`] uses unless configured otherwise via [`Dialogue::set_start_node`].
+    pub const DEFAULT_START_NODE_NAME: &'static str = "Start";
+
+    /// The name of the node that [`Dialogue::set_node_to_start`] prepares to run. Defaults to [`Dialogue::DEFAULT_START_NODE_NAME`].
+    #[must_use]
+    pub fn start_node(&self) -> &str {
+        &self.start_node_name
+    }
+
+    /// Configures which node [`Dialogue::set_node_to_start`] prepares to run, for projects whose entry node is not called `Start`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if no node with the value of `node_name` has been loaded.
+    /// In that case, the previously configured start node is kept.
+    pub fn set_start_node(&mut self, node_name: impl Into<String>) -> Result<&mut Self> {
+        let node_name = node_name.into();
+        if !self.node_exists(&node_name) {
+            return Err(DialogueError::InvalidNode { node_name });
+        }
+        self.start_node_name = node_name;
+        Ok(self)
+    }
+
+    /// Builder-style version of [`Dialogue::set_start_node`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`DialogueError::InvalidStartNode`] if no node with the value of `node_name` has been loaded.
+    /// The error hands back the unchanged [`Dialogue`], so it can still be used, e.g. to fall back to [`Dialogue::DEFAULT_START_NODE_NAME`].
+    pub fn with_start_node(mut self, node_name: impl Into<String>) -> Result<Self> {
+        let node_name = node_name.into();
+        if !self.node_exists(&node_name) {
+            return Err(DialogueError::InvalidStartNode {
+                node_name,
+                dialogue: Box::new(self),
+            });
+        }
+        self.start_node_name = node_name;
+        Ok(self)
+    }
+
+    /// Prepares the [`Dialogue`] to run the start node, i.e. [`Dialogue::start_node`]. See [`Dialogue::set_node`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the start node has not been loaded.
+    pub fn set_node_to_start(&mut self) -> Result<&mut Self> {
+        let start_node_name = self.start_node_name.clone();
+        self.set_node(start_node_name)
+    }
+
+    /// Abandons whatever the [`Dialogue`] is currently doing and continues at the start of the node `node_name` instead.
+    ///
+    /// Unlike [`Dialogue::set_node`], this may also be called while the dialogue is waiting for an option to be selected,
+    /// in which case the options are discarded. Useful for debugging tools and cheat menus.
+    /// The next call to [`Dialogue::continue_`] runs the first content of the new node.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if no node with the value of `node_name` has been loaded.
+    /// In that case, the state of the dialogue is left unchanged.
+    pub fn jump_to_node(&mut self, node_name: impl Into<String>) -> Result<&mut Self> {
+        self.vm.jump_to_node(node_name)?;
+        Ok(self)
+    }
+
     /// Attempts to pop the line hints that were generated by the last [`Dialogue::set_node`] call.
     ///
     /// Panics if [`Dialogue::line_hints_enabled`] is `false`.
@@ -284,6 +530,105 @@ impl Dialogue {
             .map(|program| program.nodes.keys().map(|s| s.as_str()))
     }
 
+    /// The header that assigns a node to a group, e.g. `group: idle_barks`.
+    pub const GROUP_HEADER: &'static str = "group";
+
+    /// Returns the names of all nodes whose `group` header is `group_name`, sorted alphabetically.
+    /// Returns an empty list if no program has been loaded.
+    ///
+    /// ## Implementation Notes
+    ///
+    /// Not part of the original implementation, which picks a node from a group by checking the `when` headers of its nodes.
+    #[must_use]
+    pub fn nodes_in_group(&self, group_name: &str) -> Vec<String> {
+        let Some(program) = self.vm.program.as_ref() else {
+            return Vec::new();
+        };
+        let mut node_names: Vec<_> = program
+            .nodes
+            .values()
+            .filter(|node| {
+                node.headers
+                    .iter()
+                    .any(|header| header.key == Self::GROUP_HEADER && header.value == group_name)
+            })
+            .map(|node| node.name.clone())
+            .collect();
+        node_names.sort();
+        node_names
+    }
+
+    /// Prepares the [`Dialogue`] to run a random node of the group `group_name`, e.g. to pick one of several idle barks.
+    ///
+    /// `random_index` is called with the number of nodes in the group and picks one of them from the list returned by [`Dialogue::nodes_in_group`].
+    /// Its result is taken modulo the number of nodes. Pass a seeded random number generator to make the pick reproducible:
+    ///
+    /// ```ignore
+    /// let mut rng = SmallRng::seed_from_u64(42);
+    /// dialogue.set_node_in_group("idle_barks", |count| rng.gen_range(0..count))?;
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`DialogueError::EmptyNodeGroup`] if no node of the group has been loaded.
+    pub fn set_node_in_group(
+        &mut self,
+        group_name: &str,
+        random_index: impl FnOnce(usize) -> usize,
+    ) -> Result<&mut Self> {
+        let node_names = self.nodes_in_group(group_name);
+        if node_names.is_empty() {
+            return Err(DialogueError::EmptyNodeGroup {
+                group_name: group_name.to_owned(),
+            });
+        }
+        let index = random_index(node_names.len()) % node_names.len();
+        self.set_node(node_names[index].clone())
+    }
+
+    /// Starts recording which lines and nodes are emitted from now on, e.g. to check that a QA session saw every line.
+    /// Does nothing if coverage tracking is already enabled.
+    ///
+    /// Read the results via [`Dialogue::coverage`] and compare them against a [`Program`] with [`DialogueCoverage::report`].
+    pub fn enable_coverage_tracking(&mut self) -> &mut Self {
+        self.vm.coverage.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Returns the lines and nodes recorded since [`Dialogue::enable_coverage_tracking`] was called,
+    /// or [`None`] if coverage tracking is not enabled.
+    #[must_use]
+    pub fn coverage(&self) -> Option<&DialogueCoverage> {
+        self.vm.coverage.as_ref()
+    }
+
+    /// Compares the recorded coverage against the currently loaded [`Program`].
+    ///
+    /// Returns [`None`] if coverage tracking is not enabled or no program has been loaded.
+    #[must_use]
+    pub fn coverage_report(&self) -> Option<CoverageReport> {
+        let program = self.vm.program.as_ref()?;
+        Some(self.coverage()?.report(program))
+    }
+
+    /// Returns the IDs of all lines and options in the currently loaded [`Program`] for which the [`TextProvider`]
+    /// has no text in the current language, e.g. to check that a translation is complete before shipping.
+    /// Lines that would fall back to the base language count as having text.
+    ///
+    /// Returns an empty set if no program has been loaded.
+    #[must_use]
+    pub fn lines_without_text(&self) -> HashSet<LineId> {
+        let Some(program) = self.vm.program.as_ref() else {
+            return HashSet::new();
+        };
+        program
+            .nodes
+            .values()
+            .flat_map(VirtualMachine::line_ids_in_node)
+            .filter(|id| self.text_provider().get_text(id).is_none())
+            .collect()
+    }
+
     /// Returns the line ID that contains the original, uncompiled source
     /// text for a node.
     ///
@@ -326,12 +671,120 @@ impl Dialogue {
 
     /// Gets the name of the node that this Dialogue is currently executing.
     ///
-    /// If [`Dialogue::next`] has never been called, this value will be [`None`].
+    /// This is [`None`] until a node is selected, e.g. with [`Dialogue::set_node`], and again after the dialogue completed or was stopped.
+    /// In between, it changes together with the [`DialogueEvent::NodeStart`] events, e.g. when the dialogue runs a `<<jump>>`.
     #[must_use]
     pub fn current_node(&self) -> Option<String> {
         self.vm.current_node()
     }
 
+    /// Writes the text of the line with the given ID into `buffer`, with the `substitutions` inserted for their `{0}`, `{1}`, ... markers.
+    /// Markup is left as it is, see [`Dialogue::parse_markup_structured`] for that.
+    ///
+    /// Backslashes in the substitutions are escaped, so that parsing the markup keeps them as they are.
+    ///
+    /// The buffer is cleared before writing, so passing the same [`String`] for every line reuses its allocation.
+    /// Any slice of string-like values can be passed as substitutions, e.g. `&[&str]` or `&[String]`.
+    pub fn compose_line_text_into(
+        &self,
+        line_id: &LineId,
+        substitutions: &[impl AsRef<str>],
+        buffer: &mut String,
+    ) -> Result<()> {
+        self.vm.compose_line_text(line_id, substitutions, buffer)
+    }
+
+    /// Parses the markup in `text` the same way as the text of a [`Line`] is parsed,
+    /// including the custom handlers registered with [`Dialogue::add_markup_attribute_handler`].
+    /// Use this for text that did not come from a [`DialogueEvent::Line`], e.g. to style text shown outside the dialogue.
+    pub fn parse_markup_structured(
+        &mut self,
+        text: &str,
+    ) -> crate::markup::Result<MarkupParseResult> {
+        self.vm.parse_markup(text).map(MarkupParseResult::from)
+    }
+
+    /// Registers a handler for a custom markup attribute, e.g. `[shake]...[/shake]` for the name `shake`.
+    ///
+    /// The handler is called whenever a line containing the attribute is prepared and returns the text that replaces
+    /// the text between the opening and closing marker. Return [`MarkupAttributeSpan::text`] to keep the text as it is,
+    /// or an empty string to remove it. The attribute itself is still reported in [`Line::attributes`], spanning the returned text.
+    /// Registering a handler for a name that already has one, including the built-in `select`, `plural`, `ordinal` and `nomarkup`, replaces the old handler.
+    pub fn add_markup_attribute_handler(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl Fn(&MarkupAttributeSpan) -> String + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.vm
+            .set_marker_processor(name, Box::new(CustomTextProcessor::new(Arc::new(handler))));
+        self
+    }
+
+    /// Registers a handler that is called whenever the [`Dialogue`] runs a command with the given name,
+    /// e.g. `<<wait 2>>` for the name `wait`. The handler receives the [`Command::parameters`].
+    ///
+    /// Commands that have a handler are not returned as [`DialogueEvent::Command`] and do not pause the dialogue,
+    /// so handlers are best suited for commands that finish immediately. Registering a handler for a name
+    /// that already has one replaces the old handler.
+    pub fn add_command_handler(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl FnMut(&[YarnValue]) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.vm
+            .command_handlers
+            .insert(name.into(), Box::new(handler));
+        self
+    }
+
+    /// Removes the handler registered via [`Dialogue::add_command_handler`] for the given command name,
+    /// so that the command is returned as [`DialogueEvent::Command`] again.
+    /// Returns `false` if there was no such handler.
+    pub fn remove_command_handler(&mut self, name: &str) -> bool {
+        self.vm.command_handlers.remove(name)
+    }
+
+    /// Takes a snapshot of everything needed to resume this [`Dialogue`] later, e.g. for a save game.
+    ///
+    /// Call this between calls to [`Dialogue::continue_`], including while waiting for an option to be selected.
+    /// Pass the result to [`Dialogue::restore_state`] to continue from precisely this point.
+    #[must_use]
+    pub fn save_state(&self) -> DialogueState {
+        self.vm.save_state()
+    }
+
+    /// Restores a snapshot created by [`Dialogue::save_state`], so that the next call to [`Dialogue::continue_`]
+    /// resumes the dialogue where it was when the snapshot was taken.
+    ///
+    /// The values of all variables in the [`VariableStorage`] are replaced by the ones in the snapshot.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the node the snapshot was taken in is not part of the loaded [`Program`].
+    pub fn restore_state(&mut self, state: DialogueState) -> Result<&mut Self> {
+        self.vm.restore_state(state)?;
+        Ok(self)
+    }
+
+    /// Returns `true` if the node `node_name` has been completed at least once.
+    ///
+    /// This is the same as calling `visited("node_name")` in Yarn. Nodes are only tracked if
+    /// they are passed to `visited` or `visited_count` somewhere, or have the header `tracking: always`.
+    /// Untracked and unknown nodes are treated as never visited.
+    #[must_use]
+    pub fn has_visited(&self, node_name: &str) -> bool {
+        self.visit_count(node_name) > 0
+    }
+
+    /// Returns how many times the node `node_name` has been completed.
+    ///
+    /// This is the same as calling `visited_count("node_name")` in Yarn. See [`Dialogue::has_visited`] for which nodes are tracked.
+    /// Untracked and unknown nodes have a count of 0.
+    #[must_use]
+    pub fn visit_count(&self, node_name: &str) -> usize {
+        get_visit_count(self.vm.variable_storage(), node_name) as usize
+    }
+
     /// Analyses the currently loaded Yarn program with the given [`Context`]. Call [`Context::finish_analysis`] afterwards to get the results.
     pub fn analyse(&self, context: &mut Context) -> &Self {
         let program = self
@@ -367,9 +820,10 @@ impl Dialogue {
     /// The ID number that should be passed as the parameter to this method should be the [`OptionId`]
     /// field in the [`DialogueOption`] that represents the user's selection.
     ///
-    /// ## Panics
-    /// - If the Dialogue is not expecting an option to be selected.
-    /// - If the option ID is not found in the vector of [`DialogueOption`] provided by [`DialogueEvent::Options`].
+    /// ## Errors
+    /// - [`DialogueError::UnexpectedOptionSelectionError`] if the Dialogue is not expecting an option to be selected.
+    /// - [`DialogueError::InvalidOptionIdError`] if the option ID is not found in the vector of [`DialogueOption`] provided by [`DialogueEvent::Options`].
+    ///   The options stay pending in this case, so a valid ID can be passed afterwards.
     ///
     /// ## See Also
     /// - [`Dialogue::continue_`]
@@ -384,9 +838,17 @@ impl Dialogue {
         self.vm.is_active()
     }
 
+    /// Returns the options of the last [`DialogueEvent::Options`] while they are waiting to be selected,
+    /// e.g. to preview them and their [availability](DialogueOption::is_available) again after the event was handled.
+    /// Returns [`None`] if the dialogue is not [waiting for an option selection](Dialogue::is_waiting_for_option_selection).
+    #[must_use]
+    pub fn peek_options(&self) -> Option<&[DialogueOption]> {
+        self.vm.pending_options()
+    }
+
     /// Returns `true` if the last call to [`Dialogue::continue_`] returned [`DialogueEvent::Options`] and the dialogue is therefore
-    /// waiting for the user to select an option via [`Dialogue::set_selected_option`]. If this is `true`, calling [`Dialogue::continue_`] will error
-    /// and [`Dialogue::next`] will panic.
+    /// waiting for the user to select an option via [`Dialogue::set_selected_option`]. If this is `true`, [`Dialogue::continue_`] and [`Dialogue::try_next`]
+    /// return [`DialogueError::ContinueOnOptionSelectionError`] and [`Dialogue::next`] returns [`None`], unless [`MissingOptionSelection::SelectFirstAvailable`] is set.
     pub fn is_waiting_for_option_selection(&self) -> bool {
         self.vm.is_waiting_for_option_selection()
     }
@@ -395,6 +857,9 @@ impl Dialogue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::*;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn is_send_sync() {
@@ -405,4 +870,584 @@ mod tests {
     }
 
     fn accept_send_sync(_: impl Send + Sync) {}
+
+    #[test]
+    fn reads_back_language_code() {
+        let variable_storage = Box::new(MemoryVariableStorage::new());
+        let text_provider = Box::new(StringTableTextProvider::new());
+        let mut dialogue =
+            Dialogue::new(variable_storage, text_provider).with_language_code(Language::new("en"));
+        assert_eq!(Some(&Language::new("en")), dialogue.language_code());
+        assert_eq!(
+            Some(Language::new("en")),
+            dialogue.text_provider().get_language()
+        );
+
+        let previous = dialogue.set_language_code(Language::new("de-CH"));
+        assert_eq!(Some(Language::new("en")), previous);
+        assert_eq!(Some(&Language::new("de-CH")), dialogue.language_code());
+
+        dialogue.set_language_code(None);
+        assert_eq!(None, dialogue.language_code());
+    }
+
+    #[test]
+    fn parses_markup_structured() {
+        let variable_storage = Box::new(MemoryVariableStorage::new());
+        let text_provider = Box::new(StringTableTextProvider::new());
+        let mut dialogue = Dialogue::new(variable_storage, text_provider);
+
+        let markup = dialogue
+            .parse_markup_structured("A [b]bold[/b] move")
+            .unwrap();
+        assert_eq!("A bold move", markup.text);
+        assert_eq!(1, markup.attributes.len());
+        assert_eq!("b", markup.attributes[0].name);
+        assert_eq!(2, markup.attributes[0].position);
+        assert_eq!(4, markup.attributes[0].length);
+
+        let markup = dialogue
+            .parse_markup_structured("Hello [wave/]there")
+            .unwrap();
+        assert_eq!("Hello there", markup.text);
+        assert_eq!(1, markup.attributes.len());
+        assert_eq!("wave", markup.attributes[0].name);
+        assert_eq!(6, markup.attributes[0].position);
+        assert_eq!(0, markup.attributes[0].length);
+    }
+
+    #[test]
+    fn yields_events_one_at_a_time() {
+        let start = node(
+            "Start",
+            vec![
+                run_line("line:1"),
+                run_line("line:2"),
+                run_line("line:3"),
+                instruction(OpCode::Stop, vec![]),
+            ],
+        );
+        let mut dialogue = dialogue(
+            program([start]),
+            &[("line:1", "One"), ("line:2", "Two"), ("line:3", "Three")],
+        );
+        dialogue.set_node("Start").unwrap();
+
+        let lines: Vec<_> = dialogue
+            .events()
+            .filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(lines, ["One", "Two", "Three"]);
+        assert!(dialogue.events().next().is_none());
+    }
+
+    #[test]
+    fn sessions_share_program_across_threads() {
+        let start = node(
+            "Start",
+            vec![
+                instruction(OpCode::PushVariable, vec!["$name".to_owned().into()]),
+                instruction(
+                    OpCode::RunLine,
+                    vec!["line:1".to_owned().into(), 1.0.into()],
+                ),
+                instruction(OpCode::Stop, vec![]),
+            ],
+        );
+        let program = Program {
+            initial_values: [("$name".to_owned(), "nobody".to_owned().into())]
+                .into_iter()
+                .collect(),
+            ..program([start])
+        };
+        let text_provider = text_provider(&[("line:1", "Hello, {0}")]);
+        let mut template = Dialogue::new(
+            Box::new(MemoryVariableStorage::new()),
+            Box::new(text_provider.clone()),
+        );
+        template.replace_program(program);
+
+        let handles: Vec<_> = ["Alice", "Bob"]
+            .into_iter()
+            .map(|name| {
+                let mut variable_storage = MemoryVariableStorage::new();
+                variable_storage
+                    .set("$name".to_owned(), name.into())
+                    .unwrap();
+                let mut session = template
+                    .new_session(Box::new(variable_storage), Box::new(text_provider.clone()));
+                assert!(std::ptr::eq(
+                    session.program().unwrap(),
+                    template.program().unwrap()
+                ));
+                std::thread::spawn(move || {
+                    session.set_node("Start").unwrap();
+                    session
+                        .events()
+                        .filter_map(|event| match event {
+                            DialogueEvent::Line(line) => Some(line.text),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let lines: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert_eq!(lines, [["Hello, Alice"], ["Hello, Bob"]]);
+    }
+
+    #[test]
+    fn composes_lines_into_a_reused_buffer() {
+        let dialogue = dialogue(
+            Program::default(),
+            &[
+                (
+                    "line:long",
+                    "{0} walks into a [b]very long[/b] tavern named {1}",
+                ),
+                ("line:short", "Hi, {0}"),
+            ],
+        );
+        let long = LineId::from("line:long");
+        let short = LineId::from("line:short");
+
+        let mut buffer = String::new();
+        dialogue
+            .compose_line_text_into(&long, &["Alice", "The Prancing Pony"], &mut buffer)
+            .unwrap();
+        let capacity = buffer.capacity();
+        for i in 0..1000 {
+            let name = i.to_string();
+            let (line_id, expected) = if i % 2 == 0 {
+                (&short, format!("Hi, {name}"))
+            } else {
+                (
+                    &long,
+                    format!("{name} walks into a [b]very long[/b] tavern named Inn"),
+                )
+            };
+            let substitutions = if i % 2 == 0 {
+                vec![name.as_str()]
+            } else {
+                vec![name.as_str(), "Inn"]
+            };
+            dialogue
+                .compose_line_text_into(line_id, &substitutions, &mut buffer)
+                .unwrap();
+            assert_eq!(expected, buffer);
+        }
+
+        assert_eq!(capacity, buffer.capacity());
+    }
+
+    #[test]
+    fn keeps_backslashes_in_substitutions_literal() {
+        let start = node(
+            "Start",
+            vec![
+                instruction(OpCode::PushString, vec![r"back\".to_owned().into()]),
+                instruction(
+                    OpCode::RunLine,
+                    vec!["line:1".to_owned().into(), 1.0.into()],
+                ),
+                instruction(OpCode::Stop, vec![]),
+            ],
+        );
+        let mut dialogue = dialogue(program([start]), &[("line:1", r"{0}[b]slash[/b] \# 1")]);
+        dialogue.set_node("Start").unwrap();
+
+        let line = dialogue
+            .events()
+            .find_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(r"back\slash # 1", line.text);
+        assert_eq!(1, line.attributes.len());
+        assert_eq!("b", line.attributes[0].name);
+        assert_eq!(5, line.attributes[0].position);
+    }
+
+    #[test]
+    fn hands_back_dialogue_when_start_node_is_missing() {
+        let intro = node(
+            "Intro",
+            vec![run_line("line:1"), instruction(OpCode::Stop, vec![])],
+        );
+        let dialogue = dialogue(program([intro]), &[("line:1", "Right node")]);
+
+        let Err(DialogueError::InvalidStartNode {
+            node_name,
+            dialogue,
+        }) = dialogue.with_start_node("Missing")
+        else {
+            panic!("Expected the missing start node to be rejected");
+        };
+        assert_eq!("Missing", node_name);
+        assert_eq!(Dialogue::DEFAULT_START_NODE_NAME, dialogue.start_node());
+
+        let mut dialogue = dialogue.with_start_node("Intro").unwrap();
+        dialogue.set_node_to_start().unwrap();
+        let first_line = dialogue.events().find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text),
+            _ => None,
+        });
+        assert_eq!(Some("Right node".to_owned()), first_line);
+    }
+
+    #[test]
+    fn computes_smart_variables_on_every_read() {
+        let is_rich = node(
+            "$is_rich",
+            vec![
+                instruction(OpCode::PushVariable, vec!["$gold".to_owned().into()]),
+                instruction(OpCode::PushFloat, vec![100.0.into()]),
+                instruction(OpCode::PushFloat, vec![2.0.into()]),
+                instruction(
+                    OpCode::CallFunc,
+                    vec![Type::Number
+                        .get_canonical_name_for_method(&Operator::GreaterThan.to_string())
+                        .into()],
+                ),
+            ],
+        );
+        let program = Program {
+            initial_values: [("$gold".to_owned(), 0.0.into())].into_iter().collect(),
+            smart_variables: [("$is_rich".to_owned(), is_rich)].into_iter().collect(),
+            ..Default::default()
+        };
+        let read_is_rich = node(
+            "",
+            vec![instruction(
+                OpCode::PushVariable,
+                vec!["$is_rich".to_owned().into()],
+            )],
+        );
+        let mut dialogue = dialogue(program, &[]);
+
+        assert_eq!(
+            YarnValue::Boolean(false),
+            dialogue.evaluate(&read_is_rich).unwrap()
+        );
+        dialogue
+            .variable_storage_mut()
+            .set("$gold".to_owned(), 150.0.into())
+            .unwrap();
+        assert_eq!(
+            YarnValue::Boolean(true),
+            dialogue.evaluate(&read_is_rich).unwrap()
+        );
+    }
+
+    #[test]
+    fn evaluating_a_node_without_a_value_is_an_error() {
+        let mut dialogue = dialogue(Program::default(), &[]);
+        let empty = node("empty", vec![]);
+
+        let error = dialogue.evaluate(&empty).unwrap_err();
+        assert!(
+            matches!(error, DialogueError::MissingExpressionValue { ref name } if name == "empty")
+        );
+    }
+
+    fn dialogue_with_options(missing_option_selection: MissingOptionSelection) -> Dialogue {
+        let add_option = |line_id: &str, destination: &str| {
+            instruction(
+                OpCode::AddOption,
+                vec![
+                    line_id.to_owned().into(),
+                    destination.to_owned().into(),
+                    0.0.into(),
+                    false.into(),
+                ],
+            )
+        };
+        let start = Node {
+            labels: [("stay".to_owned(), 4), ("leave".to_owned(), 6)]
+                .into_iter()
+                .collect(),
+            ..node(
+                "Start",
+                vec![
+                    add_option("line:stay", "stay"),
+                    add_option("line:leave", "leave"),
+                    instruction(OpCode::ShowOptions, vec![]),
+                    instruction(OpCode::Jump, vec![]),
+                    run_line("line:stayed"),
+                    instruction(OpCode::Stop, vec![]),
+                    run_line("line:left"),
+                    instruction(OpCode::Stop, vec![]),
+                ],
+            )
+        };
+        let mut dialogue = dialogue(
+            program([start]),
+            &[
+                ("line:stay", "Stay"),
+                ("line:leave", "Leave"),
+                ("line:stayed", "You stayed"),
+                ("line:left", "You left"),
+            ],
+        );
+        dialogue
+            .set_missing_option_selection(missing_option_selection)
+            .set_node("Start")
+            .unwrap();
+        dialogue
+    }
+
+    #[test]
+    fn errors_on_missing_option_selection_by_default() {
+        let mut dialogue = dialogue_with_options(MissingOptionSelection::default());
+        let events = dialogue.continue_().unwrap();
+        assert!(matches!(events.last(), Some(DialogueEvent::Options(_))));
+
+        assert!(matches!(
+            dialogue.continue_(),
+            Err(DialogueError::ContinueOnOptionSelectionError)
+        ));
+        assert!(matches!(
+            dialogue.try_next(),
+            Err(DialogueError::ContinueOnOptionSelectionError)
+        ));
+        assert!(dialogue.next().is_none());
+        assert!(dialogue.is_waiting_for_option_selection());
+    }
+
+    #[test]
+    fn peeks_pending_options() {
+        let mut dialogue = dialogue_with_options(MissingOptionSelection::default());
+        assert!(dialogue.peek_options().is_none());
+
+        let events = dialogue.continue_().unwrap();
+        let Some(DialogueEvent::Options(options)) = events.last() else {
+            panic!("Expected options, got {events:?}");
+        };
+        assert_eq!(dialogue.peek_options(), Some(options.as_slice()));
+
+        dialogue.set_selected_option(OptionId(1)).unwrap();
+        assert!(dialogue.peek_options().is_none());
+    }
+
+    #[test]
+    fn selects_first_available_option_if_configured() {
+        let dialogue = dialogue_with_options(MissingOptionSelection::SelectFirstAvailable);
+        let lines: Vec<_> = dialogue
+            .flatten()
+            .filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(lines, ["You stayed"]);
+    }
+
+    #[test]
+    fn reports_variables_without_value() {
+        let start = node(
+            "Start",
+            vec![instruction(
+                OpCode::PushVariable,
+                vec!["$gold".to_owned().into()],
+            )],
+        );
+        let mut dialogue = dialogue(program([start]), &[]);
+        dialogue.set_node("Start").unwrap();
+
+        let error = dialogue.continue_().unwrap_err();
+        assert!(matches!(
+            error,
+            DialogueError::VariableStorageError(VariableStorageError::VariableNotFound { ref name })
+                if name == "$gold"
+        ));
+    }
+
+    #[test]
+    fn tracks_current_node_across_jumps() {
+        let start = node(
+            "Start",
+            vec![
+                run_line("line:1"),
+                // <<jump End>>
+                instruction(OpCode::PushString, vec!["End".to_owned().into()]),
+                instruction(OpCode::RunNode, vec![]),
+            ],
+        );
+        let end = node(
+            "End",
+            vec![run_line("line:2"), instruction(OpCode::Stop, vec![])],
+        );
+        let mut dialogue = dialogue(
+            program([start, end]),
+            &[("line:1", "One"), ("line:2", "Two")],
+        );
+        assert_eq!(dialogue.current_node(), None);
+
+        dialogue.set_node("Start").unwrap();
+        assert_eq!(dialogue.current_node().as_deref(), Some("Start"));
+        let _ = dialogue.continue_().unwrap();
+        assert_eq!(dialogue.current_node().as_deref(), Some("Start"));
+
+        let events = dialogue.continue_().unwrap();
+        assert!(events.contains(&DialogueEvent::NodeStart("End".to_owned())));
+        assert_eq!(dialogue.current_node().as_deref(), Some("End"));
+
+        let events = dialogue.continue_().unwrap();
+        assert_eq!(events.last(), Some(&DialogueEvent::DialogueComplete));
+        assert_eq!(dialogue.current_node(), None);
+    }
+
+    #[test]
+    fn returns_from_nested_detours() {
+        let detour = |node_name: &str| {
+            [
+                instruction(OpCode::PushString, vec![node_name.to_owned().into()]),
+                instruction(OpCode::DetourToNode, vec![]),
+            ]
+        };
+        let program = program([
+            node(
+                "Start",
+                [run_line("line:start")]
+                    .into_iter()
+                    .chain(detour("Outer"))
+                    .chain([run_line("line:back"), instruction(OpCode::Stop, vec![])])
+                    .collect(),
+            ),
+            // Running past the end of a node returns just like `Return` does
+            node(
+                "Outer",
+                [run_line("line:outer")]
+                    .into_iter()
+                    .chain(detour("Inner"))
+                    .collect(),
+            ),
+            node(
+                "Inner",
+                vec![run_line("line:inner"), instruction(OpCode::Return, vec![])],
+            ),
+        ]);
+        let mut dialogue = dialogue(
+            program,
+            &[
+                ("line:start", "Start"),
+                ("line:outer", "Outer"),
+                ("line:inner", "Inner"),
+                ("line:back", "Back in Start"),
+            ],
+        );
+        dialogue.set_node("Start").unwrap();
+
+        let events: Vec<_> = dialogue.events().collect();
+        let lines: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let completed_nodes: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                DialogueEvent::NodeComplete(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(lines, ["Start", "Outer", "Inner", "Back in Start"]);
+        assert_eq!(completed_nodes, ["Inner", "Outer", "Start"]);
+        assert_eq!(events.last(), Some(&DialogueEvent::DialogueComplete));
+        assert_eq!(dialogue.current_node(), None);
+    }
+
+    #[test]
+    fn reports_declared_variable_types() {
+        let program = Program {
+            initial_values: [
+                ("$name".to_owned(), "Sally".to_owned().into()),
+                ("$gold".to_owned(), 10.0.into()),
+                ("$met_sally".to_owned(), false.into()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let mut dialogue = Dialogue::new(
+            Box::new(MemoryVariableStorage::new()),
+            Box::new(StringTableTextProvider::new()),
+        );
+        assert_eq!(dialogue.variable_type("$name"), None);
+
+        dialogue.add_program(program);
+        assert_eq!(dialogue.variable_type("$name"), Some(Type::String));
+        assert_eq!(dialogue.variable_type("$gold"), Some(Type::Number));
+        assert_eq!(dialogue.variable_type("$met_sally"), Some(Type::Boolean));
+        assert_eq!(dialogue.variable_type("$unknown"), None);
+    }
+
+    #[test]
+    fn picks_nodes_in_group() {
+        let node = |name: &str, group: Option<&str>| {
+            let node = Node {
+                name: name.to_owned(),
+                headers: group
+                    .map(|group| Header {
+                        key: Dialogue::GROUP_HEADER.to_owned(),
+                        value: group.to_owned(),
+                    })
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            };
+            (name.to_owned(), node)
+        };
+        let program = Program {
+            nodes: [
+                node("Bark_Yawn", Some("idle_barks")),
+                node("Bark_Hum", Some("idle_barks")),
+                node("Bark_Sigh", Some("idle_barks")),
+                node("Greeting", Some("greetings")),
+                node("Start", None),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let mut dialogue = Dialogue::new(
+            Box::new(MemoryVariableStorage::new()),
+            Box::new(StringTableTextProvider::new()),
+        );
+        dialogue.add_program(program);
+
+        let barks = dialogue.nodes_in_group("idle_barks");
+        assert_eq!(barks, ["Bark_Hum", "Bark_Sigh", "Bark_Yawn"]);
+        assert!(dialogue.nodes_in_group("Start").is_empty());
+
+        let mut pick = |seed| {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            dialogue
+                .set_node_in_group("idle_barks", |count| rng.gen_range(0..count))
+                .unwrap();
+            dialogue.current_node().unwrap()
+        };
+        let first = pick(42);
+        assert!(barks.contains(&first));
+        assert_eq!(first, pick(42));
+
+        assert!(matches!(
+            dialogue.set_node_in_group("battle_cries", |_| 0),
+            Err(DialogueError::EmptyNodeGroup { group_name }) if group_name == "battle_cries"
+        ));
+    }
 }
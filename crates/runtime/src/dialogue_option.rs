@@ -35,6 +35,15 @@ pub struct DialogueOption {
     /// This is intended for situations where games wish to show options that the player _could_ have taken,
     /// if some other condition had been met (e.g. having enough "charisma" points).
     pub is_available: bool,
+
+    /// The source code of the line condition attached to this option, e.g. `$gold > 10` for `-> Buy <<if $gold > 10>>`,
+    /// or [`None`] if the option has no condition.
+    /// Useful for explaining to the player why an option is not [available](DialogueOption::is_available).
+    pub condition: Option<String>,
+
+    /// The hashtags of this option without the leading `#`, e.g. `["expensive"]` for `-> Buy #expensive`.
+    /// The `#line:` tag holding the line ID is not included.
+    pub tags: Vec<String>,
 }
 
 /// The identifying number for an option. You should not need to create these yourself, since you get them from [`DialogueOption`]s.
@@ -51,6 +60,28 @@ pub struct DialogueOption {
 )]
 pub struct OptionId(pub usize);
 
+/// What the [`Dialogue`] does when it is asked to continue while options are pending,
+/// i.e. after [`DialogueEvent::Options`] without a call to [`Dialogue::set_selected_option`].
+/// Set through [`Dialogue::set_missing_option_selection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum MissingOptionSelection {
+    /// [`Dialogue::continue_`] and [`Dialogue::try_next`] fail with [`DialogueError::ContinueOnOptionSelectionError`].
+    /// The [`Iterator`] implementation of [`Dialogue`] returns [`None`] instead, just like it does at the end of the dialogue.
+    /// Use [`Dialogue::is_waiting_for_option_selection`] to tell the two apart.
+    #[default]
+    Error,
+    /// The first option that is [available](DialogueOption::is_available) is selected before continuing.
+    /// If no option is available, this behaves like [`MissingOptionSelection::Error`].
+    SelectFirstAvailable,
+}
+
 impl Display for OptionId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -0,0 +1,39 @@
+//! Not part of the original implementation; allows saving and loading an in-progress [`Dialogue`].
+
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// A snapshot of an in-progress [`Dialogue`], created by [`Dialogue::save_state`] and restored by [`Dialogue::restore_state`].
+///
+/// Contains everything needed to resume the dialogue exactly where it left off, including the
+/// current node, the position inside of it, pending options and the values of all variables.
+/// The [`Program`] itself is not part of the snapshot, so the [`Dialogue`] it is restored into
+/// must have the same program loaded. Enable the `serde` feature to write it to a save file.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DialogueState {
+    pub(crate) current_node_name: Option<String>,
+    pub(crate) state: State,
+    pub(crate) execution_state: ExecutionState,
+    pub(crate) variables: HashMap<String, YarnValue>,
+}
+
+impl DialogueState {
+    /// The name of the node the dialogue was running when this snapshot was taken, if any.
+    #[must_use]
+    pub fn current_node(&self) -> Option<&str> {
+        self.current_node_name.as_deref()
+    }
+
+    /// The values of all variables at the time this snapshot was taken.
+    #[must_use]
+    pub fn variables(&self) -> &HashMap<String, YarnValue> {
+        &self.variables
+    }
+}
@@ -0,0 +1,23 @@
+use crate::prelude::DialogueEvent;
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+
+/// The result of running a single instruction via [`Dialogue::step`](crate::prelude::Dialogue::step).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+#[non_exhaustive]
+pub struct DialogueStep {
+    /// The events produced by the instruction.
+    pub events: Vec<DialogueEvent>,
+    /// The node that will run the next instruction, or [`None`] if the dialogue is complete.
+    pub node_name: Option<String>,
+    /// The index of the next instruction to run in the node's instruction list,
+    /// as shown by [`Program::disassemble`](yarnspinner_core::prelude::Program::disassemble).
+    pub instruction_pointer: usize,
+}
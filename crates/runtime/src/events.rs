@@ -18,6 +18,9 @@ use yarnspinner_core::prelude::*;
 )]
 /// An event encountered while running [`Dialogue::continue_`]. A caller is expected to handle these events and act accordingly.
 ///
+/// With the `serde` feature, events can be serialized along with everything they contain,
+/// e.g. to record a session and replay or snapshot test it without the [`Program`] that produced it.
+///
 /// ## Implementation note
 ///
 /// Corresponds to Yarn Spinner's `<EventName>Handler`s.
@@ -45,6 +48,97 @@ pub enum DialogueEvent {
     ///
     /// Corresponds to Yarn Spinner's `PrepareForLinesHandler`
     LineHints(Vec<LineId>),
+    /// A variable was assigned a value, e.g. through `<<set>>`.
+    ///
+    /// ## Implementation note
+    ///
+    /// Not part of the original implementation. Allows reacting to changes in the dialogue's state without polling the [`VariableStorage`].
+    VariableChanged {
+        /// The name of the variable, including the leading `$`.
+        name: String,
+        /// The value the variable had before, or [`None`] if this is the first time it was assigned a value.
+        old: Option<YarnValue>,
+        /// The value the variable has now.
+        new: YarnValue,
+    },
     /// The dialogue was completed. Set it to a new node via [`Dialogue::set_node`] before calling [`Dialogue::continue_`] again.
     DialogueComplete,
 }
+
+/// An iterator over the [`DialogueEvent`]s of a [`Dialogue`], one at a time. Created by [`Dialogue::events`].
+///
+/// Runs the dialogue in the same batches as the [`Iterator`] implementation of [`Dialogue`] and yields their events in order.
+/// It ends where that iterator would return [`None`]: when the dialogue is complete, or when it waits for an option to be selected.
+/// In the latter case, the last event yielded is a [`DialogueEvent::Options`]. Drop the iterator, select an option with
+/// [`Dialogue::set_selected_option`] and call [`Dialogue::events`] again to keep going.
+#[derive(Debug)]
+pub struct DialogueEvents<'a> {
+    pub(crate) dialogue: &'a mut Dialogue,
+    pub(crate) batch: std::vec::IntoIter<DialogueEvent>,
+}
+
+impl Iterator for DialogueEvents<'_> {
+    type Item = DialogueEvent;
+
+    /// Panics under the same conditions as the [`Iterator`] implementation of [`Dialogue`].
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.batch.next() {
+                return Some(event);
+            }
+            self.batch = self.dialogue.next()?.into_iter();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::markup::{MarkupAttribute, MarkupValue};
+    use std::collections::HashMap;
+
+    fn line(id: &str, text: &str) -> Line {
+        Line {
+            id: LineId::from(id),
+            text: text.to_owned(),
+            attributes: vec![MarkupAttribute {
+                name: "wave".to_owned(),
+                position: 0,
+                length: 2,
+                properties: HashMap::from([("speed".to_owned(), MarkupValue::Float(1.5))]),
+                source_position: 0,
+            }],
+            is_last_line_before_options: true,
+        }
+    }
+
+    #[test]
+    fn round_trips_events_through_serde() {
+        let events = vec![
+            DialogueEvent::Line(line("line:1", "Hi there")),
+            DialogueEvent::Options(vec![
+                DialogueOption {
+                    line: line("line:2", "Buy a sword"),
+                    id: OptionId(0),
+                    destination_node: "Shop".to_owned(),
+                    is_available: false,
+                    condition: Some("$gold > 10".to_owned()),
+                    tags: vec!["expensive".to_owned()],
+                },
+                DialogueOption {
+                    line: line("line:3", "Leave"),
+                    id: OptionId(1),
+                    destination_node: "Start".to_owned(),
+                    is_available: true,
+                    condition: None,
+                    tags: vec![],
+                },
+            ]),
+        ];
+
+        let json = serde_json::to_string(&events).unwrap();
+        let deserialized: Vec<DialogueEvent> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(events, deserialized);
+    }
+}
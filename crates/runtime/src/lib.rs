@@ -8,16 +8,26 @@
 #![warn(missing_docs, missing_debug_implementations)]
 mod analyser;
 mod command;
+mod command_handlers;
+mod coverage;
 mod dialogue;
 mod dialogue_option;
+mod dialogue_state;
+mod dialogue_step;
 mod events;
 mod language;
 mod line;
 pub mod markup;
 mod pluralization;
+mod substitutions;
+#[cfg(test)]
+mod test_utils;
 mod text_provider;
+mod validation;
 mod variable_storage;
 mod virtual_machine;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use dialogue::Result;
 
@@ -26,15 +36,20 @@ pub mod prelude {
     pub use crate::{
         analyser::*,
         command::*,
+        coverage::*,
         dialogue::{Dialogue, DialogueError},
         dialogue_option::*,
+        dialogue_state::*,
+        dialogue_step::*,
         events::*,
         language::*,
         line::*,
         markup::MarkupParseError,
+        substitutions::*,
         text_provider::*,
+        validation::*,
         variable_storage::*,
     };
-    pub(crate) use crate::{pluralization::*, virtual_machine::*};
+    pub(crate) use crate::{command_handlers::*, pluralization::*, virtual_machine::*};
     pub(crate) use yarnspinner_core::prelude::*;
 }
@@ -37,6 +37,14 @@ pub struct Line {
     pub text: String,
     /// The list of [`MarkupAttribute`] in this parse result.
     pub attributes: Vec<MarkupAttribute>,
+    /// Whether this line is immediately followed by options, i.e. no commands, variable assignments, etc. run in between.
+    /// A UI can use this to present the options right away instead of waiting for the player to continue.
+    /// Always `false` for the lines of [`DialogueOption`]s.
+    ///
+    /// ## Implementation note
+    ///
+    /// Not part of the original implementation, which exposes this as a `lastline` hashtag in the line's metadata.
+    pub is_last_line_before_options: bool,
 }
 
 impl Line {
@@ -67,6 +75,7 @@ impl Line {
     /// #        properties: HashMap::from([("name".to_owned(), "Alice".into())]),
     /// #        source_position: 0,
     /// #    }],
+    /// #    is_last_line_before_options: false,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!(Some("Alice"), line.character_name());
@@ -82,6 +91,7 @@ impl Line {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
     /// #    attributes: vec![],
+    /// #    is_last_line_before_options: false,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert!(line.character_name().is_none());
@@ -119,6 +129,7 @@ impl Line {
     /// #        properties: HashMap::from([("name".to_owned(), "Alice".into())]),
     /// #        source_position: 0,
     /// #    }],
+    /// #    is_last_line_before_options: false,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!("Hello! How are you today?", &line.text_without_character_name());
@@ -134,6 +145,7 @@ impl Line {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
     /// #    attributes: vec![],
+    /// #    is_last_line_before_options: false,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert_eq!("Great, thanks", &line.text_without_character_name());
@@ -200,6 +212,7 @@ impl Line {
                 id: self.id.clone(),
                 text: self.text.to_string(),
                 attributes,
+                is_last_line_before_options: self.is_last_line_before_options,
             };
         }
         let deletion_start = attribute_to_delete.position;
@@ -275,6 +288,7 @@ impl Line {
             id: self.id.clone(),
             text: edited_substring,
             attributes,
+            is_last_line_before_options: self.is_last_line_before_options,
         }
     }
 }
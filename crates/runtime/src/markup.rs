@@ -10,6 +10,7 @@ mod line_parser;
 mod markup_parse_error;
 mod parsed_markup;
 
+pub use self::attribute_marker_processor::MarkupAttributeSpan;
 pub use self::line_parser::{
     CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY, TRIM_WHITESPACE_PROPERTY,
 };
@@ -34,6 +35,56 @@ mod tests {
         assert_eq!(1, markup.attributes[0].length);
     }
 
+    #[test]
+    fn test_character_name_from_markup() {
+        let line = line_parser()
+            .parse_markup("[b]Sally[/b]: Hello there")
+            .unwrap()
+            .as_line();
+        assert_eq!(Some("Sally"), line.character_name());
+        assert_eq!("Hello there", line.text_without_character_name());
+
+        let line = line_parser()
+            .parse_markup("The wind howls [wave]outside[/wave].")
+            .unwrap()
+            .as_line();
+        assert_eq!(None, line.character_name());
+    }
+
+    #[test]
+    fn test_custom_attribute_handlers() {
+        let mut line_parser = line_parser();
+        line_parser.set_marker_processor(
+            "shake",
+            Box::new(CustomTextProcessor::new(std::sync::Arc::new(
+                |span: &MarkupAttributeSpan| {
+                    assert_eq!("shake", span.name);
+                    assert_eq!(
+                        Some(&MarkupValue::Integer(2)),
+                        span.properties.get("strength")
+                    );
+                    assert_eq!((4, 5), (span.position, span.length));
+                    span.text.to_uppercase()
+                },
+            ))),
+        );
+        line_parser.set_marker_processor(
+            "secret",
+            Box::new(CustomTextProcessor::new(std::sync::Arc::new(
+                |_: &MarkupAttributeSpan| String::new(),
+            ))),
+        );
+
+        let markup = line_parser
+            .parse_markup("Oh, [shake strength=2]hello[/shake] there[secret] spy[/secret]!")
+            .unwrap();
+
+        assert_eq!("Oh, HELLO there!", markup.text);
+        assert_eq!("shake", markup.attributes[0].name);
+        assert_eq!(4, markup.attributes[0].position);
+        assert_eq!(5, markup.attributes[0].length);
+    }
+
     #[test]
     fn test_overlapping_attributes() {
         let line = "[a][b][c]X[/b][/a]X[/c]";
@@ -276,6 +327,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_no_markup_passthrough_with_escaped_brackets() {
+        let line = r"\[x\] [nomarkup][b]not bold[/b][/nomarkup] \[y\]";
+        let markup = line_parser().parse_markup(line).unwrap();
+
+        assert_eq!("[x] [b]not bold[/b] [y]", markup.text);
+        assert_eq!(1, markup.attributes.len());
+        assert_eq!("nomarkup", markup.attributes[0].name);
+        assert_eq!(4, markup.attributes[0].position);
+        assert_eq!(15, markup.attributes[0].length);
+    }
+
+    #[test]
+    fn test_escaped_braces_are_not_interpolated() {
+        let line = r"\{not interpolated\}";
+        let markup = line_parser().parse_markup(line).unwrap();
+
+        assert_eq!("{not interpolated}", markup.text);
+        assert!(markup.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let cases = [
+            (r"a \[ b", "a [ b"),
+            (r"a \] b", "a ] b"),
+            (r"a \{ b", "a { b"),
+            (r"a \} b", "a } b"),
+            (r"a \\ b", r"a \ b"),
+            (r"a \# b", "a # b"),
+            (r"a \<\< b \>\>", "a << b >>"),
+            (r"a \/\/ b", "a // b"),
+            (r"a \\[b]c[/b]", r"a \c"),
+            (r"a \n b", r"a \n b"),
+        ];
+        for (line, expected) in cases {
+            let markup = line_parser().parse_markup(line).unwrap();
+            assert_eq!(expected, markup.text, "while parsing {line:?}");
+        }
+    }
+
+    #[test]
+    fn test_trailing_backslash_is_kept() {
+        let line = r"ends with \";
+        let markup = line_parser().parse_markup(line).unwrap();
+
+        assert_eq!(r"ends with \", markup.text);
+    }
+
     #[test]
     fn test_no_markup_mode_parsing() {
         let line = "S [a]S[/a] [nomarkup][a]S;][/a][/nomarkup]";
@@ -359,6 +459,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_number_ordinals() {
+        for (value, expected) in [
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (102, "102nd"),
+        ] {
+            let line = format!(
+                "[ordinal value={value} one=\"%st\" two=\"%nd\" few=\"%rd\" other=\"%th\"/]"
+            );
+
+            let mut line_parser = line_parser();
+            line_parser.set_language_code(Language::from("en"));
+            let markup = line_parser.parse_markup(&line).unwrap();
+
+            assert_eq!(expected, markup.text);
+        }
+    }
+
     fn line_parser() -> LineParser {
         let dialogue_text_processor = Box::new(DialogueTextProcessor::new());
 
@@ -394,6 +519,7 @@ mod tests {
                 id: "test".into(),
                 text: self.text.clone(),
                 attributes: self.attributes.clone(),
+                is_last_line_before_options: false,
             }
         }
     }
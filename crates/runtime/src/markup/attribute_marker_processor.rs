@@ -1,10 +1,14 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/YarnSpinner.Markup/IAttributeMarkerProcessor.cs>
 
-pub(crate) use self::{dialogue_text_processor::*, no_markup_text_processor::*};
+pub use self::custom_text_processor::MarkupAttributeSpan;
+pub(crate) use self::{
+    custom_text_processor::*, dialogue_text_processor::*, no_markup_text_processor::*,
+};
 use crate::markup::MarkupAttributeMarker;
 use crate::prelude::Language;
 use core::fmt::Debug;
 
+mod custom_text_processor;
 mod dialogue_text_processor;
 mod no_markup_text_processor;
 
@@ -0,0 +1,75 @@
+//! Not part of the original implementation, but fills the role of implementing `IAttributeMarkerProcessor` in user code.
+
+use crate::markup::{
+    AttributeMarkerProcessor, MarkupAttributeMarker, MarkupValue, REPLACEMENT_MARKER_CONTENTS,
+};
+use crate::prelude::Language;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// An occurrence of a markup attribute passed to a handler registered with [`Dialogue::add_markup_attribute_handler`](crate::prelude::Dialogue::add_markup_attribute_handler).
+///
+/// For the line `Oh no, [shake strength=2]an earthquake[/shake]!`, the handler for `shake` receives
+/// the name `shake`, the property `strength` with the value `2` and the text `an earthquake`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupAttributeSpan<'a> {
+    /// The name of the attribute.
+    pub name: &'a str,
+    /// The properties of the attribute.
+    pub properties: &'a HashMap<String, MarkupValue>,
+    /// The raw text between the opening and the closing marker. Empty for self-closing markers such as `[wave/]`.
+    pub text: &'a str,
+    /// The position of the attribute in the plain text of the line, measured in characters.
+    pub position: usize,
+    /// The length of [`MarkupAttributeSpan::text`], measured in characters.
+    pub length: usize,
+}
+
+pub(crate) type MarkupAttributeHandler = Arc<dyn Fn(&MarkupAttributeSpan) -> String + Send + Sync>;
+
+/// Calls a user-provided handler to produce the replacement text for a marker.
+#[derive(Clone)]
+pub(crate) struct CustomTextProcessor {
+    handler: MarkupAttributeHandler,
+}
+
+impl CustomTextProcessor {
+    pub(crate) fn new(handler: MarkupAttributeHandler) -> Self {
+        Self { handler }
+    }
+}
+
+impl Debug for CustomTextProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomTextProcessor")
+            .finish_non_exhaustive()
+    }
+}
+
+impl AttributeMarkerProcessor for CustomTextProcessor {
+    fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String {
+        let mut properties = marker.properties.clone();
+        let text = match properties.remove(REPLACEMENT_MARKER_CONTENTS) {
+            Some(MarkupValue::String(text)) => text,
+            _ => String::new(),
+        };
+        let span = MarkupAttributeSpan {
+            name: marker.name.as_deref().unwrap_or_default(),
+            properties: &properties,
+            text: &text,
+            position: marker.position,
+            length: text.graphemes(true).count(),
+        };
+        (self.handler)(&span)
+    }
+
+    fn set_language_code(&mut self, _language_code: Option<Language>) {
+        // no-op
+    }
+
+    fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor> {
+        Box::new(self.clone())
+    }
+}
@@ -6,7 +6,6 @@ use crate::markup::{
     MarkupValue, NoMarkupTextProcessor, TagType,
 };
 use crate::prelude::*;
-use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::{HashMap, VecDeque};
 use unicode_normalization::UnicodeNormalization;
@@ -81,6 +80,16 @@ impl LineParser {
         self
     }
 
+    /// Like [`LineParser::register_marker_processor`], but replaces any processor that was previously registered for the marker name.
+    pub(crate) fn set_marker_processor(
+        &mut self,
+        attribute_name: impl Into<String>,
+        processor: Box<dyn AttributeMarkerProcessor>,
+    ) {
+        self.marker_processors
+            .insert(attribute_name.into(), processor);
+    }
+
     /// Parses a line of text, and produces a [`ParsedMarkup`] containing the processed text
     ///
     /// ## Implementation notes
@@ -103,16 +112,16 @@ impl LineParser {
         while let Some(character) = self.read_next() {
             match character {
                 '\\' => {
-                    // This may be the start of an escaped bracket ("\[" or "\]"). Peek ahead to see if it is.
+                    // This may be the start of an escape sequence, i.e. a backslash followed by one of `[]{}\#<>/`. Peek ahead to see if it is.
                     if let Some(next_character) = self.peek_next() {
-                        if next_character == '[' || next_character == ']' {
+                        if MARKUP_ESCAPABLE_CHARACTERS.contains(&next_character) {
                             // It is! We'll discard this '\', and read the next character as plain text.
                             let character = self.read_next().unwrap();
                             text.push(character);
                             continue;
                         }
                     }
-                    // It wasn't an escaped bracket. Continue on, and parse the '\' as a normal character.
+                    // It wasn't an escape sequence, e.g. because the line ends here. Continue on, and parse the '\' as a normal character.
                     text.push(character);
                 }
                 '[' => {
@@ -188,17 +197,22 @@ impl LineParser {
         }
 
         // Attempt to generate a character attribute from the start
-        // of the string to the first colon
-        let Some(match_) = END_OF_CHARACTER_MARKER.find(&self.input) else {
+        // of the string to the first colon.
+        // The original searches the input, which includes the markers of any markup in the character name.
+        // We search the plain text instead, so that the name and the attribute's range are free of markup.
+        let Some((character_name, spoken_text)) = split_character_name(&text) else {
             return Ok(ParsedMarkup { text, attributes });
         };
 
-        let character_name = self.input[..match_.start()].to_string();
+        let length = text[..text.len() - spoken_text.len()]
+            .graphemes(true)
+            .count();
+        let character_name = character_name.to_string();
 
         let character_attribute = MarkupAttribute {
             name: CHARACTER_ATTRIBUTE.to_string(),
             position: 0,
-            length: match_.end(),
+            length,
             properties: HashMap::from([(
                 CHARACTER_ATTRIBUTE_NAME_PROPERTY.to_string(),
                 character_name.into(),
@@ -737,6 +751,3 @@ pub const CHARACTER_ATTRIBUTE_NAME_PROPERTY: &str = "name";
 /// The name of the property to use to signify that trailing whitespace should be trimmed
 /// if a tag had preceding whitespace or begins the line. This property must be a bool value.
 pub const TRIM_WHITESPACE_PROPERTY: &str = "trimwhitespace";
-
-/// A regular expression that matches a colon followed by optional whitespace.
-static END_OF_CHARACTER_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r":\s*").unwrap());
@@ -32,3 +32,23 @@ impl ParsedMarkup {
         Self::default()
     }
 }
+
+/// The result of parsing a line of marked-up text with [`Dialogue::parse_markup_structured`](crate::prelude::Dialogue::parse_markup_structured).
+/// Contains the same text and attributes a [`Line`](crate::prelude::Line) would.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct MarkupParseResult {
+    /// The original text, with all parsed markers removed.
+    pub text: String,
+    /// The attributes found in the text, with their positions in [`MarkupParseResult::text`].
+    pub attributes: Vec<MarkupAttribute>,
+}
+
+impl From<ParsedMarkup> for MarkupParseResult {
+    fn from(markup: ParsedMarkup) -> Self {
+        Self {
+            text: markup.text,
+            attributes: markup.attributes,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Not part of the original implementation, which only knows positional substitutions like `{0}`.
+
+use crate::prelude::*;
+use crate::Result;
+use std::collections::HashMap;
+
+/// What [`expand_substitutions_named`] does with a placeholder whose name is not in the given map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnknownPlaceholder {
+    /// Fail with [`DialogueError::MissingNamedSubstitution`].
+    #[default]
+    Error,
+    /// Leave the placeholder in the text as it was written.
+    KeepLiteral,
+}
+
+/// Replaces named placeholders such as `{name}` in a text with the corresponding entry in `substitutions`.
+/// This is meant for text that is composed by the game itself, e.g. UI strings shared with translators.
+/// The lines of a Yarn script keep using positional substitutions like `{0}`, which the [`Dialogue`] expands on its own.
+///
+/// A placeholder name consists of ASCII letters, digits and underscores. Braces around anything else, e.g. `{ }`, are left as they are,
+/// as are placeholders preceded by a backslash.
+///
+/// ## Errors
+///
+/// Returns [`DialogueError::MissingNamedSubstitution`] for the first placeholder without a substitution
+/// if `unknown_placeholder` is [`UnknownPlaceholder::Error`].
+pub fn expand_substitutions_named(
+    text: &str,
+    substitutions: &HashMap<String, String>,
+    unknown_placeholder: UnknownPlaceholder,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((position, character)) = chars.next() {
+        match character {
+            '\\' => {
+                expanded.push(character);
+                expanded.extend(chars.next().map(|(_, escaped)| escaped));
+            }
+            '{' => {
+                let name_start = position + 1;
+                let mut name_end = name_start;
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    name_end += c.len_utf8();
+                }
+                let name = &text[name_start..name_end];
+                let is_placeholder =
+                    !name.is_empty() && chars.next_if(|(_, c)| *c == '}').is_some();
+                if !is_placeholder {
+                    expanded.push(character);
+                    expanded.push_str(name);
+                    continue;
+                }
+                match (substitutions.get(name), unknown_placeholder) {
+                    (Some(substitution), _) => expanded.push_str(substitution),
+                    (None, UnknownPlaceholder::KeepLiteral) => {
+                        expanded.push('{');
+                        expanded.push_str(name);
+                        expanded.push('}');
+                    }
+                    (None, UnknownPlaceholder::Error) => {
+                        return Err(DialogueError::MissingNamedSubstitution {
+                            name: name.to_owned(),
+                        })
+                    }
+                }
+            }
+            _ => expanded.push(character),
+        }
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substitutions(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_named_substitutions() {
+        let expanded = expand_substitutions_named(
+            r"{player_name} has {gold} gold, not \{gold\} or { gold }",
+            &substitutions(&[("player_name", "Alice"), ("gold", "5")]),
+            UnknownPlaceholder::Error,
+        )
+        .unwrap();
+
+        assert_eq!(r"Alice has 5 gold, not \{gold\} or { gold }", expanded);
+    }
+
+    #[test]
+    fn reports_missing_name() {
+        let error = expand_substitutions_named(
+            "Hello, {name}",
+            &substitutions(&[]),
+            UnknownPlaceholder::Error,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            DialogueError::MissingNamedSubstitution { name } if name == "name"
+        ));
+    }
+
+    #[test]
+    fn keeps_missing_name_if_requested() {
+        let expanded = expand_substitutions_named(
+            "Hello, {name}, welcome to {place}",
+            &substitutions(&[("place", "Hogwarts")]),
+            UnknownPlaceholder::KeepLiteral,
+        )
+        .unwrap();
+
+        assert_eq!("Hello, {name}, welcome to Hogwarts", expanded);
+    }
+}
@@ -0,0 +1,56 @@
+//! Helpers for tests that run hand-built programs, so that the runtime can be tested without depending on the compiler.
+
+use crate::prelude::*;
+
+pub(crate) fn instruction(opcode: OpCode, operands: Vec<Operand>) -> Instruction {
+    Instruction {
+        opcode: opcode as i32,
+        operands,
+    }
+}
+
+/// A `RunLine` instruction for a line without substitutions.
+pub(crate) fn run_line(line_id: &str) -> Instruction {
+    instruction(OpCode::RunLine, vec![line_id.to_owned().into(), 0.0.into()])
+}
+
+pub(crate) fn node(name: &str, instructions: Vec<Instruction>) -> Node {
+    Node {
+        name: name.to_owned(),
+        instructions,
+        ..Default::default()
+    }
+}
+
+/// A program containing the given nodes, keyed by their names.
+pub(crate) fn program(nodes: impl IntoIterator<Item = Node>) -> Program {
+    Program {
+        nodes: nodes
+            .into_iter()
+            .map(|node| (node.name.clone(), node))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// A text provider with the given `(line ID, text)` pairs as its base language.
+pub(crate) fn text_provider(lines: &[(&str, &str)]) -> StringTableTextProvider {
+    let mut text_provider = StringTableTextProvider::new();
+    text_provider.extend_base_language(
+        lines
+            .iter()
+            .map(|(id, text)| (LineId::from(*id), (*text).to_owned()))
+            .collect(),
+    );
+    text_provider
+}
+
+/// A dialogue backed by a [`MemoryVariableStorage`] that runs `program` and knows the text of `lines`.
+pub(crate) fn dialogue(program: Program, lines: &[(&str, &str)]) -> Dialogue {
+    let mut dialogue = Dialogue::new(
+        Box::new(MemoryVariableStorage::new()),
+        Box::new(text_provider(lines)),
+    );
+    dialogue.add_program(program);
+    dialogue
+}
@@ -4,8 +4,14 @@ use log::error;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::io::Read;
+use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
+mod async_text_provider;
+mod reloadable_text_provider;
+pub use self::{async_text_provider::*, reloadable_text_provider::*};
+
 /// A trait for providing text to a [`Dialogue`](crate::prelude::Dialogue). The default implementation is [`StringTableTextProvider`], which keeps the
 /// text for the base language, i.e. the language the Yarn files are written in, and the text for the currently selected translation in memory.
 ///
@@ -17,6 +23,15 @@ pub trait TextProvider: Debug + Send + Sync {
     fn accept_line_hints(&mut self, line_ids: &[LineId]);
     /// Returns the text for the given [`LineId`]. Will only be called if [`TextProvider::are_lines_available`] returns `true`.
     fn get_text(&self, id: &LineId) -> Option<String>;
+    /// Calls `f` with the text for the given [`LineId`], or does nothing if there is no text for it.
+    ///
+    /// The default implementation calls [`TextProvider::get_text`]. Override it if the text can be lent out instead of cloned,
+    /// since the [`Dialogue`](crate::prelude::Dialogue) only reads it.
+    fn with_text(&self, id: &LineId, f: &mut dyn FnMut(&str)) {
+        if let Some(text) = self.get_text(id) {
+            f(&text);
+        }
+    }
     /// Sets the current language. If `None` is passed, the base language will be used.
     fn set_language(&mut self, language: Option<Language>);
     /// Returns the current language. If `None` is returned, the base language is used.
@@ -34,12 +49,57 @@ pub trait TextProvider: Debug + Send + Sync {
 #[allow(missing_docs)]
 pub type StringTable = HashMap<LineId, String>;
 
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum StringTableCsvError {
+    #[error("The CSV has no \"{column}\" column")]
+    MissingColumn { column: &'static str },
+    #[error("Malformed CSV row at line {line}: {message}")]
+    MalformedRow { line: u64, message: String },
+}
+
+/// Reads a string table from CSV with a header row containing `id` and `text` columns, e.g. a strings file exported for translators.
+/// Other columns are ignored. Fields may be quoted to contain commas, quotes (escaped as `""`) and newlines.
+pub fn read_string_table_csv(
+    reader: impl Read,
+) -> std::result::Result<StringTable, StringTableCsvError> {
+    let to_malformed_row = |error: csv::Error| StringTableCsvError::MalformedRow {
+        line: error.position().map_or(1, |position| position.line()),
+        message: error.to_string(),
+    };
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers = csv_reader.headers().map_err(to_malformed_row)?.clone();
+    let column_index = |column| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .ok_or(StringTableCsvError::MissingColumn { column })
+    };
+    let id_index = column_index("id")?;
+    let text_index = column_index("text")?;
+
+    let mut string_table = StringTable::new();
+    for record in csv_reader.records() {
+        let record = record.map_err(to_malformed_row)?;
+        let id = &record[id_index];
+        if id.is_empty() {
+            return Err(StringTableCsvError::MalformedRow {
+                line: record.position().map_or(1, |position| position.line()),
+                message: "The line ID is empty".to_owned(),
+            });
+        }
+        string_table.insert(id.into(), record[text_index].to_owned());
+    }
+    Ok(string_table)
+}
+
 /// A basic implementation of [`TextProvider`] which keeps the text for the base language,
-/// i.e. the language the Yarn files are written in, and the text for the currently selected translation in memory.
+/// i.e. the language the Yarn files are written in, and the text for any number of translations in memory.
+/// [`TextProvider::set_language`] switches between the translations without having to reload them.
 #[derive(Debug, Clone, Default)]
 pub struct StringTableTextProvider {
     base_language_table: StringTable,
-    translation_table: Option<(Language, StringTable)>,
+    translation_tables: HashMap<Language, StringTable>,
     /// Set to `None` to select base language.
     translation_language: Option<Language>,
 }
@@ -50,25 +110,65 @@ impl StringTableTextProvider {
         Self::default()
     }
 
+    /// Creates a new [`StringTableTextProvider`] with the given strings for the base language.
+    /// Lines that are missing in the current translation fall back to these strings.
+    #[must_use]
+    pub fn with_base_language_table(mut self, string_table: StringTable) -> Self {
+        self.extend_base_language(string_table);
+        self
+    }
+
+    /// Creates a new [`StringTableTextProvider`] whose base language strings are read from CSV as described in [`read_string_table_csv`].
+    pub fn from_csv(reader: impl Read) -> std::result::Result<Self, StringTableCsvError> {
+        Ok(Self::new().with_base_language_table(read_string_table_csv(reader)?))
+    }
+
     /// Adds strings for the base language, i.e. the language that the Yarn files are written in.
     pub fn extend_base_language(&mut self, string_table: HashMap<LineId, String>) {
         self.base_language_table.extend(string_table);
     }
 
-    /// Adds strings for the a specific language. If this is not the language used selected by [`TextProvider::set_language`], the strings will be ignored.
+    /// Adds strings for a specific language, keeping the strings previously added for it.
+    /// They are used while the language is selected by [`TextProvider::set_language`].
     pub fn extend_translation(
         &mut self,
         language: impl Into<Language>,
         string_table: HashMap<LineId, String>,
     ) {
-        let language = language.into();
-        if let Some((current_language, translation_table)) = self.translation_table.as_mut() {
-            if language == *current_language {
-                translation_table.extend(string_table);
-                return;
+        self.translation_tables
+            .entry(language.into())
+            .or_default()
+            .extend(string_table);
+    }
+
+    /// Adds a translation, replacing all strings previously added for that language.
+    /// It is used while the language is selected by [`TextProvider::set_language`].
+    pub fn add_language(
+        &mut self,
+        language: impl Into<Language>,
+        string_table: StringTable,
+    ) -> &mut Self {
+        self.translation_tables
+            .insert(language.into(), string_table);
+        self
+    }
+
+    /// Gets the text of the line in the current language, falling back to the base language.
+    fn lookup(&self, id: &LineId) -> Option<&String> {
+        if let Some(language) = self.translation_language.as_ref() {
+            match self.translation_tables.get(language) {
+                None => {
+                    error!("Didn't find language {language} in translations, falling back to base language.");
+                }
+                Some(translation_table) => {
+                    if let Some(line) = translation_table.get(id) {
+                        return Some(line);
+                    }
+                    error!("No translation found for line {id} in language {language}, falling back to base language.");
+                }
             }
         }
-        self.translation_table.replace((language, string_table));
+        self.base_language_table.get(id)
     }
 }
 
@@ -78,19 +178,13 @@ impl TextProvider for StringTableTextProvider {
     }
 
     fn get_text(&self, id: &LineId) -> Option<String> {
-        if let Some(language) = self.translation_language.as_ref() {
-            if let Some((registered_language, translation_table)) = self.translation_table.as_ref()
-            {
-                if registered_language != language {
-                    error!("Didn't find language {language} in translations, falling back to base language.");
-                } else if let Some(line) = translation_table.get(id) {
-                    return Some(line.clone());
-                } else {
-                    error!("No translation found for line {id} in language {language}, falling back to base language.");
-                }
-            }
+        self.lookup(id).cloned()
+    }
+
+    fn with_text(&self, id: &LineId, f: &mut dyn FnMut(&str)) {
+        if let Some(text) = self.lookup(id) {
+            f(text);
         }
-        self.base_language_table.get(id).cloned()
     }
 
     fn set_language(&mut self, language_code: Option<Language>) {
@@ -105,11 +199,7 @@ impl TextProvider for StringTableTextProvider {
         let Some(language) = self.translation_language.as_ref() else {
             return !self.base_language_table.is_empty();
         };
-        let translation_language = self
-            .translation_table
-            .as_ref()
-            .map(|(language, _)| language);
-        translation_language == Some(language)
+        self.translation_tables.contains_key(language)
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -120,3 +210,118 @@ impl TextProvider for StringTableTextProvider {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_table(lines: &[(&str, &str)]) -> StringTable {
+        lines
+            .iter()
+            .map(|(id, text)| (LineId::from(*id), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn falls_back_to_base_language_for_missing_translations() {
+        let mut text_provider =
+            StringTableTextProvider::new().with_base_language_table(string_table(&[
+                ("line:hello", "Hello"),
+                ("line:bye", "Goodbye"),
+            ]));
+        text_provider.extend_translation("de-CH", string_table(&[("line:hello", "Grüezi")]));
+        text_provider.set_language(Some("de-CH".into()));
+
+        assert_eq!(
+            text_provider.get_text(&LineId::from("line:hello")),
+            Some("Grüezi".to_owned())
+        );
+        assert_eq!(
+            text_provider.get_text(&LineId::from("line:bye")),
+            Some("Goodbye".to_owned())
+        );
+        assert_eq!(text_provider.get_text(&LineId::from("line:unknown")), None);
+    }
+
+    #[test]
+    fn lends_text_in_current_language() {
+        let mut text_provider = StringTableTextProvider::new()
+            .with_base_language_table(string_table(&[("line:hello", "Hello")]));
+        text_provider.extend_translation("de-CH", string_table(&[("line:hello", "Grüezi")]));
+        text_provider.set_language(Some("de-CH".into()));
+
+        let mut lent = Vec::new();
+        text_provider.with_text(&LineId::from("line:hello"), &mut |text| {
+            lent.push(text.to_owned())
+        });
+        text_provider.with_text(&LineId::from("line:unknown"), &mut |text| {
+            lent.push(text.to_owned())
+        });
+
+        assert_eq!(lent, ["Grüezi"]);
+    }
+
+    #[test]
+    fn switches_between_translations() {
+        let mut text_provider = StringTableTextProvider::new()
+            .with_base_language_table(string_table(&[("line:hello", "Hello")]));
+        text_provider
+            .add_language("en-US", string_table(&[("line:hello", "Howdy")]))
+            .add_language("fr-FR", string_table(&[("line:hello", "Bonjour")]));
+        let hello = LineId::from("line:hello");
+
+        text_provider.set_language(Some("en-US".into()));
+        assert!(text_provider.are_lines_available());
+        assert_eq!(text_provider.get_text(&hello), Some("Howdy".to_owned()));
+
+        text_provider.set_language(Some("fr-FR".into()));
+        assert!(text_provider.are_lines_available());
+        assert_eq!(text_provider.get_text(&hello), Some("Bonjour".to_owned()));
+
+        text_provider.set_language(Some("de-CH".into()));
+        assert!(!text_provider.are_lines_available());
+
+        text_provider.set_language(None);
+        assert_eq!(text_provider.get_text(&hello), Some("Hello".to_owned()));
+    }
+
+    #[test]
+    fn reads_string_table_from_csv() {
+        let csv = "id,text,comment
+line:hello,Hello,
+line:list,\"Apples, pears and \"\"plums\"\"\",contains a comma
+line:poem,\"Roses are red
+Violets are blue\",
+";
+        let text_provider = StringTableTextProvider::from_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            text_provider.get_text(&LineId::from("line:hello")),
+            Some("Hello".to_owned())
+        );
+        assert_eq!(
+            text_provider.get_text(&LineId::from("line:list")),
+            Some("Apples, pears and \"plums\"".to_owned())
+        );
+        assert_eq!(
+            text_provider.get_text(&LineId::from("line:poem")),
+            Some("Roses are red\nViolets are blue".to_owned())
+        );
+    }
+
+    #[test]
+    fn reports_malformed_csv_rows() {
+        let csv = "id,text\nline:hello,Hello\nline:bye,Goodbye,extra\n";
+        let result = read_string_table_csv(csv.as_bytes());
+        assert!(matches!(
+            result,
+            Err(StringTableCsvError::MalformedRow { line: 3, .. })
+        ));
+
+        let result = read_string_table_csv("identifier,text\n".as_bytes());
+        assert!(matches!(
+            result,
+            Err(StringTableCsvError::MissingColumn { column: "id" })
+        ));
+    }
+}
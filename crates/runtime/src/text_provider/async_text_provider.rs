@@ -0,0 +1,155 @@
+use super::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+
+/// The future returned by [`AsyncTextProvider::get_text`].
+pub type TextFuture = Pin<Box<dyn Future<Output = Option<String>> + Send>>;
+
+/// A source of text that needs to be awaited, e.g. because the strings are downloaded on demand.
+/// Wrap it in an [`AsyncTextProviderAdapter`] to pass it to a [`Dialogue`](crate::prelude::Dialogue).
+pub trait AsyncTextProvider: Debug + Send + Sync {
+    /// Starts fetching the text for the given [`LineId`] in the given language, or the base language if it is `None`.
+    /// The future resolves to `None` if there is no text for the line.
+    fn get_text(&self, id: &LineId, language: Option<&Language>) -> TextFuture;
+}
+
+/// Adapts an [`AsyncTextProvider`] to the [`TextProvider`] trait.
+///
+/// Lines announced by [`TextProvider::accept_line_hints`] are fetched in the background:
+/// each call to [`TextProvider::are_lines_available`] polls the pending requests without blocking
+/// and returns `true` once all of them have finished, so a game can keep rendering until then.
+/// If a line is requested before its text has arrived, [`TextProvider::get_text`] blocks the current thread until it has.
+///
+/// The futures are polled by the adapter itself, so they must not depend on being run inside a specific async runtime.
+#[derive(Debug)]
+pub struct AsyncTextProviderAdapter<P> {
+    provider: P,
+    language: Option<Language>,
+    requests: Mutex<TextRequests>,
+}
+
+#[derive(Default)]
+struct TextRequests {
+    pending: HashMap<LineId, TextFuture>,
+    finished: HashMap<LineId, Option<String>>,
+}
+
+impl Debug for TextRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextRequests")
+            .field("pending", &self.pending.keys().collect::<Vec<_>>())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl<P: AsyncTextProvider> AsyncTextProviderAdapter<P> {
+    /// Creates a new [`AsyncTextProviderAdapter`] fetching text from `provider`.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            language: None,
+            requests: Mutex::default(),
+        }
+    }
+
+    /// Returns the wrapped [`AsyncTextProvider`].
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    fn request(&self, requests: &mut TextRequests, id: &LineId) {
+        if !requests.pending.contains_key(id) && !requests.finished.contains_key(id) {
+            let future = self.provider.get_text(id, self.language.as_ref());
+            requests.pending.insert(id.clone(), future);
+        }
+    }
+}
+
+impl<P: AsyncTextProvider + 'static> TextProvider for AsyncTextProviderAdapter<P> {
+    fn accept_line_hints(&mut self, line_ids: &[LineId]) {
+        let mut requests = self.requests.lock().unwrap();
+        for id in line_ids {
+            self.request(&mut requests, id);
+        }
+    }
+
+    fn get_text(&self, id: &LineId) -> Option<String> {
+        let future = {
+            let mut requests = self.requests.lock().unwrap();
+            if let Some(text) = requests.finished.get(id) {
+                return text.clone();
+            }
+            self.request(&mut requests, id);
+            requests.pending.remove(id).unwrap()
+        };
+        // Don't hold the lock while blocking, so other threads can still poll and read the other lines
+        let text = block_on(future);
+        self.requests
+            .lock()
+            .unwrap()
+            .finished
+            .entry(id.clone())
+            .or_insert(text)
+            .clone()
+    }
+
+    fn set_language(&mut self, language: Option<Language>) {
+        if language != self.language {
+            *self.requests.lock().unwrap() = TextRequests::default();
+        }
+        self.language = language;
+    }
+
+    fn get_language(&self) -> Option<Language> {
+        self.language.clone()
+    }
+
+    fn are_lines_available(&self) -> bool {
+        let mut requests = self.requests.lock().unwrap();
+        let mut context = Context::from_waker(Waker::noop());
+        let pending = std::mem::take(&mut requests.pending);
+        for (id, mut future) in pending {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(text) => {
+                    requests.finished.insert(id, text);
+                }
+                Poll::Pending => {
+                    requests.pending.insert(id, future);
+                }
+            }
+        }
+        requests.pending.is_empty()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs a future to completion on the current thread, parking it while the future is pending.
+fn block_on<T>(mut future: Pin<Box<dyn Future<Output = T> + Send>>) -> T {
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(value) => return value,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
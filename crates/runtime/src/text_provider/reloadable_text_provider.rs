@@ -0,0 +1,85 @@
+use super::*;
+use std::sync::{Arc, RwLock};
+
+/// A [`TextProvider`] whose strings can be swapped out while a [`Dialogue`](crate::prelude::Dialogue) is running, e.g. while editing translations.
+///
+/// Clones share the same strings, so keep a clone around after passing one to [`Dialogue::new`](crate::prelude::Dialogue::new)
+/// and call [`ReloadableTextProvider::reload`] on it. All lines fetched afterwards use the new strings.
+#[derive(Debug, Clone, Default)]
+pub struct ReloadableTextProvider(Arc<RwLock<StringTableTextProvider>>);
+
+impl ReloadableTextProvider {
+    /// Creates a new [`ReloadableTextProvider`] serving the strings of `text_provider`.
+    pub fn new(text_provider: StringTableTextProvider) -> Self {
+        Self(Arc::new(RwLock::new(text_provider)))
+    }
+
+    /// Replaces all strings of the currently selected language, or of the base language if no language is selected.
+    /// The change is visible to all clones of this text provider.
+    pub fn reload(&self, string_table: StringTable) {
+        let mut text_provider = self.0.write().unwrap();
+        match text_provider.translation_language.clone() {
+            Some(language) => {
+                text_provider.add_language(language, string_table);
+            }
+            None => text_provider.base_language_table = string_table,
+        }
+    }
+}
+
+impl TextProvider for ReloadableTextProvider {
+    fn accept_line_hints(&mut self, line_ids: &[LineId]) {
+        self.0.write().unwrap().accept_line_hints(line_ids);
+    }
+
+    fn get_text(&self, id: &LineId) -> Option<String> {
+        self.0.read().unwrap().get_text(id)
+    }
+
+    fn with_text(&self, id: &LineId, f: &mut dyn FnMut(&str)) {
+        self.0.read().unwrap().with_text(id, f);
+    }
+
+    fn set_language(&mut self, language: Option<Language>) {
+        self.0.write().unwrap().set_language(language);
+    }
+
+    fn get_language(&self) -> Option<Language> {
+        self.0.read().unwrap().get_language()
+    }
+
+    fn are_lines_available(&self) -> bool {
+        self.0.read().unwrap().are_lines_available()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_is_visible_through_existing_clones() {
+        let hello = LineId::from("line:hello");
+        let text_provider = ReloadableTextProvider::new(
+            StringTableTextProvider::new()
+                .with_base_language_table(StringTable::from([(hello.clone(), "Hello".to_owned())])),
+        );
+        let mut injected_clone: Box<dyn TextProvider> = Box::new(text_provider.clone());
+        assert_eq!(injected_clone.get_text(&hello), Some("Hello".to_owned()));
+
+        text_provider.reload(StringTable::from([(hello.clone(), "Hi there".to_owned())]));
+        assert_eq!(injected_clone.get_text(&hello), Some("Hi there".to_owned()));
+
+        injected_clone.set_language(Some("fr-FR".into()));
+        text_provider.reload(StringTable::from([(hello.clone(), "Bonjour".to_owned())]));
+        assert_eq!(injected_clone.get_text(&hello), Some("Bonjour".to_owned()));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+/// Walks every branch of a compiled [`Program`] without running it and reports the problems that would make the
+/// [`Dialogue`] fail or panic once a player takes that branch, e.g. before shipping a project that has no test plans.
+///
+/// The reported problems are:
+/// - calls to functions that are not in `library`, or with a number of parameters the function does not accept
+/// - jumps, detours and options that lead to labels or nodes that don't exist
+/// - instructions that the runtime does not know or no longer supports
+///
+/// Every node is treated as an entry point, since any of them can be passed to [`Dialogue::set_node`].
+/// From there, both outcomes of every condition and every option are followed, but each instruction is only checked once,
+/// which keeps loops between nodes from running forever. Values are not tracked, so only jumps to nodes whose name is a constant
+/// are checked, and lines, commands and variables are left to the [`TextProvider`] and [`VariableStorage`].
+///
+/// Pass [`Dialogue::library`] to include the functions every [`Dialogue`] provides, such as `visited`.
+/// The problems are returned as [`DiagnosisSeverity::Error`]s, ordered by node name and then by position in the node.
+#[must_use]
+pub fn validate_program(program: &Program, library: &Library) -> Vec<Diagnosis> {
+    let mut node_names: Vec<_> = program.nodes.keys().collect();
+    node_names.sort();
+    let mut diagnoses = Vec::new();
+    for node_name in node_names {
+        let node = &program.nodes[node_name];
+        let mut visited = HashSet::new();
+        let mut pending = vec![0];
+        let mut problems = Vec::new();
+        while let Some(program_counter) = pending.pop() {
+            if program_counter >= node.instructions.len() || !visited.insert(program_counter) {
+                continue;
+            }
+            let validator = InstructionValidator {
+                program,
+                library,
+                node,
+                program_counter,
+            };
+            let (successors, problem) = validator.validate();
+            pending.extend(successors);
+            problems.extend(problem.map(|problem| (program_counter, problem)));
+        }
+        problems.sort_by_key(|(program_counter, _)| *program_counter);
+        diagnoses.extend(problems.into_iter().map(|(_, message)| {
+            Diagnosis::new(DiagnosisSeverity::Error, message).with_node_name(node_name)
+        }));
+    }
+    diagnoses
+}
+
+struct InstructionValidator<'a> {
+    program: &'a Program,
+    library: &'a Library,
+    node: &'a Node,
+    program_counter: usize,
+}
+
+impl InstructionValidator<'_> {
+    /// Returns the instructions that can run after this one and the problem with this one, if any.
+    fn validate(&self) -> (Vec<usize>, Option<String>) {
+        let next = self.program_counter + 1;
+        let Ok(opcode) = OpCode::try_from(self.instruction().opcode) else {
+            let opcode = self.instruction().opcode;
+            return (vec![], Some(format!("Unknown instruction {opcode}")));
+        };
+        match opcode {
+            OpCode::JumpTo => self.jump_to_label(0, None),
+            OpCode::JumpIfFalse | OpCode::AddOption => {
+                let operand = if opcode == OpCode::AddOption { 1 } else { 0 };
+                self.jump_to_label(operand, Some(next))
+            }
+            // The destinations of the options on the stack were already followed at `AddOption`
+            OpCode::Jump | OpCode::Stop | OpCode::Return => (vec![], None),
+            OpCode::RunNode => (vec![], self.check_node_on_stack()),
+            OpCode::DetourToNode => (vec![next], self.check_node_on_stack()),
+            OpCode::CallFunc => (vec![next], self.check_function_call()),
+            OpCode::PushNull => (
+                vec![],
+                Some("PushNull is no longer supported, re-compile the source code".to_owned()),
+            ),
+            _ => (vec![next], None),
+        }
+    }
+
+    fn instruction(&self) -> &Instruction {
+        &self.node.instructions[self.program_counter]
+    }
+
+    /// The instruction before this one, which pushed the constant this one works with, if any.
+    fn previous_instruction(&self, opcode: OpCode) -> Option<&Instruction> {
+        let previous = self
+            .node
+            .instructions
+            .get(self.program_counter.checked_sub(1)?)?;
+        (previous.opcode == opcode as i32).then_some(previous)
+    }
+
+    fn string_operand(instruction: &Instruction, index: usize) -> Option<String> {
+        instruction
+            .operands
+            .get(index)
+            .and_then(|operand| String::try_from(operand.clone()).ok())
+    }
+
+    fn jump_to_label(&self, operand: usize, next: Option<usize>) -> (Vec<usize>, Option<String>) {
+        let Some(label) = Self::string_operand(self.instruction(), operand) else {
+            return (
+                next.into_iter().collect(),
+                Some("Jump without a label".to_owned()),
+            );
+        };
+        match self.node.labels.get(&label) {
+            Some(&target) => {
+                let successors = next.into_iter().chain([target as usize]).collect();
+                (successors, None)
+            }
+            None => (
+                next.into_iter().collect(),
+                Some(format!("Jump to unknown label \"{label}\"")),
+            ),
+        }
+    }
+
+    fn check_node_on_stack(&self) -> Option<String> {
+        // Names computed by `<<jump {$expression}>>` are only known at runtime
+        let node_name = self
+            .previous_instruction(OpCode::PushString)
+            .and_then(|push| Self::string_operand(push, 0))?;
+        (!self.program.nodes.contains_key(&node_name))
+            .then(|| format!("Jump to unknown node \"{node_name}\""))
+    }
+
+    fn check_function_call(&self) -> Option<String> {
+        let Some(function_name) = Self::string_operand(self.instruction(), 0) else {
+            return Some("Function call without a function name".to_owned());
+        };
+        let Some(function) = self.library.get(&function_name) else {
+            return Some(format!(
+                "Function \"{function_name}\" is not in the library"
+            ));
+        };
+        // The compiler pushes the number of parameters right before the call
+        let parameter_count =
+            self.previous_instruction(OpCode::PushFloat)
+                .and_then(|push| push.operands.first())
+                .and_then(|operand| f32::try_from(operand.clone()).ok())? as usize;
+        let expected = function.required_parameter_count()..=function.parameter_types().len();
+        (!expected.contains(&parameter_count)).then(|| {
+            format!(
+                "Function \"{function_name}\" expects {} to {} parameters, but is called with {parameter_count}",
+                expected.start(),
+                expected.end()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instruction(opcode: OpCode, operands: Vec<Operand>) -> Instruction {
+        Instruction {
+            opcode: opcode as i32,
+            operands,
+        }
+    }
+
+    #[test]
+    fn reports_unknown_function_in_option_branch() {
+        let start = Node {
+            name: "Start".to_owned(),
+            instructions: vec![
+                // -> Buy a sword
+                //     <<set $roll to dice(6)>>
+                // -> Leave
+                //     <<jump Shop>>
+                instruction(
+                    OpCode::AddOption,
+                    vec![
+                        "line:buy".to_owned().into(),
+                        "L0".to_owned().into(),
+                        0.0.into(),
+                        false.into(),
+                    ],
+                ),
+                instruction(
+                    OpCode::AddOption,
+                    vec![
+                        "line:leave".to_owned().into(),
+                        "L1".to_owned().into(),
+                        0.0.into(),
+                        false.into(),
+                    ],
+                ),
+                instruction(OpCode::ShowOptions, vec![]),
+                instruction(OpCode::Jump, vec![]),
+                // L0
+                instruction(OpCode::PushFloat, vec![6.0.into()]),
+                instruction(OpCode::PushFloat, vec![1.0.into()]),
+                instruction(OpCode::CallFunc, vec!["dice".to_owned().into()]),
+                instruction(OpCode::StoreVariable, vec!["$roll".to_owned().into()]),
+                instruction(OpCode::Pop, vec![]),
+                instruction(OpCode::Return, vec![]),
+                // L1
+                instruction(OpCode::PushString, vec!["Shop".to_owned().into()]),
+                instruction(OpCode::RunNode, vec![]),
+            ],
+            labels: [("L0".to_owned(), 4), ("L1".to_owned(), 10)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let program = Program {
+            nodes: [("Start".to_owned(), start)].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let diagnoses = validate_program(&program, &Library::standard_library());
+        let messages: Vec<_> = diagnoses
+            .iter()
+            .map(|diagnosis| diagnosis.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Function \"dice\" is not in the library",
+                "Jump to unknown node \"Shop\""
+            ]
+        );
+        assert!(diagnoses.iter().all(|diagnosis| {
+            diagnosis.severity == DiagnosisSeverity::Error
+                && diagnosis.node_name.as_deref() == Some("Start")
+        }));
+
+        let mut library = Library::standard_library();
+        library.add_function("dice", |sides: f32| sides);
+        let diagnoses = validate_program(&program, &library);
+        assert_eq!(diagnoses.len(), 1);
+    }
+}
@@ -6,6 +6,15 @@ use std::sync::{Arc, RwLock};
 use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
+#[cfg(feature = "serde")]
+mod file_variable_storage;
+mod null_variable_storage;
+mod read_only_variable_storage;
+#[cfg(feature = "serde")]
+pub use self::file_variable_storage::*;
+pub use self::null_variable_storage::*;
+pub use self::read_only_variable_storage::*;
+
 #[allow(missing_docs)]
 pub type Result<T> = std::result::Result<T, VariableStorageError>;
 
@@ -38,6 +47,16 @@ pub trait VariableStorage: Debug + Send + Sync {
     fn variables(&self) -> HashMap<String, YarnValue>;
     /// Clears all variables in this variable storage.
     fn clear(&mut self);
+    /// Takes a snapshot of all variables in this variable storage, e.g. for a quick-save.
+    fn snapshot(&self) -> VariableSnapshot {
+        VariableSnapshot(self.variables())
+    }
+    /// Replaces all variables in this variable storage with the ones in the snapshot.
+    /// Variables that were set after the snapshot was taken are removed.
+    fn restore(&mut self, snapshot: VariableSnapshot) -> Result<()> {
+        self.clear();
+        self.extend(snapshot.0)
+    }
     /// Gets the [`VariableStorage`] as a trait object.
     /// This allows retrieving the concrete type by downcasting, using the `downcast_ref` method available through the `Any` trait.
     fn as_any(&self) -> &dyn Any;
@@ -61,6 +80,8 @@ pub enum VariableStorageError {
     InvalidVariableName { name: String },
     #[error("Variable name {name} is not defined")]
     VariableNotFound { name: String },
+    #[error("Cannot set {name} because the variable storage is read-only")]
+    ReadOnly { name: String },
     #[error("Internal variable storage error: {error}")]
     InternalError {
         error: Box<dyn std::error::Error + Send + Sync>,
@@ -73,15 +94,145 @@ impl Clone for Box<dyn VariableStorage> {
     }
 }
 
+/// The values of all variables of a [`VariableStorage`] at some point in time, created by [`VariableStorage::snapshot`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableSnapshot(pub HashMap<String, YarnValue>);
+
+impl VariableSnapshot {
+    /// Returns the names of all variables that were added, removed or changed between `earlier` and this snapshot, in no particular order.
+    pub fn changed_since<'a>(&'a self, earlier: &'a VariableSnapshot) -> Vec<&'a str> {
+        let changed_or_added = self
+            .0
+            .iter()
+            .filter(|(name, value)| earlier.0.get(*name) != Some(*value))
+            .map(|(name, _)| name.as_str());
+        let removed = earlier
+            .0
+            .keys()
+            .filter(|name| !self.0.contains_key(*name))
+            .map(|name| name.as_str());
+        changed_or_added.chain(removed).collect()
+    }
+}
+
+/// A callback registered with [`MemoryVariableStorage::on_change`].
+/// Receives the name of the variable, its previous value if it was set before, and its new value.
+pub type VariableChangeCallback = dyn Fn(&str, Option<&YarnValue>, &YarnValue) + Send + Sync;
+
 /// A simple concrete implementation of [`VariableStorage`] that keeps all variables in memory.
-#[derive(Debug, Clone, Default)]
-pub struct MemoryVariableStorage(Arc<RwLock<HashMap<String, YarnValue>>>);
+#[derive(Clone, Default)]
+pub struct MemoryVariableStorage {
+    values: Arc<RwLock<HashMap<String, YarnValue>>>,
+    on_change: Arc<RwLock<Option<Arc<VariableChangeCallback>>>>,
+}
+
+impl Debug for MemoryVariableStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let has_on_change = self.on_change.read().unwrap().is_some();
+        f.debug_struct("MemoryVariableStorage")
+            .field("values", &self.values)
+            .field("on_change", &has_on_change)
+            .finish()
+    }
+}
 
 impl MemoryVariableStorage {
     /// Creates a new empty `MemoryVariableStorage`.
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a new `MemoryVariableStorage` seeded with the given variables, e.g. from a loaded save or a table of default values.
+    /// Fails with a [`VariableStorageError::InvalidVariableName`] if any of the variable names do not start with a `$`.
+    pub fn with_initial_values(values: HashMap<String, YarnValue>) -> Result<Self> {
+        let mut storage = Self::new();
+        VariableStorage::extend(&mut storage, values)?;
+        Ok(storage)
+    }
+
+    /// Sets multiple variables at once from anything that converts into names and [`YarnValue`]s,
+    /// e.g. `storage.extend_values([("$gold", 10), ("$health", 100)])`. Otherwise behaves like [`VariableStorage::extend`].
+    pub fn extend_values<K, V>(&mut self, values: impl IntoIterator<Item = (K, V)>) -> Result<()>
+    where
+        K: Into<String>,
+        V: Into<YarnValue>,
+    {
+        let values = values
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+        VariableStorage::extend(self, values)
+    }
+
+    /// Registers a callback that is called whenever a variable is written through [`VariableStorage::set`] or [`VariableStorage::extend`],
+    /// replacing any previously registered callback. Shallow clones of this storage share the callback.
+    ///
+    /// The callback is called after the storage has released its lock, so it may freely read from or write to the storage.
+    pub fn on_change(
+        &mut self,
+        callback: impl Fn(&str, Option<&YarnValue>, &YarnValue) + Send + Sync + 'static,
+    ) -> &mut Self {
+        *self.on_change.write().unwrap() = Some(Arc::new(callback));
+        self
+    }
+
+    /// Removes the callback registered with [`MemoryVariableStorage::on_change`], if any.
+    pub fn remove_on_change(&mut self) -> &mut Self {
+        *self.on_change.write().unwrap() = None;
+        self
+    }
+
+    /// Returns the value of a variable if it is set and is a [`YarnValue::Number`].
+    /// Unlike the [`TryFrom`] conversions of [`YarnValue`], values of other types are not converted, so this returns `None` for them.
+    pub fn get_number(&self, name: &str) -> Option<f32> {
+        match self.values.read().unwrap().get(name) {
+            Some(YarnValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a variable if it is set and is a [`YarnValue::String`].
+    /// Values of other types are not converted, so this returns `None` for them.
+    pub fn get_string(&self, name: &str) -> Option<String> {
+        match self.values.read().unwrap().get(name) {
+            Some(YarnValue::String(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a variable if it is set and is a [`YarnValue::Boolean`].
+    /// Values of other types are not converted, so this returns `None` for them.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.values.read().unwrap().get(name) {
+            Some(YarnValue::Boolean(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Sets the value of a variable from anything that converts into a [`YarnValue`],
+    /// e.g. `storage.set_value("$gold", 10)`. Otherwise behaves like [`VariableStorage::set`].
+    pub fn set_value(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<YarnValue>,
+    ) -> Result<()> {
+        VariableStorage::set(self, name.into(), value.into())
+    }
+
+    fn notify_change(&self, name: &str, old: Option<&YarnValue>, new: &YarnValue) {
+        // Clone the callback out of the lock so that it can register a new callback without deadlocking
+        let callback = self.on_change.read().unwrap().clone();
+        if let Some(callback) = callback {
+            callback(name, old, new);
+        }
+    }
 }
 
 impl VariableStorage for MemoryVariableStorage {
@@ -91,33 +242,53 @@ impl VariableStorage for MemoryVariableStorage {
 
     fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
         Self::validate_name(&name)?;
-        self.0.write().unwrap().insert(name, value);
+        let old = self
+            .values
+            .write()
+            .unwrap()
+            .insert(name.clone(), value.clone());
+        self.notify_change(&name, old.as_ref(), &value);
         Ok(())
     }
 
     fn get(&self, name: &str) -> Result<YarnValue> {
         Self::validate_name(name)?;
-        self.0.read().unwrap().get(name).cloned().ok_or_else(|| {
-            VariableStorageError::VariableNotFound {
+        self.values
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| VariableStorageError::VariableNotFound {
                 name: name.to_string(),
-            }
-        })
+            })
     }
 
     fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
         for name in values.keys() {
             Self::validate_name(name)?;
         }
-        self.0.write().unwrap().extend(values);
+        let changes: Vec<_> = {
+            let mut variables = self.values.write().unwrap();
+            values
+                .into_iter()
+                .map(|(name, value)| {
+                    let old = variables.insert(name.clone(), value.clone());
+                    (name, old, value)
+                })
+                .collect()
+        };
+        for (name, old, new) in changes {
+            self.notify_change(&name, old.as_ref(), &new);
+        }
         Ok(())
     }
 
     fn variables(&self) -> HashMap<String, YarnValue> {
-        self.0.read().unwrap().clone()
+        self.values.read().unwrap().clone()
     }
 
     fn clear(&mut self) {
-        self.0.write().unwrap().clear();
+        self.values.write().unwrap().clear();
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -141,3 +312,118 @@ impl MemoryVariableStorage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restoring_snapshot_reverts_changes() {
+        let mut storage = MemoryVariableStorage::new();
+        storage.set("$gold".to_owned(), 10.0.into()).unwrap();
+        storage.set("$name".to_owned(), "Sally".into()).unwrap();
+        let snapshot = storage.snapshot();
+
+        storage.set("$gold".to_owned(), 5.0.into()).unwrap();
+        storage.set("$met_sally".to_owned(), true.into()).unwrap();
+        let changed_snapshot = storage.snapshot();
+        let mut changes = changed_snapshot.changed_since(&snapshot);
+        changes.sort_unstable();
+        assert_eq!(changes, vec!["$gold", "$met_sally"]);
+
+        storage.restore(snapshot.clone()).unwrap();
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10.0));
+        assert!(!storage.contains("$met_sally"));
+        assert_eq!(storage.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn calls_on_change_for_every_write() {
+        let changes = Arc::new(RwLock::new(Vec::new()));
+        let mut storage = MemoryVariableStorage::new();
+        let observed_storage = storage.clone();
+        let recorded_changes = changes.clone();
+        storage.on_change(move |name, old, new| {
+            // Reading from the storage inside the callback must not deadlock
+            assert_eq!(observed_storage.get(name).unwrap(), *new);
+            recorded_changes
+                .write()
+                .unwrap()
+                .push((name.to_owned(), old.cloned(), new.clone()));
+        });
+
+        storage.set("$gold".to_owned(), 10.0.into()).unwrap();
+        storage.set("$gold".to_owned(), 5.0.into()).unwrap();
+        storage
+            .extend(HashMap::from([("$name".to_owned(), "Sally".into())]))
+            .unwrap();
+        assert!(storage.set("gold".to_owned(), 1.0.into()).is_err());
+
+        let changes = changes.read().unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0], ("$gold".to_owned(), None, 10.0.into()));
+        assert_eq!(
+            changes[1],
+            ("$gold".to_owned(), Some(10.0.into()), 5.0.into())
+        );
+        assert_eq!(changes[2], ("$name".to_owned(), None, "Sally".into()));
+    }
+
+    #[test]
+    fn typed_getters_return_values_of_matching_type() {
+        let mut storage = MemoryVariableStorage::new();
+        storage.set_value("$gold", 10).unwrap();
+        storage.set_value("$name", "Sally").unwrap();
+        storage.set_value("$met_sally", true).unwrap();
+
+        assert_eq!(storage.get_number("$gold"), Some(10.0));
+        assert_eq!(storage.get_string("$name"), Some("Sally".to_owned()));
+        assert_eq!(storage.get_bool("$met_sally"), Some(true));
+        assert_eq!(storage.get_number("$unknown"), None);
+        assert!(storage.set_value("gold", 1).is_err());
+    }
+
+    #[test]
+    fn typed_getters_return_none_on_type_mismatch() {
+        let mut storage = MemoryVariableStorage::new();
+        storage.set_value("$gold", 10).unwrap();
+        storage.set_value("$number_text", "5").unwrap();
+        storage.set_value("$met_sally", true).unwrap();
+
+        assert_eq!(storage.get_number("$number_text"), None);
+        assert_eq!(storage.get_number("$met_sally"), None);
+        assert_eq!(storage.get_string("$gold"), None);
+        assert_eq!(storage.get_string("$met_sally"), None);
+        assert_eq!(storage.get_bool("$gold"), None);
+        assert_eq!(storage.get_bool("$number_text"), None);
+    }
+
+    #[test]
+    fn seeds_initial_values() {
+        let mut storage = MemoryVariableStorage::with_initial_values(HashMap::from([
+            ("$gold".to_owned(), 10.0.into()),
+            ("$name".to_owned(), "Sally".into()),
+            ("$met_sally".to_owned(), true.into()),
+        ]))
+        .unwrap();
+
+        assert_eq!(storage.get_number("$gold"), Some(10.0));
+        assert_eq!(storage.get_string("$name"), Some("Sally".to_owned()));
+        assert_eq!(storage.get_bool("$met_sally"), Some(true));
+
+        storage
+            .extend_values([("$gold", 5), ("$health", 100)])
+            .unwrap();
+        assert_eq!(storage.get_number("$gold"), Some(5.0));
+        assert_eq!(storage.get_number("$health"), Some(100.0));
+
+        assert!(matches!(
+            MemoryVariableStorage::with_initial_values(HashMap::from([(
+                "gold".to_owned(),
+                10.0.into()
+            )])),
+            Err(VariableStorageError::InvalidVariableName { .. })
+        ));
+        assert!(storage.extend_values([("health", 1)]).is_err());
+    }
+}
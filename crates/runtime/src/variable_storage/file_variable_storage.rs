@@ -0,0 +1,204 @@
+use super::*;
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A [`VariableStorage`] that persists all variables to a JSON file, so that they survive between sessions.
+///
+/// The file is read once when the storage is created and written again after every change.
+/// Changes are written to a temporary file next to it first, which then replaces the file,
+/// so that the saved variables are not lost if the game is closed while they are being written.
+/// Like [`MemoryVariableStorage`], clones share the same variables, so a clone can be passed to [`Dialogue::new`](crate::prelude::Dialogue::new)
+/// while the original is kept around to inspect the variables.
+///
+/// Only available with the `serde` feature.
+#[derive(Debug, Clone)]
+pub struct FileVariableStorage {
+    path: Arc<PathBuf>,
+    variables: MemoryVariableStorage,
+    /// Set if the file could not be loaded nor moved out of the way, so that it is not overwritten.
+    is_read_only: bool,
+    /// Held while saving, so that clones don't write to the temporary file at the same time.
+    saving: Arc<Mutex<()>>,
+}
+
+impl FileVariableStorage {
+    /// Creates a new storage backed by the file at `path`, loading any variables that were previously saved there.
+    ///
+    /// If the file does not exist yet or cannot be read as a set of variables, the storage starts out empty and a warning is logged.
+    /// A file that exists but cannot be read is moved to the same path with `.bak` appended, e.g. `save.json.bak`, so that it is not lost
+    /// when the variables are saved. If that fails too, every change returns an error instead of overwriting the file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let variables = MemoryVariableStorage::new();
+        let mut is_read_only = false;
+        match Self::load(&path) {
+            Ok(loaded_variables) => {
+                variables.values.write().unwrap().extend(loaded_variables);
+            }
+            Err(e) if !path.exists() => {
+                warn!(
+                    "Failed to load variables from {}, starting with no variables instead: {e}",
+                    path.display()
+                );
+            }
+            Err(e) => {
+                let backup_path = with_appended_extension(&path, "bak");
+                match std::fs::rename(&path, &backup_path) {
+                    Ok(()) => warn!(
+                        "Failed to load variables from {}, moved it to {} and starting with no variables instead: {e}",
+                        path.display(),
+                        backup_path.display()
+                    ),
+                    Err(backup_error) => {
+                        warn!(
+                            "Failed to load variables from {} ({e}) and to move it to {} ({backup_error}), \
+                            starting with no variables that will not be saved instead",
+                            path.display(),
+                            backup_path.display()
+                        );
+                        is_read_only = true;
+                    }
+                }
+            }
+        }
+        Self {
+            path: Arc::new(path),
+            variables,
+            is_read_only,
+            saving: Default::default(),
+        }
+    }
+
+    /// The path of the file the variables are saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(
+        path: &Path,
+    ) -> std::result::Result<HashMap<String, YarnValue>, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save(&self) -> Result<()> {
+        let to_internal_error = |error: Box<dyn std::error::Error + Send + Sync>| {
+            VariableStorageError::InternalError { error }
+        };
+        if self.is_read_only {
+            let message = format!(
+                "Refusing to overwrite {}, which could neither be loaded nor backed up",
+                self.path.display()
+            );
+            return Err(to_internal_error(message.into()));
+        }
+        // Also covers taking the snapshot, so that an older one never replaces a newer one
+        let _saving = self.saving.lock().unwrap();
+        let contents = serde_json::to_string_pretty(&self.variables.variables())
+            .map_err(|e| to_internal_error(e.into()))?;
+        let temp_path = with_appended_extension(&self.path, "tmp");
+        std::fs::write(&temp_path, contents)
+            .and_then(|()| std::fs::rename(&temp_path, self.path.as_ref()))
+            .map_err(|e| to_internal_error(e.into()))
+    }
+}
+
+fn with_appended_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+impl VariableStorage for FileVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
+        self.variables.set(name, value)?;
+        self.save()
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        self.variables.get(name)
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        self.variables.extend(values)?;
+        self.save()
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        self.variables.variables()
+    }
+
+    fn clear(&mut self) {
+        self.variables.clear();
+        if let Err(e) = self.save() {
+            warn!(
+                "Failed to save cleared variables to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("yarnspinner_{name}_{}.json", std::process::id()))
+    }
+
+    #[test]
+    fn variables_survive_reconstruction() {
+        let path = temp_path("variables_survive_reconstruction");
+        let _ = std::fs::remove_file(&path);
+
+        let mut storage = FileVariableStorage::new(&path);
+        storage.set("$gold".to_owned(), 10.0.into()).unwrap();
+        storage.set("$name".to_owned(), "Sally".into()).unwrap();
+        storage.set("$met_sally".to_owned(), true.into()).unwrap();
+        drop(storage);
+
+        let storage = FileVariableStorage::new(&path);
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10.0));
+        assert_eq!(storage.get("$name").unwrap(), YarnValue::from("Sally"));
+        assert_eq!(storage.get("$met_sally").unwrap(), YarnValue::from(true));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn starts_empty_on_corrupt_file() {
+        let path = temp_path("starts_empty_on_corrupt_file");
+        let backup_path = with_appended_extension(&path, "bak");
+        std::fs::write(&path, "this is not json").unwrap();
+
+        let mut storage = FileVariableStorage::new(&path);
+        assert!(storage.variables().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&backup_path).unwrap(),
+            "this is not json"
+        );
+
+        storage.set("$gold".to_owned(), 10.0.into()).unwrap();
+        let storage = FileVariableStorage::new(&path);
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10.0));
+        assert!(!with_appended_extension(&path, "tmp").exists());
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&backup_path).unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+/// A [`VariableStorage`] that stores nothing: writes succeed but are discarded, and no variable is ever found.
+///
+/// Reading a variable in a dialogue thus always yields the initial value it was declared with.
+/// This is handy for passes that only care about the lines of a dialogue, such as extracting them for translation,
+/// or for testing the generated code without setting up any state.
+///
+/// ## Example
+///
+/// ```
+/// # use yarnspinner_runtime::prelude::*;
+/// let dialogue = Dialogue::new(
+///     Box::new(NullVariableStorage),
+///     Box::new(StringTableTextProvider::new()),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullVariableStorage;
+
+impl VariableStorage for NullVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(*self)
+    }
+
+    fn set(&mut self, name: String, _value: YarnValue) -> Result<()> {
+        MemoryVariableStorage::validate_name(name)
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        MemoryVariableStorage::validate_name(name)?;
+        Err(VariableStorageError::VariableNotFound {
+            name: name.to_owned(),
+        })
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        values
+            .keys()
+            .try_for_each(MemoryVariableStorage::validate_name)
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        HashMap::new()
+    }
+
+    fn clear(&mut self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn discards_writes() {
+        let mut storage = NullVariableStorage;
+        storage.set("$gold".to_owned(), 10.0.into()).unwrap();
+        storage
+            .extend(HashMap::from([("$name".to_owned(), "Sally".into())]))
+            .unwrap();
+
+        assert!(matches!(
+            storage.get("$gold"),
+            Err(VariableStorageError::VariableNotFound { .. })
+        ));
+        assert!(!storage.contains("$name"));
+        assert!(storage.variables().is_empty());
+        assert!(matches!(
+            storage.set("gold".to_owned(), 10.0.into()),
+            Err(VariableStorageError::InvalidVariableName { .. })
+        ));
+    }
+
+    #[test]
+    fn runs_dialogue_with_initial_values() {
+        let start = node(
+            "Start",
+            vec![
+                // <<set $gold to 5>>
+                instruction(OpCode::PushFloat, vec![5.0.into()]),
+                instruction(OpCode::StoreVariable, vec!["$gold".to_owned().into()]),
+                instruction(OpCode::Pop, vec![]),
+                // You have {$gold} gold.
+                instruction(OpCode::PushVariable, vec!["$gold".to_owned().into()]),
+                instruction(
+                    OpCode::RunLine,
+                    vec!["line:1".to_owned().into(), 1.0.into()],
+                ),
+                instruction(OpCode::Stop, vec![]),
+            ],
+        );
+        let program = Program {
+            initial_values: [("$gold".to_owned(), 10.0.into())].into_iter().collect(),
+            ..program([start])
+        };
+        let text_provider = text_provider(&[("line:1", "You have {0} gold.")]);
+        let mut dialogue = Dialogue::new(Box::new(NullVariableStorage), Box::new(text_provider));
+        dialogue.add_program(program);
+        dialogue.set_node("Start").unwrap();
+
+        let lines: Vec<_> = dialogue
+            .events()
+            .filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(lines, ["You have 10 gold."]);
+    }
+}
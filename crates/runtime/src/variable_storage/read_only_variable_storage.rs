@@ -0,0 +1,124 @@
+use super::*;
+
+/// A [`VariableStorage`] that wraps another one and forwards all reads to it, but never changes its variables.
+///
+/// Use this to run a dialogue against a fixed set of variables, e.g. for replays or analysis.
+/// By default, writes fail with a [`VariableStorageError::ReadOnly`], which stops the dialogue.
+/// Call [`ReadOnlyVariableStorage::ignoring_writes`] to silently discard them instead,
+/// in which case `<<set>>` statements have no effect and the dialogue keeps running.
+/// [`VariableStorage::clear`] never fails and is always ignored.
+#[derive(Debug, Clone)]
+pub struct ReadOnlyVariableStorage<S> {
+    inner: S,
+    ignore_writes: bool,
+}
+
+impl<S: VariableStorage> ReadOnlyVariableStorage<S> {
+    /// Wraps `inner`, making it read-only.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            ignore_writes: false,
+        }
+    }
+
+    /// Makes writes succeed without changing any variables instead of returning an error.
+    #[must_use]
+    pub fn ignoring_writes(mut self) -> Self {
+        self.ignore_writes = true;
+        self
+    }
+
+    /// Returns the wrapped variable storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwraps the wrapped variable storage.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn reject_write<'a>(&self, mut names: impl Iterator<Item = &'a String>) -> Result<()> {
+        match names.next() {
+            Some(name) if !self.ignore_writes => {
+                Err(VariableStorageError::ReadOnly { name: name.clone() })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S: VariableStorage + Clone + 'static> VariableStorage for ReadOnlyVariableStorage<S> {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, _value: YarnValue) -> Result<()> {
+        MemoryVariableStorage::validate_name(&name)?;
+        self.reject_write(std::iter::once(&name))
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        self.inner.get(name)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.inner.contains(name)
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        for name in values.keys() {
+            MemoryVariableStorage::validate_name(name)?;
+        }
+        let mut names: Vec<_> = values.keys().collect();
+        names.sort_unstable();
+        self.reject_write(names.into_iter())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        self.inner.variables()
+    }
+
+    fn clear(&mut self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwards_reads_and_rejects_writes() {
+        let mut inner = MemoryVariableStorage::new();
+        inner.set("$gold".to_owned(), 10.0.into()).unwrap();
+        let mut storage = ReadOnlyVariableStorage::new(inner.clone());
+
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10.0));
+        assert!(matches!(
+            storage.set("$gold".to_owned(), 5.0.into()),
+            Err(VariableStorageError::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            storage.set("gold".to_owned(), 5.0.into()),
+            Err(VariableStorageError::InvalidVariableName { .. })
+        ));
+        storage.clear();
+        assert_eq!(inner.get("$gold").unwrap(), YarnValue::from(10.0));
+
+        let mut storage = storage.ignoring_writes();
+        storage.set("$gold".to_owned(), 5.0.into()).unwrap();
+        storage
+            .extend(HashMap::from([("$name".to_owned(), "Sally".into())]))
+            .unwrap();
+        assert_eq!(inner.variables().len(), 1);
+        assert_eq!(inner.get("$gold").unwrap(), YarnValue::from(10.0));
+    }
+}
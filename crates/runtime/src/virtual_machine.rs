@@ -4,11 +4,12 @@
 //! The `Operand` extensions and the `Operator` enum were moved into upstream crates to make them not depend on the runtime.
 
 pub(crate) use self::{execution_state::*, state::*};
-use crate::markup::{LineParser, ParsedMarkup};
+use crate::markup::{AttributeMarkerProcessor, LineParser, ParsedMarkup};
 use crate::prelude::*;
 use crate::Result;
 use log::*;
 use std::fmt::Debug;
+use std::sync::Arc;
 use yarnspinner_core::prelude::OpCode;
 use yarnspinner_core::prelude::*;
 
@@ -18,15 +19,18 @@ mod state;
 #[derive(Debug)]
 pub(crate) struct VirtualMachine {
     pub(crate) library: Library,
-    pub(crate) program: Option<Program>,
+    pub(crate) program: Option<Arc<Program>>,
     pub(crate) variable_storage: Box<dyn VariableStorage>,
     pub(crate) line_hints_enabled: bool,
+    pub(crate) missing_option_selection: MissingOptionSelection,
+    pub(crate) command_handlers: CommandHandlers,
+    pub(crate) coverage: Option<DialogueCoverage>,
     current_node_name: Option<String>,
     state: State,
     execution_state: ExecutionState,
     current_node: Option<Node>,
     batched_events: Vec<DialogueEvent>,
-    line_parser: LineParser,
+    pub(crate) line_parser: LineParser,
     text_provider: Box<dyn TextProvider>,
     language_code: Option<Language>,
 }
@@ -35,9 +39,14 @@ impl Iterator for VirtualMachine {
     type Item = Vec<DialogueEvent>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.assert_can_continue().is_ok().then(||
-            self.continue_()
-            .unwrap_or_else(|e| panic!("Encountered error while running dialogue through its `Iterator` implementation: {e}")))
+        match self.try_next() {
+            Ok(events) => events,
+            // Like the end of the dialogue, a pending option selection ends the iteration.
+            Err(DialogueError::ContinueOnOptionSelectionError) => None,
+            Err(e) => panic!(
+                "Encountered error while running dialogue through its `Iterator` implementation: {e}"
+            ),
+        }
     }
 }
 
@@ -61,6 +70,9 @@ impl VirtualMachine {
             current_node: Default::default(),
             batched_events: Default::default(),
             line_hints_enabled: Default::default(),
+            missing_option_selection: Default::default(),
+            command_handlers: Default::default(),
+            coverage: Default::default(),
         }
     }
 
@@ -100,6 +112,15 @@ impl VirtualMachine {
         self
     }
 
+    /// Continues the dialogue unless it has no node to run, in which case `Ok(None)` is returned.
+    /// Used by both [`Dialogue::try_next`] and the [`Iterator`] implementation.
+    pub(crate) fn try_next(&mut self) -> Result<Option<Vec<DialogueEvent>>> {
+        match self.continue_() {
+            Err(DialogueError::NoNodeSelectedOnContinue) => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
     /// # Implementation Notes
     /// The original does not reset the state upon calling this. I suspect that's a bug.
     pub(crate) fn stop(&mut self) -> Vec<DialogueEvent> {
@@ -127,17 +148,30 @@ impl VirtualMachine {
         Ok(())
     }
 
+    pub(crate) fn jump_to_node(&mut self, node_name: impl Into<String>) -> Result<()> {
+        self.set_node(node_name)?;
+        // Any options that were presented belong to the node we just left
+        if self.execution_state == ExecutionState::WaitingOnOptionSelection {
+            self.execution_state = ExecutionState::WaitingForContinue;
+        }
+        Ok(())
+    }
+
     fn send_line_hints(&mut self) {
         // Create a list; we will never have more lines and options
         // than total instructions, so that's a decent capacity for
         // the list
         // [sic] TODO: maybe this list could be reused to save on allocations?
 
-        let string_ids: Vec<_> = self
-            .current_node
-            .as_ref()
-            .unwrap()
-            .instructions
+        let string_ids = Self::line_ids_in_node(self.current_node.as_ref().unwrap());
+        self.text_provider.accept_line_hints(&string_ids);
+        self.batched_events
+            .push(DialogueEvent::LineHints(string_ids));
+    }
+
+    /// Returns the IDs of all lines and options that can be shown to the player in the given node.
+    pub(crate) fn line_ids_in_node(node: &Node) -> Vec<LineId> {
+        node.instructions
             .iter()
             // Loop over every instruction and find the ones that run a
             // line or add an option; these are the two instructions
@@ -151,13 +185,10 @@ impl VirtualMachine {
                         // they want to show as their first operand, so
                         // store that
                         let id: String = instruction.operands[0].clone().try_into().unwrap();
-                        LineId(id)
+                        LineId::from(id)
                     })
             })
-            .collect();
-        self.text_provider.accept_line_hints(&string_ids);
-        self.batched_events
-            .push(DialogueEvent::LineHints(string_ids));
+            .collect()
     }
 
     pub(crate) fn pop_line_hints(&mut self) -> Option<Vec<LineId>> {
@@ -195,34 +226,112 @@ impl VirtualMachine {
     /// Exposed via the more idiomatic [`Iterator::next`] implementation.
     ///
     pub(crate) fn continue_(&mut self) -> crate::Result<Vec<DialogueEvent>> {
+        self.select_missing_option()?;
         self.assert_can_continue()?;
         self.set_execution_state(ExecutionState::Running);
 
         while self.execution_state == ExecutionState::Running {
-            let current_node = self.current_node.clone().unwrap();
-            let current_instruction = &current_node.instructions[self.state.program_counter];
-            self.run_instruction(current_instruction)?;
-            // ## Implementation note
-            // The original increments the program counter here, but that leads to intentional underflow on [`OpCode::RunNode`],
-            // so we do the incrementation in [`VirtualMachine::run_instruction`] instead.
-
-            if self.state.program_counter < current_node.instructions.len() {
-                continue;
-            }
+            self.run_next_instruction()?;
+        }
+        Ok(self.take_batched_events())
+    }
+
+    /// Like [`VirtualMachine::continue_`], but runs at most one instruction.
+    pub(crate) fn step(&mut self) -> crate::Result<DialogueStep> {
+        if self.execution_state != ExecutionState::Running {
+            self.select_missing_option()?;
+            self.assert_can_continue()?;
+            self.set_execution_state(ExecutionState::Running);
+        }
+        self.run_next_instruction()?;
+        Ok(DialogueStep {
+            events: self.take_batched_events(),
+            node_name: self.current_node_name.clone(),
+            instruction_pointer: self.state.program_counter,
+        })
+    }
+
+    pub(crate) fn stack(&self) -> Vec<YarnValue> {
+        self.state
+            .stack
+            .iter()
+            .map(|value| value.raw_value.clone())
+            .collect()
+    }
+
+    /// Runs the instructions of `expression` on a fresh stack and returns the value left on top of it.
+    /// The state of the running dialogue is left untouched.
+    pub(crate) fn evaluate(&mut self, expression: &Node) -> crate::Result<YarnValue> {
+        let state = std::mem::take(&mut self.state);
+        let batched_events = std::mem::take(&mut self.batched_events);
+        let result = expression
+            .instructions
+            .iter()
+            .try_for_each(|instruction| self.run_instruction(instruction))
+            .and_then(|()| {
+                self.state
+                    .stack
+                    .pop()
+                    .map(|value| value.raw_value)
+                    .ok_or_else(|| DialogueError::MissingExpressionValue {
+                        name: expression.name.clone(),
+                    })
+            });
+        self.state = state;
+        self.batched_events = batched_events;
+        result
+    }
+
+    fn run_next_instruction(&mut self) -> crate::Result<()> {
+        let current_node = self.current_node.clone().unwrap();
+        let current_instruction = &current_node.instructions[self.state.program_counter];
+        self.run_instruction(current_instruction)?;
+        // ## Implementation note
+        // The original increments the program counter here, but that leads to intentional underflow on [`OpCode::RunNode`],
+        // so we do the incrementation in [`VirtualMachine::run_instruction`] instead.
+
+        // The instruction may have moved execution into another node, e.g. by returning from a detour
+        let instruction_count = self
+            .current_node
+            .as_ref()
+            .map(|node| node.instructions.len());
+        if instruction_count.is_some_and(|count| self.state.program_counter < count) {
+            return Ok(());
+        }
+        // Not in the original: running past the end of a node entered through `<<detour>>` continues its caller
+        if self.return_to_caller()? {
+            return Ok(());
+        }
+
+        self.batched_events
+            .push(DialogueEvent::NodeComplete(current_node.name));
+        self.set_execution_state(ExecutionState::Stopped);
+        self.batched_events.push(DialogueEvent::DialogueComplete);
+        debug!("Run complete.");
+        Ok(())
+    }
 
-            self.batched_events
-                .push(DialogueEvent::NodeComplete(current_node.name.clone()));
-            self.set_execution_state(ExecutionState::Stopped);
-            self.batched_events.push(DialogueEvent::DialogueComplete);
-            debug!("Run complete.");
+    fn take_batched_events(&mut self) -> Vec<DialogueEvent> {
+        let events = std::mem::take(&mut self.batched_events);
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.record(&events);
         }
-        Ok(std::mem::take(&mut self.batched_events))
+        events
     }
 
     pub(crate) fn parse_markup(&mut self, line: &str) -> crate::markup::Result<ParsedMarkup> {
         self.line_parser.parse_markup(line)
     }
 
+    pub(crate) fn set_marker_processor(
+        &mut self,
+        attribute_name: impl Into<String>,
+        processor: Box<dyn AttributeMarkerProcessor>,
+    ) {
+        self.line_parser
+            .set_marker_processor(attribute_name, processor);
+    }
+
     /// Runs a series of tests to see if the [`VirtualMachine`] is in a state where [`VirtualMachine::r#continue`] can be called. Panics if it can't.
     fn assert_can_continue(&self) -> crate::Result<()> {
         if self.current_node.is_none() || self.current_node_name.is_none() {
@@ -267,6 +376,25 @@ impl VirtualMachine {
         Ok(())
     }
 
+    /// Selects the first available option if options are pending and [`MissingOptionSelection::SelectFirstAvailable`] is set.
+    fn select_missing_option(&mut self) -> Result<()> {
+        if self.missing_option_selection != MissingOptionSelection::SelectFirstAvailable
+            || self.execution_state != ExecutionState::WaitingOnOptionSelection
+        {
+            return Ok(());
+        }
+        let first_available = self
+            .state
+            .current_options
+            .iter()
+            .find(|option| option.is_available)
+            .map(|option| option.id);
+        match first_available {
+            Some(option_id) => self.set_selected_option(option_id),
+            None => Ok(()),
+        }
+    }
+
     pub(crate) fn is_active(&self) -> bool {
         self.execution_state != ExecutionState::Stopped
     }
@@ -275,10 +403,40 @@ impl VirtualMachine {
         self.execution_state == ExecutionState::WaitingOnOptionSelection
     }
 
+    pub(crate) fn pending_options(&self) -> Option<&[DialogueOption]> {
+        self.is_waiting_for_option_selection()
+            .then_some(self.state.current_options.as_slice())
+    }
+
     pub(crate) fn current_node(&self) -> Option<String> {
         self.current_node_name.clone()
     }
 
+    pub(crate) fn save_state(&self) -> DialogueState {
+        DialogueState {
+            current_node_name: self.current_node_name.clone(),
+            state: self.state.clone(),
+            execution_state: self.execution_state,
+            variables: self.variable_storage.variables(),
+        }
+    }
+
+    pub(crate) fn restore_state(&mut self, dialogue_state: DialogueState) -> Result<()> {
+        let current_node = dialogue_state
+            .current_node_name
+            .as_deref()
+            .map(|node_name| self.get_node_from_name(node_name).cloned())
+            .transpose()?;
+        self.variable_storage.clear();
+        VariableStorage::extend(self.variable_storage.as_mut(), dialogue_state.variables)?;
+        self.current_node = current_node;
+        self.current_node_name = dialogue_state.current_node_name;
+        self.state = dialogue_state.state;
+        self.execution_state = dialogue_state.execution_state;
+        self.batched_events.clear();
+        Ok(())
+    }
+
     /// ## Implementation note
     ///
     /// Increments the program counter here instead of in `continue_` for cleaner code
@@ -310,7 +468,13 @@ impl VirtualMachine {
                 assert_up_to_date_compiler(instruction.operands.len() >= 2);
 
                 let substitutions = self.pop_substitutions_with_count_at_operand(instruction, 1);
-                let line = self.prepare_line(string_id, &substitutions)?;
+                let mut line = self.prepare_line(string_id, &substitutions)?;
+                // ## Implementation note
+                // Not in the original. The third operand is missing in programs compiled before it was introduced.
+                line.is_last_line_before_options = instruction
+                    .operands
+                    .get(2)
+                    .is_some_and(|_| instruction.read_operand(2));
 
                 self.batched_events.push(DialogueEvent::Line(line));
 
@@ -334,6 +498,11 @@ impl VirtualMachine {
                         command_text.replace(&format!("{{{i}}}"), &substitution)
                     });
                 let command = Command::parse(command_text);
+                if self.command_handlers.try_handle(&command) {
+                    // The command was already taken care of, so there's nothing to wait for
+                    self.state.program_counter += 1;
+                    return Ok(());
+                }
 
                 self.batched_events.push(DialogueEvent::Command(command));
 
@@ -368,6 +537,17 @@ impl VirtualMachine {
                     true
                 };
 
+                // ## Implementation note:
+                // Not in the original. Programs compiled before these operands were introduced lack them.
+                let condition = instruction
+                    .operands
+                    .get(4)
+                    .map(|_| instruction.read_operand::<String>(4))
+                    .filter(|condition| !condition.is_empty());
+                let tags: Vec<String> = (5..instruction.operands.len())
+                    .map(|index| instruction.read_operand(index))
+                    .collect();
+
                 let index = self.state.current_options.len();
                 let node_name = instruction.read_operand(1);
                 // ## Implementation note:
@@ -378,6 +558,8 @@ impl VirtualMachine {
                     id: OptionId(index),
                     destination_node: node_name,
                     is_available: line_condition_passed,
+                    condition,
+                    tags,
                 });
                 self.state.program_counter += 1;
             }
@@ -467,9 +649,10 @@ impl VirtualMachine {
                 // Expect the compiler to have placed the number of parameters
                 // actually passed at the top of the stack.
                 let expected_parameter_count = function.parameter_types().len();
+                let required_parameter_count = function.required_parameter_count();
 
-                assert_eq!(
-                    expected_parameter_count, actual_parameter_count,
+                assert!(
+                    (required_parameter_count..=expected_parameter_count).contains(&actual_parameter_count),
                     "Function {function_name} expected {expected_parameter_count} parameters, but received {actual_parameter_count}",
                 );
 
@@ -492,29 +675,35 @@ impl VirtualMachine {
             OpCode::PushVariable => {
                 // Get the contents of a variable, push that onto the stack.
                 let variable_name: String = instruction.read_operand(0);
-                let loaded_value = self
-                    .variable_storage
-                    .get(&variable_name)
-                    .or_else(|e| {
-                        if let VariableStorageError::VariableNotFound { .. } = e {
-                            // We don't have a value for this. The initial
-                            // value may be found in the program. (If it's
-                            // not, then the variable's value is undefined,
-                            // which isn't allowed.)
-
-                            Ok(self
-                                .program
-                                .as_ref()
-                                .unwrap()
-                                .initial_values
-                                .get(&variable_name)
-                                .unwrap_or_else(|| panic!("The loaded program does not contain an initial value for the variable {variable_name}"))
-                                .clone()
-                                .into())
-                        } else {
-                            Err(e)
-                        }
-                    })?;
+                let smart_variable = self
+                    .program
+                    .as_ref()
+                    .and_then(|program| program.smart_variables.get(&variable_name))
+                    .cloned();
+                // ## Implementation note
+                // Not in the original. Smart variables are not stored anywhere, but computed anew on every read.
+                let loaded_value = if let Some(smart_variable) = smart_variable {
+                    self.evaluate(&smart_variable)?
+                } else {
+                    self.variable_storage
+                        .get(&variable_name)
+                        .or_else(|e| match e {
+                            VariableStorageError::VariableNotFound { .. } => {
+                                // We don't have a value for this. The initial
+                                // value may be found in the program. (If it's
+                                // not, then the variable's value is undefined,
+                                // which isn't allowed.)
+                                // ## Implementation note
+                                // The original throws in that case, we report the variable as not found instead.
+                                self.program
+                                    .as_ref()
+                                    .and_then(|program| program.initial_values.get(&variable_name))
+                                    .map(|initial_value| initial_value.clone().into())
+                                    .ok_or(e)
+                            }
+                            e => Err(e),
+                        })?
+                };
                 self.state.push(loaded_value);
                 self.state.program_counter += 1;
             }
@@ -522,18 +711,27 @@ impl VirtualMachine {
                 // Store the top value on the stack in a variable.
                 let top_value = self.state.peek_value().clone();
                 let variable_name: String = instruction.read_operand(0);
-                self.variable_storage.set(variable_name, top_value.into())?;
+                let new_value: YarnValue = top_value.into();
+                let old_value = if self.variable_storage.contains(&variable_name) {
+                    Some(self.variable_storage.get(&variable_name)?)
+                } else {
+                    None
+                };
+                self.variable_storage
+                    .set(variable_name.clone(), new_value.clone())?;
+                self.batched_events.push(DialogueEvent::VariableChanged {
+                    name: variable_name,
+                    old: old_value,
+                    new: new_value,
+                });
                 self.state.program_counter += 1;
             }
             OpCode::Stop => {
-                // Immediately stop execution, and report that fact.
-                let current_node_name = self.current_node_name.clone().unwrap();
-                self.batched_events
-                    .push(DialogueEvent::NodeComplete(current_node_name));
-                self.batched_events.push(DialogueEvent::DialogueComplete);
-                self.set_execution_state(ExecutionState::Stopped);
-
-                self.state.program_counter += 1;
+                // Immediately stop execution
+                // ## Implementation note:
+                // Not in the original: `<<stop>>` also ends all nodes that are waiting for a `<<detour>>` to return.
+                self.state.call_stack.clear();
+                self.stop_at_current_instruction();
             }
             OpCode::RunNode => {
                 // Run a node
@@ -541,24 +739,70 @@ impl VirtualMachine {
                 // Pop a string from the stack, and jump to a node
                 // with that name.
                 let node_name: String = self.state.pop();
+                // ## Implementation note:
+                // Not in the original. The name may have been computed by `<<jump {$expression}>>`,
+                // so check that the node exists before reporting anything.
+                self.get_node_from_name(&node_name)?;
                 self.batched_events
                     .push(DialogueEvent::NodeComplete(node_name.clone()));
                 self.set_node(&node_name)?;
 
                 // No need to increment the program counter, since otherwise we'd skip the first instruction
             }
+            OpCode::DetourToNode => {
+                // ## Implementation note:
+                // Not in the original. Like `RunNode`, but remembers where to continue once the node returns.
+                let node_name: String = self.state.pop();
+                self.get_node_from_name(&node_name)?;
+                let mut call_stack = std::mem::take(&mut self.state.call_stack);
+                call_stack.push(CallFrame {
+                    node_name: self.current_node_name.clone().unwrap(),
+                    program_counter: self.state.program_counter + 1,
+                });
+                self.set_node(&node_name)?;
+                self.state.call_stack = call_stack;
+            }
+            OpCode::Return => {
+                // ## Implementation note:
+                // Not in the original. Emitted for `<<return>>` and at the end of every node.
+                if !self.return_to_caller()? {
+                    self.stop_at_current_instruction();
+                }
+            }
         }
         Ok(())
     }
 
+    /// Continues the node that ran the innermost pending `<<detour>>` after the detour, if there is one.
+    /// Returns whether there was one.
+    fn return_to_caller(&mut self) -> Result<bool> {
+        let Some(frame) = self.state.call_stack.pop() else {
+            return Ok(false);
+        };
+        let current_node_name = self.current_node_name.clone().unwrap();
+        self.batched_events
+            .push(DialogueEvent::NodeComplete(current_node_name));
+        self.current_node = Some(self.get_node_from_name(&frame.node_name)?.clone());
+        self.current_node_name = Some(frame.node_name);
+        self.state.program_counter = frame.program_counter;
+        Ok(true)
+    }
+
+    /// Immediately stops execution, and reports that fact.
+    fn stop_at_current_instruction(&mut self) {
+        let current_node_name = self.current_node_name.clone().unwrap();
+        self.batched_events
+            .push(DialogueEvent::NodeComplete(current_node_name));
+        self.batched_events.push(DialogueEvent::DialogueComplete);
+        self.set_execution_state(ExecutionState::Stopped);
+
+        self.state.program_counter += 1;
+    }
+
     fn prepare_line(&mut self, string_id: LineId, substitutions: &[String]) -> Result<Line> {
-        let line_text = self.text_provider.get_text(&string_id).ok_or_else(|| {
-            DialogueError::LineProviderError {
-                id: string_id.clone(),
-                language_code: self.language_code.clone(),
-            }
+        let substituted_text = self.with_line_text(&string_id, |line_text| {
+            expand_substitutions(&string_id, line_text, substitutions)
         })?;
-        let substituted_text = expand_substitutions(&line_text, substitutions);
         let markup = self
             .parse_markup(&substituted_text)
             .map_err(DialogueError::MarkupParseError)?;
@@ -566,10 +810,42 @@ impl VirtualMachine {
             id: string_id,
             text: markup.text,
             attributes: markup.attributes,
+            is_last_line_before_options: false,
         };
         Ok(line)
     }
 
+    /// Writes the text of the line with the given ID into `buffer`, with its substitutions expanded but its markup left untouched.
+    /// The buffer is cleared first, so its allocation can be reused across lines.
+    pub(crate) fn compose_line_text(
+        &self,
+        line_id: &LineId,
+        substitutions: &[impl AsRef<str>],
+        buffer: &mut String,
+    ) -> Result<()> {
+        buffer.clear();
+        self.with_line_text(line_id, |line_text| {
+            expand_substitutions_into(line_id, line_text, substitutions, buffer)
+        })
+    }
+
+    /// Runs `f` on the text of the line with the given ID, borrowing it from the text provider instead of cloning it where possible.
+    fn with_line_text<T>(&self, line_id: &LineId, f: impl FnOnce(&str) -> Result<T>) -> Result<T> {
+        let mut f = Some(f);
+        let mut result = None;
+        self.text_provider.with_text(line_id, &mut |line_text| {
+            if let Some(f) = f.take() {
+                result = Some(f(line_text));
+            }
+        });
+        result.unwrap_or_else(|| {
+            Err(DialogueError::LineProviderError {
+                id: line_id.clone(),
+                language_code: self.language_code.clone(),
+            })
+        })
+    }
+
     /// Looks up the instruction number for a named label in the current node.
     ///
     /// # Panics
@@ -622,15 +898,167 @@ fn assert_up_to_date_compiler(predicate: bool) {
 ///
 /// This method replaces substitution markers
 /// -  for example, `{0}` - with the corresponding entry in `substitutions`.
-/// If `test` contains a substitution marker whose
-/// index is not present in `substitutions`, it is
-/// ignored.
-#[must_use]
-fn expand_substitutions(text: &str, substitutions: &[String]) -> String {
-    substitutions
-        .iter()
-        .enumerate()
-        .fold(text.to_owned(), |text, (i, substitution)| {
-            text.replace(&format!("{{{i}}}",), substitution)
-        })
+///
+/// Markers preceded by a backslash are escaped and left as they are.
+///
+/// ## Implementation notes
+///
+/// The original implementation silently ignores markers without a substitution and substitutions without a marker.
+/// Both point to a text that does not fit the compiled line, e.g. an outdated translation, so we return an error instead.
+///
+/// Escape sequences are resolved later by the markup parser. So that a backslash at the end of a substitution
+/// does not escape the text that follows it, backslashes in substitutions are escaped themselves.
+fn expand_substitutions(line_id: &LineId, text: &str, substitutions: &[String]) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    expand_substitutions_into(line_id, text, substitutions, &mut expanded)?;
+    Ok(expanded)
+}
+
+/// Like [`expand_substitutions`], but appends the result to `expanded` so that its allocation can be reused.
+fn expand_substitutions_into(
+    line_id: &LineId,
+    text: &str,
+    substitutions: &[impl AsRef<str>],
+    expanded: &mut String,
+) -> Result<()> {
+    let mut used = vec![false; substitutions.len()];
+    let mut chars = text.char_indices().peekable();
+    while let Some((position, character)) = chars.next() {
+        match character {
+            '\\' => {
+                expanded.push(character);
+                expanded.extend(chars.next().map(|(_, escaped)| escaped));
+            }
+            '{' => {
+                let digits_start = position + 1;
+                let mut digits_end = digits_start;
+                while let Some((_, digit)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    digits_end += digit.len_utf8();
+                }
+                let digits = &text[digits_start..digits_end];
+                let index = (!digits.is_empty())
+                    .then(|| chars.next_if(|(_, c)| *c == '}'))
+                    .flatten()
+                    .and_then(|_| digits.parse::<usize>().ok());
+                let Some(index) = index else {
+                    // Not a marker after all, so keep the text as it was
+                    expanded.push(character);
+                    expanded.push_str(digits);
+                    continue;
+                };
+                let substitution =
+                    substitutions
+                        .get(index)
+                        .ok_or_else(|| DialogueError::MissingSubstitution {
+                            id: line_id.clone(),
+                            index,
+                            substitution_count: substitutions.len(),
+                        })?;
+                for character in substitution.as_ref().chars() {
+                    if character == '\\' {
+                        expanded.push('\\');
+                    }
+                    expanded.push(character);
+                }
+                used[index] = true;
+            }
+            _ => expanded.push(character),
+        }
+    }
+    if let Some(index) = used.iter().position(|used| !used) {
+        return Err(DialogueError::UnusedSubstitution {
+            id: line_id.clone(),
+            index,
+            substitution_count: substitutions.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substitutions(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_substitutions() {
+        let line_id = LineId::from("line:test");
+        let expanded = expand_substitutions(
+            &line_id,
+            r"{1} has {0} gold, not \{0\}",
+            &substitutions(&["5", "Alice"]),
+        )
+        .unwrap();
+
+        assert_eq!(r"Alice has 5 gold, not \{0\}", expanded);
+    }
+
+    #[test]
+    fn escapes_backslashes_in_substitutions() {
+        let line_id = LineId::from("line:test");
+        let expanded =
+            expand_substitutions(&line_id, r"{0}[b]{1}", &substitutions(&["C:\\", r"\"])).unwrap();
+
+        assert_eq!(r"C:\\[b]\\", expanded);
+    }
+
+    #[test]
+    fn reports_too_few_substitutions() {
+        let line_id = LineId::from("line:test");
+        let error =
+            expand_substitutions(&line_id, "{0} and {1}", &substitutions(&["Alice"])).unwrap_err();
+
+        assert!(matches!(
+            error,
+            DialogueError::MissingSubstitution {
+                index: 1,
+                substitution_count: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn reports_too_many_substitutions() {
+        let line_id = LineId::from("line:test");
+        let error = expand_substitutions(&line_id, "Only {1}", &substitutions(&["Alice", "Bob"]))
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            DialogueError::UnusedSubstitution {
+                index: 0,
+                substitution_count: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn expanding_into_a_buffer_keeps_its_capacity() {
+        let line_id = LineId::from("line:test");
+        let mut buffer = String::new();
+        expand_substitutions_into(&line_id, "{0} has {1} gold", &["Alice", "100"], &mut buffer)
+            .unwrap();
+        let capacity = buffer.capacity();
+
+        for (text, substitutions, expected) in [
+            ("{0} walks in", ["Bob"].as_slice(), "Bob walks in"),
+            (r"\{0\} is {0}", ["Eve"].as_slice(), r"\{0\} is Eve"),
+            (
+                "{not a marker} café {",
+                [].as_slice(),
+                "{not a marker} café {",
+            ),
+        ] {
+            buffer.clear();
+            expand_substitutions_into(&line_id, text, substitutions, &mut buffer).unwrap();
+
+            assert_eq!(expected, buffer);
+            assert_eq!(capacity, buffer.capacity());
+        }
+    }
 }
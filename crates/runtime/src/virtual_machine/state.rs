@@ -22,6 +22,26 @@ pub(crate) struct State {
 
     /// The value stack.
     pub(crate) stack: Vec<InternalValue>,
+
+    /// Where to continue once the nodes entered through `<<detour>>` return, innermost last.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) call_stack: Vec<CallFrame>,
+}
+
+/// The position to return to after a `<<detour>>`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub(crate) struct CallFrame {
+    /// The node that ran the `<<detour>>`.
+    pub(crate) node_name: String,
+    /// The instruction after the `<<detour>>`.
+    pub(crate) program_counter: usize,
 }
 
 impl State {
@@ -0,0 +1,106 @@
+//! Bindings for running a [`Dialogue`] in the browser through [`wasm_bindgen`](mod@wasm_bindgen). Only available with the `wasm` feature.
+//!
+//! The program is compiled ahead of time and passed in as the bytes produced by [`Program::to_bytes`].
+//! Lines are looked up in a string table given as CSV, as produced by
+//! [`Compilation::string_table_to_csv`](https://docs.rs/yarnspinner_compiler/latest/yarnspinner_compiler/prelude/struct.Compilation.html#method.string_table_to_csv)
+//! of the compiler crate. See [`read_string_table_csv`] for the columns that are read.
+//! Variables are kept in a [`MemoryVariableStorage`].
+//!
+//! ```js
+//! import init, { JsDialogue } from "./pkg/my_game.js";
+//!
+//! await init();
+//! const program = new Uint8Array(await (await fetch("dialogue.yarnc")).arrayBuffer());
+//! const dialogue = new JsDialogue(program);
+//! dialogue.setStringTableCsv(await (await fetch("dialogue.csv")).text());
+//! dialogue.setNode("Start");
+//!
+//! for (let event = dialogue.next(); event !== "DialogueComplete"; event = dialogue.next()) {
+//!     if (event.Line) {
+//!         console.log(event.Line.text);
+//!     } else if (event.Options) {
+//!         event.Options.forEach((option, index) => console.log(`${index}: ${option.line.text}`));
+//!         dialogue.selectOption(0);
+//!     }
+//! }
+//! ```
+
+use crate::prelude::*;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// A [`Dialogue`] with an API that can cross the JavaScript boundary.
+///
+/// Events are returned one by one by [`JsDialogue::next`] as the JavaScript representation of the serialized [`DialogueEvent`],
+/// e.g. `{ Line: { id: "line:1", text: "Hello", attributes: [] } }` or `"DialogueComplete"`.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct JsDialogue {
+    dialogue: Dialogue,
+    text_provider: ReloadableTextProvider,
+    pending_events: VecDeque<DialogueEvent>,
+}
+
+#[wasm_bindgen]
+impl JsDialogue {
+    /// Creates a new dialogue running the program encoded in `program` by [`Program::to_bytes`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(program: &[u8]) -> std::result::Result<JsDialogue, JsError> {
+        let program = Program::from_bytes(program)?;
+        let text_provider = ReloadableTextProvider::default();
+        let mut dialogue = Dialogue::new(
+            Box::new(MemoryVariableStorage::new()),
+            Box::new(text_provider.clone()),
+        );
+        dialogue.add_program(program);
+        Ok(Self {
+            dialogue,
+            text_provider,
+            pending_events: VecDeque::new(),
+        })
+    }
+
+    /// Replaces the strings used for lines and options with the ones in `csv`, in the format described in [`read_string_table_csv`].
+    #[wasm_bindgen(js_name = setStringTableCsv)]
+    pub fn set_string_table_csv(&mut self, csv: &str) -> std::result::Result<(), JsError> {
+        let string_table = read_string_table_csv(csv.as_bytes())?;
+        self.text_provider.reload(string_table);
+        Ok(())
+    }
+
+    /// Starts the dialogue at the node with the given name. See [`Dialogue::set_node`].
+    #[wasm_bindgen(js_name = setNode)]
+    pub fn set_node(&mut self, node_name: &str) -> std::result::Result<(), JsError> {
+        self.pending_events.clear();
+        self.dialogue.set_node(node_name)?;
+        Ok(())
+    }
+
+    /// Returns the next [`DialogueEvent`], continuing the dialogue if all previous events have been returned.
+    ///
+    /// Fails if the dialogue is waiting for [`JsDialogue::select_option`] to be called.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> std::result::Result<JsValue, JsError> {
+        if self.pending_events.is_empty() {
+            let events = self.dialogue.continue_()?;
+            self.pending_events.extend(events);
+        }
+        match self.pending_events.pop_front() {
+            Some(event) => Ok(serde_wasm_bindgen::to_value(&event)?),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Selects the option with the given index in the most recent `Options` event. See [`Dialogue::set_selected_option`].
+    #[wasm_bindgen(js_name = selectOption)]
+    pub fn select_option(&mut self, index: usize) -> std::result::Result<(), JsError> {
+        self.dialogue.set_selected_option(OptionId(index))?;
+        Ok(())
+    }
+
+    /// Returns whether the dialogue is waiting for [`JsDialogue::select_option`] to be called.
+    #[wasm_bindgen(js_name = isWaitingForOptionSelection)]
+    pub fn is_waiting_for_option_selection(&self) -> bool {
+        self.dialogue.is_waiting_for_option_selection()
+    }
+}
@@ -0,0 +1,67 @@
+use crate::compiler::{Compiler, CompilerError, Declaration};
+use crate::core::{Type, YarnValue};
+use crate::runtime::{Dialogue, DialogueError};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Extends [`Dialogue`] with the ability to evaluate Yarn expressions that are not part of any node,
+/// which needs both the compiler and the runtime.
+pub trait DialogueExpressionExt {
+    /// Compiles and evaluates a single expression such as `$gold + 5 > 10`, e.g. for a debug console.
+    ///
+    /// The expression is type-checked against the variables of the loaded [`Program`](crate::core::Program) and the [`VariableStorage`](crate::runtime::VariableStorage),
+    /// and may call any function of the dialogue's [`Library`](crate::core::Library).
+    /// It is then run against the live [`VariableStorage`](crate::runtime::VariableStorage) without affecting the running dialogue.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExpressionError::Compilation`] if the expression is invalid, e.g. because the type of an unknown variable cannot be inferred,
+    /// and [`ExpressionError::Evaluation`] if it reads a variable without a value or calls a missing function.
+    fn evaluate_expression(&mut self, expression: &str) -> Result<YarnValue, ExpressionError>;
+}
+
+impl DialogueExpressionExt for Dialogue {
+    fn evaluate_expression(&mut self, expression: &str) -> Result<YarnValue, ExpressionError> {
+        let mut values: HashMap<String, YarnValue> = self
+            .program()
+            .map(|program| {
+                program
+                    .initial_values
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone().into()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        values.extend(self.variable_storage().variables());
+        let smart_variables: Vec<_> = self
+            .program()
+            .map(|program| program.smart_variables.values().cloned().collect())
+            .unwrap_or_default();
+        for smart_variable in smart_variables {
+            // Smart variables have no stored value, so computing their current one is the easiest way to learn their type
+            let value = self.evaluate(&smart_variable)?;
+            values.insert(smart_variable.name, value);
+        }
+
+        let mut compiler = Compiler::new();
+        compiler.extend_library(self.library().clone());
+        for (name, value) in values {
+            compiler.declare_variable(
+                Declaration::new(name, Type::from(&value)).with_default_value(value),
+            );
+        }
+        let node = compiler.compile_expression(expression)?;
+        Ok(self.evaluate(&node)?)
+    }
+}
+
+/// An error returned by [`DialogueExpressionExt::evaluate_expression`].
+#[derive(Error, Debug)]
+pub enum ExpressionError {
+    /// The expression could not be compiled.
+    #[error(transparent)]
+    Compilation(#[from] CompilerError),
+    /// The expression failed while running.
+    #[error(transparent)]
+    Evaluation(#[from] DialogueError),
+}
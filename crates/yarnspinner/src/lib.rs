@@ -7,6 +7,8 @@
 
 pub use log;
 
+mod expression;
+
 pub mod prelude {
     //! Everything you need to get started using Yarn Spinner.
     pub use crate::compiler::{
@@ -19,9 +21,10 @@ pub mod prelude {
     };
     pub use crate::runtime::{
         Command as YarnCommand, CompiledProgramAnalyser as YarnAnalyser,
-        Context as YarnAnalysisContext, Dialogue, DialogueError, DialogueEvent, DialogueOption,
-        Language, Line as YarnLine, MarkupAttribute, MarkupValue, OptionId,
-        Result as YarnRuntimeResult, StringTable, TextProvider, VariableStorage,
+        Context as YarnAnalysisContext, Dialogue, DialogueError, DialogueEvent,
+        DialogueExpressionExt, DialogueOption, Language, Line as YarnLine, MarkupAttribute,
+        MarkupValue, OptionId, Result as YarnRuntimeResult, StringTable, TextProvider,
+        VariableStorage,
     };
 }
 
@@ -29,9 +32,9 @@ pub mod core {
     //! Core types and traits that are used by both the compiler and runtime.
     pub use yarnspinner_core::prelude::{
         yarn_fn_type, yarn_library, Header, Instruction, IntoYarnValueFromNonYarnValue,
-        InvalidOpCodeError, Library, LineId, Node, Position, Program, Type, UntypedYarnFn, YarnFn,
-        YarnFnParam, YarnFnParamItem, YarnValue, YarnValueCastError, YarnValueWrapper,
-        YarnValueWrapperIter,
+        InvalidOpCodeError, Library, LineId, Node, Position, Program, ProgramCombineError,
+        ProgramDecodeError, RoundingMode, Type, UntypedYarnFn, YarnFn, YarnFnParam,
+        YarnFnParamItem, YarnValue, YarnValueCastError, YarnValueWrapper, YarnValueWrapperIter,
     };
 }
 pub mod compiler {
@@ -42,10 +45,13 @@ pub mod compiler {
 
 pub mod runtime {
     //! Types and traits used by the runtime, in particular the [`Dialogue`] struct.
+    pub use crate::expression::{DialogueExpressionExt, ExpressionError};
     pub use yarnspinner_runtime::markup::{
-        MarkupAttribute, MarkupParseError, MarkupValue, CHARACTER_ATTRIBUTE,
-        CHARACTER_ATTRIBUTE_NAME_PROPERTY, TRIM_WHITESPACE_PROPERTY,
+        MarkupAttribute, MarkupAttributeSpan, MarkupParseError, MarkupParseResult, MarkupValue,
+        CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY, TRIM_WHITESPACE_PROPERTY,
     };
     pub use yarnspinner_runtime::prelude::*;
+    #[cfg(feature = "wasm")]
+    pub use yarnspinner_runtime::wasm::JsDialogue;
     pub use yarnspinner_runtime::Result;
 }
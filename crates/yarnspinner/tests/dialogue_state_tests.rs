@@ -0,0 +1,105 @@
+use test_base::prelude::*;
+use yarnspinner::compiler::*;
+use yarnspinner::core::*;
+use yarnspinner::runtime::*;
+
+mod test_base;
+
+const SOURCE: &str = "title: Start
+---
+<<declare $coins = 0>>
+<<set $coins to $coins + 5>>
+Welcome, traveller. #line:welcome
+<<play_sound bell>>
+-> Buy a map for 3 coins #line:buy
+    <<set $coins to $coins - 3>>
+    <<jump Map>>
+-> Leave #line:leave
+You leave with {$coins} coins. #line:leave_result
+===
+title: Map
+---
+You bought a map and have {$coins} coins left. #line:bought
+===
+";
+
+fn test_base() -> TestBase {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: SOURCE.to_string(),
+    };
+    let compilation = Compiler::new().add_file(file).compile().unwrap();
+    TestBase::new().with_compilation(compilation)
+}
+
+/// Runs the dialogue until it waits for an option selection or completes,
+/// and returns a description of every event it emitted.
+fn run_until_options(dialogue: &mut Dialogue) -> Vec<String> {
+    let mut descriptions = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            descriptions.push(match event {
+                DialogueEvent::Line(line) => format!("line: {}", line.text),
+                DialogueEvent::Options(options) => {
+                    let texts: Vec<_> =
+                        options.into_iter().map(|option| option.line.text).collect();
+                    format!("options: {}", texts.join(", "))
+                }
+                DialogueEvent::Command(command) => format!("command: {}", command.raw),
+                other => format!("{other:?}"),
+            });
+        }
+        if dialogue.is_waiting_for_option_selection() {
+            break;
+        }
+    }
+    descriptions
+}
+
+#[test]
+fn test_restored_dialogue_resumes_mid_option_selection() {
+    let mut uninterrupted = test_base();
+    let uninterrupted_dialogue = &mut uninterrupted.dialogue;
+    uninterrupted_dialogue.set_node("Start").unwrap();
+    run_until_options(uninterrupted_dialogue);
+    uninterrupted_dialogue
+        .set_selected_option(OptionId(0))
+        .unwrap();
+    let expected = run_until_options(uninterrupted_dialogue);
+
+    let mut original = test_base();
+    let original_dialogue = &mut original.dialogue;
+    original_dialogue.set_node("Start").unwrap();
+    run_until_options(original_dialogue);
+    assert!(original_dialogue.is_waiting_for_option_selection());
+    let state = original_dialogue.save_state();
+    assert_eq!(state.current_node(), Some("Start"));
+    assert_eq!(state.variables()["$coins"], YarnValue::from(5.0));
+
+    let mut restored = test_base();
+    let restored_dialogue = &mut restored.dialogue;
+    restored_dialogue.restore_state(state).unwrap();
+    assert!(restored_dialogue.is_waiting_for_option_selection());
+    assert_eq!(restored_dialogue.current_node().as_deref(), Some("Start"));
+    restored_dialogue.set_selected_option(OptionId(0)).unwrap();
+    let actual = run_until_options(restored_dialogue);
+
+    assert_eq!(expected, actual);
+    assert!(actual.contains(&"line: You bought a map and have 2 coins left.".to_owned()));
+    assert_eq!(
+        restored_dialogue.variable_storage().get("$coins").unwrap(),
+        YarnValue::from(2.0)
+    );
+}
+
+#[test]
+fn test_restoring_state_of_unknown_node_fails() {
+    let mut original = test_base();
+    original.dialogue.set_node("Start").unwrap();
+    run_until_options(&mut original.dialogue);
+    let state = original.dialogue.save_state();
+
+    let mut restored = TestBase::new();
+    let result = restored.dialogue.restore_state(state);
+    assert!(result.is_err());
+}
@@ -3,8 +3,10 @@
 //! ## Implementation notes
 //! `TestDumpingCode` was not ported because `GetByteCode` is not used by a user directly and thus was not implemented at all.
 
+use std::sync::{Arc, Mutex};
 use test_base::prelude::*;
 use yarnspinner::compiler::*;
+use yarnspinner::core::*;
 use yarnspinner::runtime::*;
 
 mod test_base;
@@ -245,6 +247,36 @@ fn test_function_argument_type_inference() {
     assert!(!bool_value);
 }
 
+#[test]
+fn test_interpolation_containing_braces_spans_the_whole_line() {
+    let test_base = TestBase::new().extend_library(|library| {
+        library.add_function("format", |template: &str, value: f32| {
+            template.replacen("{}", &value.to_string(), 1)
+        });
+    });
+    let source = "\
+    <<declare $x = 5>>
+    Value: { format(\"<{}>\", $x) } and { \"}\" } done
+    ";
+
+    let result = Compiler::from_test_source(source)
+        .extend_library(test_base.dialogue.library().clone())
+        .compile()
+        .unwrap();
+
+    let line_texts: Vec<_> = result
+        .string_table
+        .values()
+        .map(|info| info.text.as_str())
+        .collect();
+    assert_eq!(line_texts, vec!["Value: {0} and {1} done"]);
+
+    test_base
+        .with_compilation(result)
+        .with_test_plan(TestPlan::new().expect_line("Value: <5> and } done"))
+        .run_standard_testcase();
+}
+
 #[test]
 fn test_selecting_option_from_inside_option_callback() {
     let result = Compiler::from_test_source("-> option 1\n->option 2\nfinal line\n")
@@ -300,8 +332,924 @@ fn test_selecting_option_from_inside_option_callback() {
                 DialogueEvent::Command(_)
                 | DialogueEvent::NodeComplete(_)
                 | DialogueEvent::NodeStart(_)
-                | DialogueEvent::LineHints(_) => {}
+                | DialogueEvent::LineHints(_)
+                | DialogueEvent::VariableChanged { .. } => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn test_variable_changes_are_reported() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: create_test_node(
+            "<<declare $gold = 0>>
+<<set $gold to 10>>
+<<set $gold to $gold + 5>>",
+        ),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut changes = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            if let DialogueEvent::VariableChanged { name, old, new } = event {
+                changes.push((name, old, new));
+            }
+        }
+    }
+
+    assert_eq!(
+        changes,
+        vec![
+            ("$gold".to_owned(), None, YarnValue::from(10.0)),
+            (
+                "$gold".to_owned(),
+                Some(YarnValue::from(10.0)),
+                YarnValue::from(15.0)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_command_handlers_are_called_instead_of_returning_commands() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: create_test_node(
+            "<<declare $seconds = 2.5>>
+<<wait {$seconds} \"for real\">>
+<<fade_out>>",
+        ),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    let received_parameters = Arc::new(Mutex::new(Vec::new()));
+    let received_parameters_clone = received_parameters.clone();
+    dialogue
+        .add_command_handler("wait", move |parameters: &[YarnValue]| {
+            received_parameters_clone
+                .lock()
+                .unwrap()
+                .push(parameters.to_vec());
+        })
+        .set_node("Start")
+        .unwrap();
+
+    let mut unhandled_commands = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            if let DialogueEvent::Command(command) = event {
+                unhandled_commands.push(command.name);
+            }
+        }
+    }
+
+    assert_eq!(
+        *received_parameters.lock().unwrap(),
+        vec![vec![YarnValue::from(2.5), YarnValue::from("for real")]]
+    );
+    assert_eq!(unhandled_commands, vec!["fade_out"]);
+}
+
+#[test]
+fn test_jumping_to_node_mid_dialogue() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: A
+---
+First line of A
+-> Go to B
+    <<jump B>>
+===
+title: B
+---
+First line of B
+===
+title: C
+---
+First line of C
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("A").unwrap();
+
+    let lines = |events: Vec<DialogueEvent>| -> Vec<String> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(lines(dialogue.next().unwrap()), vec!["First line of A"]);
+
+    dialogue.jump_to_node("C").unwrap();
+    assert_eq!(dialogue.current_node().as_deref(), Some("C"));
+    assert_eq!(lines(dialogue.next().unwrap()), vec!["First line of C"]);
+
+    // Also works while options are waiting to be selected
+    dialogue.set_node("A").unwrap();
+    let _ = dialogue.next().unwrap();
+    let _ = dialogue.next().unwrap();
+    assert!(dialogue.is_waiting_for_option_selection());
+    dialogue.jump_to_node("C").unwrap();
+    assert!(!dialogue.is_waiting_for_option_selection());
+    assert_eq!(lines(dialogue.next().unwrap()), vec!["First line of C"]);
+
+    assert!(dialogue.jump_to_node("D").is_err());
+    assert_eq!(dialogue.current_node().as_deref(), Some("C"));
+}
+
+#[test]
+fn test_listing_node_names() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello
+===
+title: Other
+---
+World
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let dialogue = TestBase::new().with_compilation(result).dialogue;
+
+    let mut node_names: Vec<_> = dialogue.node_names().unwrap().collect();
+    node_names.sort_unstable();
+    assert_eq!(node_names, vec!["Other", "Start"]);
+    assert!(dialogue.node_exists("Other"));
+    assert!(!dialogue.node_exists("Missing"));
+}
+
+#[test]
+fn test_reporting_lines_without_text() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello #line:hello
+Untranslated #line:untranslated
+-> Okay #line:okay
+==="
+        .to_string(),
+    };
+    let mut result = Compiler::new().add_file(file).compile().unwrap();
+    result
+        .string_table
+        .remove(&LineId::from("line:untranslated"));
+    let dialogue = TestBase::new().with_compilation(result).dialogue;
+
+    let missing = dialogue.lines_without_text();
+
+    assert_eq!(missing.len(), 1);
+    assert!(missing.contains(&LineId::from("line:untranslated")));
+}
+
+#[test]
+fn test_visit_counts() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+tracking: always
+---
+Hello
+===
+title: Untracked
+---
+World
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    assert!(!dialogue.has_visited("Start"));
+    assert_eq!(dialogue.visit_count("Start"), 0);
+
+    for _ in 0..2 {
+        dialogue.set_node("Start").unwrap();
+        while dialogue.next().is_some() {}
+    }
+    dialogue.set_node("Untracked").unwrap();
+    while dialogue.next().is_some() {}
+
+    assert!(dialogue.has_visited("Start"));
+    assert_eq!(dialogue.visit_count("Start"), 2);
+    assert!(!dialogue.has_visited("Untracked"));
+    assert_eq!(dialogue.visit_count("Missing"), 0);
+}
+
+#[test]
+fn test_coverage_reports_unvisited_branch() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Pick a pet. #line:question
+-> Dog #line:dog
+    Woof! #line:woof
+-> Cat #line:cat
+    Meow! #line:meow
+===
+title: Unused
+---
+Nobody sees this. #line:unused
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.enable_coverage_tracking();
+
+    dialogue.set_node("Start").unwrap();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            if let DialogueEvent::Options(options) = event {
+                let dog = options.iter().find(|o| o.line.text == "Dog").unwrap();
+                dialogue.set_selected_option(dog.id).unwrap();
+            }
+        }
+    }
+
+    let report = dialogue.coverage_report().unwrap();
+    assert_eq!(report.total_lines, 6);
+    assert_eq!(
+        report.uncovered_lines,
+        vec![LineId::from("line:meow"), LineId::from("line:unused")]
+    );
+    assert_eq!(report.uncovered_nodes, vec!["Unused".to_owned()]);
+    assert!((report.line_percentage() - 400.0 / 6.0).abs() < 0.001);
+}
+
+#[test]
+fn test_stepping_advances_one_instruction_at_a_time() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello
+World
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut expected_instruction_pointer = 0;
+    let mut lines = Vec::new();
+    loop {
+        let step = dialogue.step().unwrap();
+        lines.extend(step.events.iter().filter_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text.clone()),
+            _ => None,
+        }));
+        if step.events.contains(&DialogueEvent::DialogueComplete) {
+            break;
+        }
+        expected_instruction_pointer += 1;
+        assert_eq!(step.instruction_pointer, expected_instruction_pointer);
+        assert_eq!(step.node_name.as_deref(), Some("Start"));
+    }
+
+    assert_eq!(lines, vec!["Hello".to_owned(), "World".to_owned()]);
+    assert!(!dialogue.is_active());
+}
+
+#[test]
+fn test_inspecting_stack_while_stepping() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+The sum is {1 + 2}
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+    assert!(dialogue.debug_stack().is_empty());
+
+    while dialogue.debug_stack().is_empty() {
+        dialogue.step().unwrap();
+    }
+    assert_eq!(dialogue.debug_stack(), vec![YarnValue::Number(1.0)]);
+
+    dialogue.step().unwrap();
+    assert_eq!(
+        dialogue.debug_stack(),
+        vec![YarnValue::Number(1.0), YarnValue::Number(2.0)]
+    );
+}
+
+#[test]
+fn test_option_carries_condition_and_tags() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $gold = 5>>
+-> Buy the sword <<if $gold > 10>> #expensive
+-> Leave
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let options = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Options(options) => Some(options),
+            _ => None,
+        })
+        .unwrap();
+
+    assert!(!options[0].is_available);
+    assert_eq!(options[0].condition.as_deref(), Some("$gold > 10"));
+    assert_eq!(options[0].tags, vec!["expensive".to_owned()]);
+    assert!(options[1].is_available);
+    assert_eq!(options[1].condition, None);
+    assert!(options[1].tags.is_empty());
+}
+
+#[test]
+fn test_selecting_out_of_range_option_fails() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+-> A
+-> B
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+    let _ = dialogue.continue_().unwrap();
+    assert!(dialogue.is_waiting_for_option_selection());
+
+    let error = dialogue.set_selected_option(OptionId(2)).unwrap_err();
+
+    assert!(matches!(
+        error,
+        DialogueError::InvalidOptionIdError {
+            selected_option_id: OptionId(2),
+            max_id: 1,
+        }
+    ));
+    assert!(error.to_string().contains("between 0 and 1"));
+    // The options are still pending, so a valid selection works afterwards
+    assert!(dialogue.is_waiting_for_option_selection());
+    dialogue.set_selected_option(OptionId(1)).unwrap();
+}
+
+#[test]
+fn test_selecting_option_without_pending_options_fails() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+    let _ = dialogue.continue_().unwrap();
+
+    let error = dialogue.set_selected_option(OptionId(0)).unwrap_err();
+
+    assert!(matches!(
+        error,
+        DialogueError::UnexpectedOptionSelectionError
+    ));
+}
+
+#[test]
+fn test_last_line_before_options_is_flagged() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+First
+Second
+-> Option
+    Third
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => {
+                    lines.push((line.text, line.is_last_line_before_options));
+                }
+                DialogueEvent::Options(options) => {
+                    assert!(!options[0].line.is_last_line_before_options);
+                    dialogue.set_selected_option(options[0].id).unwrap();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    assert_eq!(
+        lines,
+        vec![
+            ("First".to_owned(), false),
+            ("Second".to_owned(), true),
+            ("Third".to_owned(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_custom_start_node() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Wrong node
+===
+title: Intro
+---
+Right node
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let dialogue = TestBase::new().with_compilation(result).dialogue;
+    assert_eq!(dialogue.start_node(), Dialogue::DEFAULT_START_NODE_NAME);
+    let mut dialogue = dialogue.with_start_node("Intro").unwrap();
+    assert!(matches!(
+        dialogue.set_start_node("Missing"),
+        Err(DialogueError::InvalidNode { .. })
+    ));
+    assert_eq!(dialogue.start_node(), "Intro");
+
+    dialogue.set_node_to_start().unwrap();
+    let first_line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text),
+            _ => None,
+        });
+
+    assert_eq!(first_line.as_deref(), Some("Right node"));
+}
+
+#[test]
+fn test_try_next_returns_runtime_errors() {
+    let mut compiler_library = Library::new();
+    compiler_library.add_function("missing_function", || 1.0_f32);
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+The answer is {missing_function()}
+==="
+        .to_string(),
+    };
+    let result = Compiler::new()
+        .add_file(file)
+        .extend_library(compiler_library)
+        .compile()
+        .unwrap();
+    // The dialogue's library does not contain `missing_function`
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let error = dialogue.try_next().unwrap_err();
+
+    assert!(matches!(
+        error,
+        DialogueError::FunctionNotFound { ref function_name, .. } if function_name == "missing_function"
+    ));
+}
+
+#[test]
+fn test_try_next_returns_none_when_dialogue_cannot_continue() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    assert_eq!(dialogue.try_next().unwrap(), None);
+
+    dialogue.set_node("Start").unwrap();
+    while dialogue.try_next().unwrap().is_some() {}
+    assert!(!dialogue.is_active());
+}
+
+fn dialogue_with_gold() -> Dialogue {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $gold = 0>>
+You have {$gold} gold.
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    TestBase::new().with_compilation(result).dialogue
+}
+
+#[test]
+fn test_evaluate_arithmetic_expression() {
+    let mut dialogue = dialogue_with_gold();
+    dialogue
+        .variable_storage_mut()
+        .set("$gold".to_owned(), 8.0.into())
+        .unwrap();
+
+    assert_eq!(
+        dialogue.evaluate_expression("$gold * 2 - 1").unwrap(),
+        YarnValue::Number(15.0)
+    );
+    assert_eq!(
+        dialogue.evaluate_expression("$gold + 5 > 10").unwrap(),
+        YarnValue::Boolean(true)
+    );
+    assert!(dialogue.debug_stack().is_empty());
+}
+
+#[test]
+fn test_evaluate_expression_with_function_call() {
+    let mut dialogue = dialogue_with_gold();
+    dialogue
+        .library_mut()
+        .add_function("double", |value: f32| value * 2.0);
+
+    // `$gold` is not set in the variable storage, so its initial value is used
+    assert_eq!(
+        dialogue.evaluate_expression("double($gold + 3)").unwrap(),
+        YarnValue::Number(6.0)
+    );
+}
+
+#[test]
+fn test_evaluate_expression_with_undefined_variable() {
+    let mut dialogue = dialogue_with_gold();
+
+    let error = dialogue.evaluate_expression("$silver + 1").unwrap_err();
+
+    assert!(matches!(
+        error,
+        ExpressionError::Evaluation(DialogueError::VariableStorageError(
+            VariableStorageError::VariableNotFound { ref name }
+        )) if name == "$silver"
+    ));
+}
+
+#[test]
+fn test_evaluate_expression_rejects_statements() {
+    let mut dialogue = dialogue_with_gold();
+
+    let error = dialogue
+        .evaluate_expression("1)>> <<set $gold to (2")
+        .unwrap_err();
+
+    assert!(matches!(error, ExpressionError::Compilation(_)));
+    assert!(error.to_string().contains("is not a single expression"));
+    assert_eq!(
+        dialogue.evaluate_expression("($gold + 1) * 2").unwrap(),
+        YarnValue::Number(2.0)
+    );
+}
+
+#[test]
+fn test_smart_variable_tracks_its_dependencies() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $gold = 50>>
+<<if $is_rich>>
+    Rich
+<<else>>
+    Poor
+<<endif>>
+==="
+        .to_string(),
+    };
+    let result = Compiler::new()
+        .add_file(file)
+        .declare_variable(
+            Declaration::new("$is_rich", Type::Any).with_smart_expression("$gold > 100"),
+        )
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+
+    let first_line = |dialogue: &mut Dialogue| -> String {
+        dialogue.set_node("Start").unwrap();
+        dialogue
+            .continue_()
+            .unwrap()
+            .into_iter()
+            .find_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .unwrap()
+    };
+    assert_eq!(first_line(&mut dialogue), "Poor");
+    assert_eq!(
+        dialogue.evaluate_expression("$is_rich").unwrap(),
+        YarnValue::Boolean(false)
+    );
+
+    dialogue
+        .variable_storage_mut()
+        .set("$gold".to_owned(), 150.0.into())
+        .unwrap();
+    assert_eq!(first_line(&mut dialogue), "Rich");
+    assert_eq!(
+        dialogue.evaluate_expression("$is_rich").unwrap(),
+        YarnValue::Boolean(true)
+    );
+}
+
+#[test]
+fn test_once_block_only_runs_on_first_visit() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<once>>
+Nice to meet you!
+<<endonce>>
+Hello
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+
+    let mut visit = || {
+        dialogue.set_node("Start").unwrap();
+        let mut lines = Vec::new();
+        while let Some(events) = dialogue.next() {
+            lines.extend(events.into_iter().filter_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            }));
+        }
+        lines
+    };
+
+    assert_eq!(visit(), vec!["Nice to meet you!", "Hello"]);
+    assert_eq!(visit(), vec!["Hello"]);
+}
+
+#[test]
+fn test_unclosed_once_block_is_an_error() {
+    let result = Compiler::from_test_source("<<once>>\nNice to meet you!\n").compile();
+
+    let diagnostics = result.unwrap_err().0;
+    assert!(diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.message.contains("missing a closing <<endonce>>")));
+}
+
+#[test]
+fn test_once_block_crossing_if_is_an_error() {
+    let result = Compiler::from_test_source(
+        "<<if true>>\n<<once>>\nNice to meet you!\n<<endif>>\n<<endonce>>\n",
+    )
+    .compile();
+
+    let diagnostics = result.unwrap_err().0;
+    assert!(diagnostics.iter().any(|diagnostic| diagnostic
+        .message
+        .contains("before the end of the <<if>> clause or option")));
+    assert!(diagnostics.iter().any(|diagnostic| diagnostic
+        .message
+        .contains("<<endonce>> without a matching <<once>>")));
+}
+
+#[test]
+fn test_endonce_in_option_of_outer_once_block_is_an_error() {
+    let result =
+        Compiler::from_test_source("<<once>>\n-> Hi\n    <<endonce>>\n-> Bye\n<<endonce>>\n")
+            .compile();
+
+    let diagnostics = result.unwrap_err().0;
+    assert!(diagnostics.iter().any(|diagnostic| diagnostic
+        .message
+        .contains("different <<if>> clause or option than its <<once>>")));
+}
+
+#[test]
+fn test_once_variables_are_numbered_within_their_node() {
+    let source = |preamble: &str| {
+        format!("title: Start\n---\n{preamble}<<once>>\nA\n<<endonce>>\n<<once>>\nB\n<<endonce>>\n===\n")
+    };
+    let once_variables = |source: String| -> Vec<String> {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source,
+        };
+        let mut names: Vec<_> = Compiler::new()
+            .add_file(file)
+            .compile()
+            .unwrap()
+            .declarations
+            .into_iter()
+            .map(|declaration| declaration.name)
+            .filter(|name| name.starts_with("$Yarn.Internal.Once."))
+            .collect();
+        names.sort();
+        names
+    };
+
+    let expected = [
+        Library::generate_unique_once_variable("Start", 0),
+        Library::generate_unique_once_variable("Start", 1),
+    ];
+    assert_eq!(once_variables(source("")), expected);
+    // Adding lines before the blocks does not rename their variables, so existing saves keep working
+    assert_eq!(once_variables(source("Hello\nHow are you?\n")), expected);
+}
+
+#[test]
+fn test_jump_to_expression() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $next_node = \"Start\">>
+<<set $next_node to \"End\">>
+<<jump {$next_node}>>
+===
+title: End
+---
+Reached the end
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    while let Some(events) = dialogue.next() {
+        lines.extend(events.into_iter().filter_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text),
+            _ => None,
+        }));
+    }
+
+    assert_eq!(lines, vec!["Reached the end"]);
+}
+
+#[test]
+fn test_jump_to_expression_naming_missing_node_is_an_error() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $next_node = \"Nowhere\">>
+<<jump {$next_node}>>
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let error = dialogue.continue_().unwrap_err();
+    assert!(matches!(
+        error,
+        DialogueError::InvalidNode { node_name } if node_name == "Nowhere"
+    ));
+}
+
+#[test]
+fn test_detour_returns_to_caller() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Before the detour
+<<detour Shop>>
+After the detour
+===
+title: Shop
+---
+Welcome to the shop
+<<detour Greeting>>
+<<return>>
+This line is skipped
+===
+title: Greeting
+---
+Nice to see you
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    let mut completed = false;
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => lines.push(line.text),
+                DialogueEvent::DialogueComplete => completed = true,
+                _ => {}
             }
         }
     }
+
+    assert!(completed);
+    assert_eq!(
+        lines,
+        vec![
+            "Before the detour",
+            "Welcome to the shop",
+            "Nice to see you",
+            "After the detour"
+        ]
+    );
+}
+
+#[test]
+fn test_stop_inside_detour_ends_dialogue() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Before the detour
+<<detour Shop>>
+This line is skipped
+===
+title: Shop
+---
+Welcome to the shop
+<<stop>>
+This line is skipped too
+==="
+        .to_string(),
+    };
+    let result = Compiler::new().add_file(file).compile().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    let mut completed = false;
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => lines.push(line.text),
+                DialogueEvent::DialogueComplete => completed = true,
+                _ => {}
+            }
+        }
+    }
+
+    assert!(completed);
+    assert!(!dialogue.is_active());
+    assert_eq!(lines, vec!["Before the detour", "Welcome to the shop"]);
+}
+
+#[test]
+fn test_detour_without_destination_is_an_error() {
+    let result = Compiler::from_test_source("<<detour>>\n").compile();
+
+    let diagnostics = result.unwrap_err().0;
+    assert!(diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.message.contains("<<detour>> expects")));
 }
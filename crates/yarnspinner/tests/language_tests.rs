@@ -47,7 +47,6 @@ fn can_compile_space_demo() {
 }
 
 #[test]
-#[should_panic]
 fn test_merging_nodes() {
     let test_base = TestBase::default();
     let sally_path = space_demo_scripts_path().join("Sally.yarn");
@@ -65,11 +64,18 @@ fn test_merging_nodes() {
         .compile()
         .unwrap();
 
-    // Loading code with the same contents should throw
-    let _combined_not_working = Program::combine(vec![
-        result_sally.program.unwrap(),
-        result_sally_and_ship.program.unwrap(),
-    ]);
+    let program_sally = result_sally.program.unwrap();
+    let mut sally_node_names: Vec<_> = program_sally.nodes.keys().cloned().collect();
+    sally_node_names.sort();
+
+    // Loading code with the same contents should fail
+    let result = Program::combine(vec![program_sally, result_sally_and_ship.program.unwrap()]);
+    assert_eq!(
+        result.unwrap_err(),
+        ProgramCombineError::DuplicateNodeNames {
+            node_names: sally_node_names
+        }
+    );
 }
 
 #[test]
@@ -210,6 +216,45 @@ fn test_sources() {
     }
 }
 
+#[test]
+fn test_plan_comments_and_set_directives() {
+    let path = own_test_data_path().join("TestPlanDirectives.yarn");
+    let test_plan = TestPlan::read(path.with_extension("testplan"));
+    assert_eq!(
+        test_plan.initial_variables,
+        vec![
+            ("$name".to_owned(), YarnValue::from("Alice")),
+            ("$gold".to_owned(), YarnValue::from(15.0)),
+            ("$met".to_owned(), YarnValue::from(true)),
+        ]
+    );
+
+    let result = Compiler::new().read_file(&path).compile().unwrap();
+    TestBase::new()
+        .with_test_plan(test_plan)
+        .with_compilation(result)
+        .run_standard_testcase();
+}
+
+#[test]
+fn test_smart_variables_declared_in_yarn() {
+    let path = own_test_data_path().join("SmartVariables.yarn");
+    let test_plan = path.with_extension("testplan");
+
+    let result = Compiler::new().read_file(&path).compile().unwrap();
+    let is_rich = result
+        .declarations
+        .iter()
+        .find(|declaration| declaration.name == "$is_rich")
+        .unwrap();
+    assert_eq!(is_rich.smart_expression.as_deref(), Some("$gold > 100"));
+
+    TestBase::new()
+        .read_test_plan(test_plan)
+        .with_compilation(result)
+        .run_standard_testcase();
+}
+
 #[test]
 #[should_panic]
 fn crashes_on_command_expression_evaluating_whitespace() {
@@ -0,0 +1,74 @@
+use test_base::prelude::*;
+use yarnspinner::compiler::*;
+use yarnspinner::core::*;
+use yarnspinner::runtime::*;
+
+mod test_base;
+
+#[test]
+fn test_program_survives_binary_round_trip() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+<<declare $visits = 0>>
+<<set $visits to $visits + 1>>
+Hello there! #line:hello
+-> Hi! #line:hi
+    <<jump Other>>
+-> Bye! #line:bye
+===
+title: Other
+---
+You have visited {$visits} time. #line:visits
+==="
+        .to_string(),
+    };
+    let compilation = Compiler::new().add_file(file).compile().unwrap();
+    let program = compilation.program.clone().unwrap();
+
+    let bytes = program.to_bytes();
+    let loaded_program = Program::from_bytes(&bytes).unwrap();
+    assert_eq!(program, loaded_program);
+
+    let mut test_base = TestBase::new()
+        .with_program(loaded_program)
+        .with_string_table(compilation.string_table);
+    let dialogue = &mut test_base.dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    let mut completed = false;
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => lines.push(line.text),
+                DialogueEvent::Options(options) => {
+                    dialogue.set_selected_option(options[0].id).unwrap();
+                }
+                DialogueEvent::DialogueComplete => completed = true,
+                _ => {}
+            }
+        }
+    }
+
+    assert!(completed);
+    assert_eq!(lines, vec!["Hello there!", "You have visited 1 time."]);
+}
+
+#[test]
+fn test_program_with_different_binary_version_is_rejected() {
+    let mut bytes = Program::default().to_bytes();
+    bytes[4..8].copy_from_slice(&(Program::BINARY_FORMAT_VERSION + 1).to_le_bytes());
+
+    let result = Program::from_bytes(&bytes);
+
+    assert!(matches!(
+        result,
+        Err(ProgramDecodeError::UnsupportedVersion { .. })
+    ));
+    assert!(matches!(
+        Program::from_bytes(b"not a program"),
+        Err(ProgramDecodeError::NotAProgram)
+    ));
+}
@@ -218,7 +218,7 @@ before 🧑🏾‍❤️‍💋‍🧑🏻after #line:abc130 // with a comment
 
     for (tag, line) in expected_results
         .iter()
-        .map(|(tag, line)| (tag.map(|s| LineId(s.to_string())), line.to_string()))
+        .map(|(tag, line)| (tag.map(|s| LineId::from(s.to_string())), line.to_string()))
     {
         println!("checking tag: {:#?} line: {:#?}", tag, line);
         if let Some(tag) = tag {
@@ -245,16 +245,16 @@ before 🧑🏾‍❤️‍💋‍🧑🏻after #line:abc130 // with a comment
                 .iter()
                 .filter(|(_, v)| v.text == line)
                 .filter(|(k, _)| !visited_ids.contains(k))
-                .filter(|(k, _)| expected_results.iter().all(|(t, _)| *t != Some(&k.0)))
+                .filter(|(k, _)| expected_results.iter().all(|(t, _)| *t != Some(k.as_str())))
                 .collect::<Vec<_>>();
 
             // that line has a line tag
             for (line_tag, _) in matching_entries {
-                assert!(line_tag.0.starts_with("line:"));
+                assert!(line_tag.as_str().starts_with("line:"));
 
                 // that line is not a duplicate of any other line tag
                 let all_line_tags = compilation.string_table.keys();
-                assert_eq!(all_line_tags.filter(|t| t.0 == line_tag.0).count(), 1);
+                assert_eq!(all_line_tags.filter(|t| *t == line_tag).count(), 1);
 
                 // flagging this ID as having been visited
                 visited_ids.insert(line_tag.clone());
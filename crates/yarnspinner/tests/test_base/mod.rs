@@ -61,17 +61,10 @@ impl Default for TestBase {
         let variable_storage = MemoryVariableStorage::new();
         let string_table = SharedTextProvider::new(StringTableTextProvider::new());
 
-        let mut dialogue = Dialogue::new(
+        let dialogue = Dialogue::new(
             Box::new(variable_storage.clone()),
             Box::new(string_table.clone()),
         );
-        dialogue
-            .library_mut()
-            .add_function("assert", |value: YarnValue| {
-                let is_truthy: bool = value.try_into().unwrap();
-                assert!(is_truthy);
-                true
-            });
 
         Self {
             dialogue,
@@ -97,6 +90,12 @@ impl TestBase {
 
     #[must_use]
     pub fn with_test_plan(mut self, test_plan: TestPlan) -> Self {
+        for (name, value) in &test_plan.initial_variables {
+            self.dialogue
+                .variable_storage_mut()
+                .set(name.clone(), value.clone())
+                .unwrap();
+        }
         self.test_plan.replace(test_plan);
         self
     }
@@ -235,6 +234,7 @@ impl TestBase {
                     DialogueEvent::NodeComplete(_) => {}
                     DialogueEvent::NodeStart(_) => {}
                     DialogueEvent::LineHints(_) => {}
+                    DialogueEvent::VariableChanged { .. } => {}
                     DialogueEvent::DialogueComplete => {
                         let Some(test_plan) = self.test_plan.as_mut() else {
                             continue;
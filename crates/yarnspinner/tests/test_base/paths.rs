@@ -18,6 +18,11 @@ pub fn test_data_path() -> PathBuf {
     project_root_path().join("../../third-party/YarnSpinner/Tests")
 }
 
+/// Test data written for this port, as opposed to the data shared with the original implementation.
+pub fn own_test_data_path() -> PathBuf {
+    project_root_path().join("tests/test_data")
+}
+
 pub fn space_demo_scripts_path() -> PathBuf {
     test_data_path().join("Projects/Space")
 }
@@ -3,12 +3,15 @@
 use crate::prelude::*;
 use std::fs;
 use std::path::Path;
+use yarnspinner::core::YarnValue;
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct TestPlan {
     pub next_expected_step: ExpectedStepType,
     pub next_expected_options: Vec<ProcessedOption>,
     pub next_step_value: Option<StepValue>,
+    /// Variables to store before running the dialogue, given by `set $name = value` directives.
+    pub initial_variables: Vec<(String, YarnValue)>,
     steps: Vec<Step>,
     current_test_plan_step: usize,
 }
@@ -25,19 +28,24 @@ impl TestPlan {
     }
 
     pub fn read(path: impl AsRef<Path>) -> Self {
-        let steps = fs::read_to_string(path)
-            .unwrap()
+        let mut test_plan = Self::default();
+        let source = fs::read_to_string(path).unwrap();
+        let lines = source
             .lines()
             // Skip commented lines
             .filter(|line| !line.trim_start().starts_with('#'))
             // Skip empty or blank lines
-            .filter(|line| !line.trim().is_empty())
-            .map(Step::read)
-            .collect();
-        Self {
-            steps,
-            ..Default::default()
+            .filter(|line| !line.trim().is_empty());
+        for line in lines {
+            if let Some(directive) = line.trim_start().strip_prefix("set ") {
+                test_plan
+                    .initial_variables
+                    .push(read_set_directive(directive));
+            } else {
+                test_plan.steps.push(Step::read(line));
+            }
         }
+        test_plan
     }
 
     pub fn next(&mut self) {
@@ -101,3 +109,29 @@ impl TestPlan {
         self
     }
 }
+
+/// Reads the `$name = value` part of a `set` directive. The value is a number, `true`, `false` or a string in double quotes.
+fn read_set_directive(directive: &str) -> (String, YarnValue) {
+    let (name, value) = directive
+        .split_once('=')
+        .unwrap_or_else(|| panic!("Expected '=' in set directive: {directive}"));
+    let name = name.trim();
+    assert!(
+        name.starts_with('$'),
+        "Expected variable name starting with '$' in set directive: {directive}"
+    );
+    let value = value.trim();
+    let value = if let Some(string) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        YarnValue::from(string)
+    } else if let Ok(boolean) = value.parse::<bool>() {
+        YarnValue::from(boolean)
+    } else if let Ok(number) = value.parse::<f32>() {
+        YarnValue::from(number)
+    } else {
+        panic!("Invalid value in set directive: {directive}")
+    };
+    (name.to_owned(), value)
+}
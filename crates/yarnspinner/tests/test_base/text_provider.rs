@@ -30,6 +30,10 @@ impl TextProvider for SharedTextProvider {
         self.0.read().unwrap().get_text(id)
     }
 
+    fn with_text(&self, id: &LineId, f: &mut dyn FnMut(&str)) {
+        self.0.read().unwrap().with_text(id, f);
+    }
+
     fn set_language(&mut self, language: Option<Language>) {
         self.0.write().unwrap().set_language(language);
     }
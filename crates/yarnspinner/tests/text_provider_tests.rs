@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use yarnspinner::compiler::*;
+use yarnspinner::core::*;
+use yarnspinner::runtime::*;
+
+/// Resolves to its text once its delay has passed, waking the task from a background thread.
+struct Delayed {
+    text: Option<String>,
+    ready_at: Instant,
+    timer_started: bool,
+}
+
+impl Future for Delayed {
+    type Output = Option<String>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        if Instant::now() >= self.ready_at {
+            return Poll::Ready(self.text.take());
+        }
+        if !self.timer_started {
+            self.timer_started = true;
+            let waker = context.waker().clone();
+            let ready_at = self.ready_at;
+            std::thread::spawn(move || {
+                std::thread::sleep(ready_at.saturating_duration_since(Instant::now()));
+                waker.wake();
+            });
+        }
+        Poll::Pending
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockCdn {
+    lines: HashMap<LineId, String>,
+    requests: Arc<Mutex<Vec<LineId>>>,
+}
+
+impl AsyncTextProvider for MockCdn {
+    fn get_text(&self, id: &LineId, _language: Option<&Language>) -> TextFuture {
+        self.requests.lock().unwrap().push(id.clone());
+        Box::pin(Delayed {
+            text: self.lines.get(id).cloned(),
+            ready_at: Instant::now() + Duration::from_millis(20),
+            timer_started: false,
+        })
+    }
+}
+
+#[test]
+fn test_async_text_provider_resolves_lines() {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: "title: Start
+---
+Hello from the CDN #line:hello
+-> Thanks #line:thanks
+==="
+        .to_string(),
+    };
+    let compilation = Compiler::new().add_file(file).compile().unwrap();
+    let cdn = MockCdn {
+        lines: compilation
+            .string_table
+            .into_iter()
+            .map(|(id, info)| (id, info.text))
+            .collect(),
+        ..Default::default()
+    };
+    let requests = cdn.requests.clone();
+    let mut dialogue = Dialogue::new(
+        Box::new(MemoryVariableStorage::new()),
+        Box::new(AsyncTextProviderAdapter::new(cdn)),
+    );
+    dialogue
+        .set_line_hints_enabled(true)
+        .add_program(compilation.program.unwrap());
+    dialogue.set_node("Start").unwrap();
+
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert!(!dialogue.text_provider().are_lines_available());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !dialogue.text_provider().are_lines_available() {
+        assert!(Instant::now() < deadline, "Lines never became available");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let mut texts = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            match event {
+                DialogueEvent::Line(line) => texts.push(line.text),
+                DialogueEvent::Options(options) => {
+                    texts.extend(options.iter().map(|option| option.line.text.clone()));
+                    dialogue.set_selected_option(options[0].id).unwrap();
+                }
+                _ => {}
+            }
+        }
+    }
+    assert_eq!(texts, vec!["Hello from the CDN", "Thanks"]);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_async_text_provider_blocks_for_unhinted_lines() {
+    let mut adapter = AsyncTextProviderAdapter::new(MockCdn {
+        lines: HashMap::from([(LineId::from("line:late"), "Worth the wait".to_owned())]),
+        ..Default::default()
+    });
+    adapter.accept_line_hints(&[]);
+
+    assert_eq!(
+        adapter.get_text(&LineId::from("line:late")),
+        Some("Worth the wait".to_owned())
+    );
+    assert_eq!(adapter.get_text(&LineId::from("line:unknown")), None);
+}
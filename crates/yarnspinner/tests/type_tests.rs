@@ -587,6 +587,41 @@ fn test_type_conversion_failure_to_bool() {
     test_base.with_compilation(result).run_standard_testcase();
 }
 
+#[test]
+#[should_panic = "x must be positive"]
+fn test_assert_reports_custom_message() {
+    let source = "<<set $x to -1>>\n{assert($x > 0, \"x must be positive\")}";
+    let test_base =
+        TestBase::new().with_test_plan(TestPlan::new().expect_line("test failure if seen"));
+    let result = Compiler::from_test_source(source)
+        .extend_library(test_base.dialogue.library().clone())
+        .compile()
+        .unwrap();
+    test_base.with_compilation(result).run_standard_testcase();
+}
+
+#[test]
+fn test_assert_message_is_optional() {
+    let source = "{assert(true)} {assert(1 < 2, \"unused\")}";
+    let test_base = TestBase::new().with_test_plan(TestPlan::new().expect_line("true true"));
+    let result = Compiler::from_test_source(source)
+        .extend_library(test_base.dialogue.library().clone())
+        .compile()
+        .unwrap();
+    test_base.with_compilation(result).run_standard_testcase();
+}
+
+#[test]
+fn test_assert_rejects_too_many_arguments() {
+    let source = "{assert(true, \"a\", \"b\")}";
+    let result = Compiler::from_test_source(source).compile().unwrap_err();
+    assert!(result
+        .0
+        .iter()
+        .any(|d| d.message
+            == "Function \"assert\" expects between 1 and 2 parameters, but received 3"));
+}
+
 #[test]
 fn test_implicit_function_declarations() {
     let source = "
@@ -0,0 +1,69 @@
+use yarnspinner::compiler::*;
+use yarnspinner::core::*;
+use yarnspinner::runtime::*;
+
+const SOURCE: &str = "title: Start
+---
+<<declare $gold = 0>>
+<<set $gold to $gold + 5>>
+You have {$gold} gold. #line:gold
+===
+";
+
+fn dialogue_with(variable_storage: Box<dyn VariableStorage>) -> Dialogue {
+    let file = File {
+        file_name: "test.yarn".to_string(),
+        source: SOURCE.to_string(),
+    };
+    let compilation = Compiler::new().add_file(file).compile().unwrap();
+    let mut text_provider = StringTableTextProvider::new();
+    text_provider.extend_base_language(
+        compilation
+            .string_table
+            .into_iter()
+            .map(|(id, info)| (id, info.text))
+            .collect(),
+    );
+    let mut dialogue = Dialogue::new(variable_storage, Box::new(text_provider));
+    dialogue.add_program(compilation.program.unwrap());
+    dialogue.set_node("Start").unwrap();
+    dialogue
+}
+
+#[test]
+fn test_read_only_storage_ignoring_writes_keeps_variables_unchanged() {
+    let mut variables = MemoryVariableStorage::new();
+    variables.set("$gold".to_owned(), 10.0.into()).unwrap();
+    let storage = ReadOnlyVariableStorage::new(variables.clone()).ignoring_writes();
+    let mut dialogue = dialogue_with(Box::new(storage));
+
+    let mut lines = Vec::new();
+    while let Some(events) = dialogue.next() {
+        for event in events {
+            if let DialogueEvent::Line(line) = event {
+                lines.push(line.text);
+            }
+        }
+    }
+
+    assert_eq!(lines, vec!["You have 10 gold."]);
+    assert_eq!(variables.get("$gold").unwrap(), YarnValue::from(10.0));
+}
+
+#[test]
+fn test_read_only_storage_rejects_set_statements() {
+    let mut variables = MemoryVariableStorage::new();
+    variables.set("$gold".to_owned(), 10.0.into()).unwrap();
+    let storage = ReadOnlyVariableStorage::new(variables.clone());
+    let mut dialogue = dialogue_with(Box::new(storage));
+
+    let result = dialogue.continue_();
+
+    assert!(matches!(
+        result,
+        Err(DialogueError::VariableStorageError(
+            VariableStorageError::ReadOnly { .. }
+        ))
+    ));
+    assert_eq!(variables.get("$gold").unwrap(), YarnValue::from(10.0));
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_yarnspinner::events::{DialogueCompleteEvent, PresentLineEvent, PresentOptionsEvent};
+use bevy_yarnspinner::prelude::*;
+
+// A minimal dialogue view without any UI: lines and options are logged to the console,
+// the space bar advances the dialogue and the number keys select an option.
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins,
+        YarnSpinnerPlugin::with_yarn_source(YarnFileSource::file("dialogue/hello_world.yarn")),
+    ))
+    .add_systems(
+        Update,
+        (
+            // Spawn the dialogue runner once the Yarn project has finished compiling
+            spawn_dialogue_runner.run_if(resource_added::<YarnProject>),
+            // Read the events sent by the dialogue runner in the same frame they are sent
+            print_dialogue.after(YarnSpinnerSystemSet),
+            advance_dialogue_on_key_press,
+        ),
+    )
+    .run();
+}
+
+fn spawn_dialogue_runner(mut commands: Commands, project: Res<YarnProject>) {
+    let mut dialogue_runner = project.create_dialogue_runner();
+    dialogue_runner.start_node("HelloWorld");
+    commands.spawn(dialogue_runner);
+}
+
+fn print_dialogue(
+    mut present_line_events: EventReader<PresentLineEvent>,
+    mut present_options_events: EventReader<PresentOptionsEvent>,
+    mut dialogue_complete_events: EventReader<DialogueCompleteEvent>,
+) {
+    for event in present_line_events.read() {
+        info!("{} (press space to continue)", event.line.text);
+    }
+    for event in present_options_events.read() {
+        for (index, option) in event.options.iter().enumerate() {
+            info!("[{}] {}", index + 1, option.line.text);
+        }
+    }
+    for _event in dialogue_complete_events.read() {
+        info!("The dialogue is over.");
+    }
+}
+
+fn advance_dialogue_on_key_press(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut dialogue_runners: Query<&mut DialogueRunner>,
+) {
+    const OPTION_KEYS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for mut dialogue_runner in dialogue_runners.iter_mut() {
+        if !dialogue_runner.is_running() {
+            continue;
+        }
+        if dialogue_runner.is_waiting_for_option_selection() {
+            let Some(index) = OPTION_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+                continue;
+            };
+            // Options are numbered in the order they were presented, which matches their IDs
+            if let Err(error) = dialogue_runner.select_option(OptionId(index)) {
+                warn!("{error}");
+            }
+        } else if keys.just_pressed(KeyCode::Space) {
+            dialogue_runner.continue_in_next_update();
+        }
+    }
+}